@@ -0,0 +1,184 @@
+use enum_iterator::all;
+
+use crate::{
+	components::pattern::TileColor,
+	export::{ExportSize, LengthUnit},
+	theme::Theme,
+	tiling::{Tiling, Vertex}
+};
+
+/// Width of the outline that tiles are drawn with in their own color, so that
+/// no background shows through between adjacent tiles.
+pub const SEAM_STROKE_WIDTH: f32 = 0.05;
+
+/// The most repetitions in either direction that fitting chooses.
+pub const MAX_REPETITIONS: usize = 20;
+
+/// How the number of repetitions of an export is chosen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepeatFit {
+	/// Both counts are set by hand.
+	Manual,
+	/// The vertical count is set by hand and the horizontal one approximates
+	/// the aspect ratio `width / height`.
+	AspectRatio { width: f32, height: f32 },
+	/// As many repetitions as cover an area of the given size, with grid
+	/// squares of `square_size`. All lengths are in mm.
+	Size {
+		width: f32,
+		height: f32,
+		square_size: f32
+	}
+}
+
+impl RepeatFit {
+	/// Every kind of fit, with default values.
+	pub const ALL: [Self; 3] = [
+		Self::Manual,
+		Self::AspectRatio {
+			width: 16.0,
+			height: 9.0
+		},
+		Self::Size {
+			width: 1000.0,
+			height: 1000.0,
+			square_size: 100.0
+		}
+	];
+
+	pub fn label(&self) -> &'static str {
+		match self {
+			Self::Manual => "Manuell",
+			Self::AspectRatio { .. } => "Seitenverhältnis",
+			Self::Size { .. } => "Größe"
+		}
+	}
+
+	/// The horizontal and vertical repetitions, given the counts set by hand.
+	pub fn repetitions(&self, tiling: &Tiling, reps_x: usize, reps_y: usize) -> (usize, usize) {
+		let (rep_width, rep_height) = (tiling.viewport_width(), tiling.viewport_height());
+		let fit = |count: f32| (count as usize).clamp(1, MAX_REPETITIONS);
+		match *self {
+			Self::Manual => (reps_x, reps_y),
+			Self::AspectRatio { width, height } => {
+				let reps_x = width / height * reps_y as f32 * rep_height / rep_width;
+				(fit(reps_x.round()), reps_y)
+			}
+			Self::Size {
+				width,
+				height,
+				square_size
+			} => {
+				// Tolerate rounding errors, so that exact multiples don't get
+				// an additional repetition.
+				let cover =
+					|length: f32, rep: f32| fit((length / (rep * square_size) - 1e-4).ceil());
+				(cover(width, rep_width), cover(height, rep_height))
+			}
+		}
+	}
+
+	/// The export size that a [`RepeatFit::Size`] implies for the given
+	/// repetitions: the area they cover in mm, so that grid squares come out at
+	/// `square_size`. Other fits leave the export size to the user.
+	pub fn export_size(&self, tiling: &Tiling, reps_x: usize, reps_y: usize) -> Option<ExportSize> {
+		let Self::Size { square_size, .. } = *self else {
+			return None;
+		};
+		Some(ExportSize {
+			unit: LengthUnit::Mm,
+			width: Some(tiling.viewport_width() * reps_x as f32 * square_size),
+			height: Some(tiling.viewport_height() * reps_y as f32 * square_size),
+			..ExportSize::default()
+		})
+	}
+}
+
+/// A pattern as it is exported: the tiling repeated `reps_x` times
+/// horizontally and `reps_y` times vertically, in the colors of a theme.
+/// Coordinates are in tiling units with the y axis pointing down, as in SVG.
+#[derive(Debug, Clone, Copy)]
+pub struct Artwork<'a> {
+	pub tiling: &'a Tiling,
+	pub colors: &'a [TileColor],
+	pub theme: Theme,
+	pub reps_x: usize,
+	pub reps_y: usize
+}
+
+impl<'a> Artwork<'a> {
+	pub fn width(&self) -> f32 {
+		self.tiling.viewport_width() * self.reps_x as f32
+	}
+
+	pub fn height(&self) -> f32 {
+		self.tiling.viewport_height() * self.reps_y as f32
+	}
+
+	/// The tile colors that occur in the artwork, in declaration order. Empty
+	/// tiles are filled with the background, so it always occurs.
+	pub fn used_colors(&self) -> Vec<TileColor> {
+		all::<TileColor>()
+			.filter(|color| *color == TileColor::None || self.colors.contains(color))
+			.collect()
+	}
+
+	/// The outlines of all tiles in all repetitions, with their colors.
+	pub fn tiles(&self) -> impl Iterator<Item = (TileColor, Vec<Vertex>)> + 'a {
+		let tiling = self.tiling;
+		let colors = self.colors;
+		let (reps_x, reps_y) = (self.reps_x, self.reps_y);
+		let (width, height) = (tiling.viewport_width(), tiling.viewport_height());
+		(0..reps_y)
+			.flat_map(move |rep_y| (0..reps_x).map(move |rep_x| (rep_x, rep_y)))
+			.flat_map(move |(rep_x, rep_y)| {
+				let offset = Vertex::new(rep_x as f32 * width, rep_y as f32 * height);
+				tiling.iter_tiles().enumerate().map(move |(i, shape)| {
+					let vertices = shape
+						.vertices()
+						.iter()
+						.map(|v| Vertex::new(v.x + offset.x, v.y + offset.y))
+						.collect();
+					(colors.get(i).copied().unwrap_or_default(), vertices)
+				})
+			})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tiling::TilingFormat;
+
+	use super::*;
+
+	const SIZE: RepeatFit = RepeatFit::Size {
+		width: 1000.0,
+		height: 1000.0,
+		square_size: 100.0
+	};
+
+	#[test]
+	fn covers_size() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		assert_eq!(SIZE.repetitions(&tiling, 1, 1), (2, 2));
+		let fit = RepeatFit::Size {
+			width: 1001.0,
+			height: 400.0,
+			square_size: 100.0
+		};
+		assert_eq!(fit.repetitions(&tiling, 1, 1), (3, 1));
+	}
+
+	#[test]
+	fn sizes_export_in_millimetres() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let (reps_x, reps_y) = SIZE.repetitions(&tiling, 1, 1);
+		let size = SIZE.export_size(&tiling, reps_x, reps_y).unwrap();
+		assert_eq!(size.unit, LengthUnit::Mm);
+		assert_eq!((size.width, size.height), (Some(1000.0), Some(1000.0)));
+		let (points_x, points_y) = size.points(1.0);
+		assert!((points_x - 1000.0 / 25.4 * 72.0).abs() < 0.01);
+		assert!((points_y - 1000.0 / 25.4 * 72.0).abs() < 0.01);
+		assert_eq!(RepeatFit::Manual.export_size(&tiling, 2, 2), None);
+	}
+}
@@ -0,0 +1,569 @@
+use std::{
+	collections::HashSet,
+	env,
+	error::Error,
+	fs,
+	path::{Path, PathBuf},
+	process::ExitCode
+};
+
+use enum_iterator::all;
+use goldbeck_pattern::{
+	artwork::Artwork,
+	components::share_link::FRAGMENT_PREFIX,
+	export::{write_artwork, ExportOptions, LengthUnit, OutputFormat},
+	pattern_file::{PatternFile, EXTENSION},
+	share::SharedPattern,
+	svg_import::{import_svg, import_svg_as, ImportedSvg, SvgImportError},
+	theme::Theme,
+	tiling::Tiling
+};
+
+const USAGE: &str = "\
+Renders Goldbeck patterns without the browser.
+
+Usage: goldbeck-pattern-cli [OPTIONS] <INPUT>...
+
+Inputs are pattern files (.gbpattern), exported SVGs, share codes or share
+links. Directories are rendered in batch, every pattern file and SVG in them.
+
+Options:
+  -f, --format <FORMATS>  Comma-separated output formats: svg, pdf, png, ai,
+                          eps or dxf [default: svg]
+  -t, --theme <THEME>     bubbles, icy, glisten or noble [default: the
+                          pattern's theme]
+  -x, --reps-x <COUNT>    Horizontal repetitions [default: 1]
+  -y, --reps-y <COUNT>    Vertical repetitions [default: 1]
+      --width <LENGTH>    Width, e.g. 1920px, 210mm, 20cm or 8in
+      --height <LENGTH>   Height [default: 1000px, if no width is given]
+      --dpi <DPI>         Resolution between pixels and physical units
+                          [default: 300]
+  -o, --output <PATH>     Output file, or directory if there are several
+                          outputs [default: the current directory]
+  -h, --help              Print this help
+";
+
+#[derive(Debug)]
+struct Args {
+	inputs: Vec<String>,
+	formats: Vec<OutputFormat>,
+	theme: Option<Theme>,
+	reps_x: usize,
+	reps_y: usize,
+	options: ExportOptions,
+	output: Option<PathBuf>
+}
+
+/// A pattern read from an input, with the name its outputs are given.
+struct Input {
+	name: String,
+	/// The pattern file, if the input contained one; it is embedded in SVGs.
+	file: Option<PatternFile>,
+	shared: SharedPattern
+}
+
+fn parse_count(value: &str) -> Result<usize, String> {
+	match value.parse() {
+		Ok(count) if count > 0 => Ok(count),
+		_ => Err(format!("invalid repetition count '{value}'"))
+	}
+}
+
+/// Parses a length with a unit suffix into a value and its unit; plain
+/// numbers are pixels.
+fn parse_length(value: &str) -> Result<(f32, LengthUnit), String> {
+	let (number, unit) = all::<LengthUnit>()
+		.find_map(|unit| Some((value.strip_suffix(unit.label())?, unit)))
+		.unwrap_or((value, LengthUnit::Px));
+	match number.trim().parse::<f32>() {
+		Ok(length) if length > 0.0 => Ok((length, unit)),
+		_ => Err(format!("invalid length '{value}'"))
+	}
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+	let mut parsed = Args {
+		inputs: Vec::new(),
+		formats: vec![OutputFormat::Svg],
+		theme: None,
+		reps_x: 1,
+		reps_y: 1,
+		options: ExportOptions::default(),
+		output: None
+	};
+	let mut width = None;
+	let mut height = None;
+	while let Some(arg) = args.next() {
+		let mut value = || {
+			args.next()
+				.ok_or_else(|| format!("missing value for '{arg}'"))
+		};
+		match arg.as_str() {
+			"-h" | "--help" => return Ok(None),
+			"-f" | "--format" => {
+				parsed.formats = value()?
+					.split(',')
+					.map(|name| {
+						all::<OutputFormat>()
+							.find(|format| format.extension() == name.trim())
+							.ok_or_else(|| format!("unknown format '{name}'"))
+					})
+					.collect::<Result<_, _>>()?;
+			}
+			"-t" | "--theme" => {
+				let name = value()?;
+				let theme = all::<Theme>()
+					.find(|theme| theme.name() == name)
+					.ok_or_else(|| format!("unknown theme '{name}'"))?;
+				parsed.theme = Some(theme);
+			}
+			"-x" | "--reps-x" => parsed.reps_x = parse_count(&value()?)?,
+			"-y" | "--reps-y" => parsed.reps_y = parse_count(&value()?)?,
+			"--width" => width = Some(parse_length(&value()?)?),
+			"--height" => height = Some(parse_length(&value()?)?),
+			"--dpi" => {
+				let dpi = value()?;
+				parsed.options.size.dpi = match dpi.parse() {
+					Ok(dpi) if dpi > 0 => dpi,
+					_ => return Err(format!("invalid resolution '{dpi}'"))
+				};
+			}
+			"-o" | "--output" => parsed.output = Some(PathBuf::from(value()?)),
+			_ if arg.starts_with('-') && arg.len() > 1 => {
+				return Err(format!("unknown option '{arg}'"));
+			}
+			_ => parsed.inputs.push(arg)
+		}
+	}
+
+	if let (Some((_, width_unit)), Some((_, height_unit))) = (width, height) {
+		if width_unit != height_unit {
+			return Err("width and height must have the same unit".to_string());
+		}
+	}
+	if let Some((_, unit)) = width.or(height) {
+		let size = &mut parsed.options.size;
+		size.unit = unit;
+		size.width = width.map(|(length, _)| length);
+		size.height = height.map(|(length, _)| length);
+	}
+	if parsed.inputs.is_empty() {
+		return Err("no inputs given".to_string());
+	}
+	Ok(Some(parsed))
+}
+
+/// Reads a pattern file or SVG. If the colors of an SVG fit several themes,
+/// `theme` picks one.
+fn read_file(path: &Path, theme: Option<Theme>) -> Result<Input, Box<dyn Error>> {
+	let content = fs::read_to_string(path)?;
+	let stem = path
+		.file_stem()
+		.map(|stem| stem.to_string_lossy().into_owned())
+		.unwrap_or_default();
+	let file = if path.extension().is_some_and(|ext| ext == "svg") {
+		let imported = match (import_svg(&content), theme) {
+			(Err(SvgImportError::AmbiguousTheme(_)), Some(theme)) => {
+				import_svg_as(&content, theme)?
+			}
+			(Err(err @ SvgImportError::AmbiguousTheme(_)), None) => {
+				return Err(format!("{err}; choose one with --theme").into());
+			}
+			(imported, _) => imported?
+		};
+		match imported {
+			ImportedSvg::Document(file) => file,
+			ImportedSvg::Reconstructed(pattern) => {
+				return Ok(Input {
+					name: stem,
+					file: None,
+					shared: SharedPattern {
+						format: pattern.format,
+						theme: pattern.theme,
+						colors: pattern.composite()
+					}
+				});
+			}
+		}
+	} else {
+		PatternFile::from_json(&content)?
+	};
+	let name = if file.info.name.is_empty() {
+		stem
+	} else {
+		file.info.name.clone()
+	};
+	Ok(Input {
+		name,
+		shared: SharedPattern {
+			format: file.pattern.format,
+			theme: file.pattern.theme,
+			colors: file.pattern.composite()
+		},
+		file: Some(file)
+	})
+}
+
+/// Collects the files to render for an input: the input itself, or the
+/// pattern files and SVGs in a directory.
+fn input_files(path: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+	if !path.is_dir() {
+		return Ok(vec![path.to_path_buf()]);
+	}
+	let mut files: Vec<PathBuf> = fs::read_dir(path)?
+		.filter_map(|entry| Some(entry.ok()?.path()))
+		.filter(|file| {
+			file.is_file()
+				&& file
+					.extension()
+					.is_some_and(|ext| ext == EXTENSION || ext == "svg")
+		})
+		.collect();
+	files.sort();
+	Ok(files)
+}
+
+/// Reads the patterns of an input, which are either files or a share code.
+fn read_input(input: &str, theme: Option<Theme>) -> Vec<Result<Input, String>> {
+	let path = Path::new(input);
+	if path.exists() {
+		return match input_files(path) {
+			Ok(files) => files
+				.iter()
+				.map(|file| {
+					read_file(file, theme).map_err(|err| format!("{}: {err}", file.display()))
+				})
+				.collect(),
+			Err(err) => vec![Err(format!("{input}: {err}"))]
+		};
+	}
+	let code = match input.rsplit_once(FRAGMENT_PREFIX) {
+		Some((_, code)) => code,
+		None => input
+	};
+	vec![SharedPattern::decode(code)
+		.map(|shared| Input {
+			name: "Pattern".to_string(),
+			file: None,
+			shared
+		})
+		.map_err(|err| format!("{input}: not a file, and not a share code: {err}"))]
+}
+
+/// Replaces characters that aren't safe in file names.
+fn file_stem(name: &str) -> String {
+	name.chars()
+		.map(|ch| {
+			if ch.is_alphanumeric() || ch == '-' {
+				ch
+			} else {
+				'_'
+			}
+		})
+		.collect()
+}
+
+/// Makes `stem` unique among the stems in `used` by appending `-2`, `-3` and
+/// so on, so that patterns with the same name don't overwrite each other.
+/// Stems are compared case-insensitively, like some file systems do.
+fn unique_stem(stem: &str, used: &mut HashSet<String>) -> String {
+	let mut unique = stem.to_string();
+	let mut n = 2;
+	while !used.insert(unique.to_lowercase()) {
+		unique = format!("{stem}-{n}");
+		n += 1;
+	}
+	unique
+}
+
+fn render(input: &Input, format: OutputFormat, args: &Args) -> Result<Vec<u8>, Box<dyn Error>> {
+	let tiling = Tiling::load(input.shared.format);
+	let theme = args.theme.unwrap_or(input.shared.theme);
+	let artwork = Artwork {
+		tiling: &tiling,
+		colors: &input.shared.colors,
+		theme,
+		reps_x: args.reps_x,
+		reps_y: args.reps_y
+	};
+	let metadata = input.file.clone().map(|mut file| {
+		file.pattern.theme = theme;
+		file.to_json()
+	});
+	write_artwork(
+		&artwork,
+		&input.name,
+		format,
+		metadata.as_deref(),
+		&args.options
+	)
+}
+
+/// Renders every input in every format, reporting the written files to stdout
+/// and errors to stderr. Returns whether everything succeeded.
+fn run(args: &Args) -> bool {
+	let inputs: Vec<Result<Input, String>> = args
+		.inputs
+		.iter()
+		.flat_map(|input| read_input(input, args.theme))
+		.collect();
+	let num_outputs = inputs.len() * args.formats.len();
+	// A single output may be written to the given file; otherwise the output
+	// is a directory.
+	let output_file = args
+		.output
+		.as_ref()
+		.filter(|output| num_outputs == 1 && !output.is_dir());
+	let output_dir = match (&args.output, output_file) {
+		(Some(output), None) => output.clone(),
+		_ => PathBuf::from(".")
+	};
+	if output_file.is_none() {
+		if let Err(err) = fs::create_dir_all(&output_dir) {
+			eprintln!("error: {}: {err}", output_dir.display());
+			return false;
+		}
+	}
+
+	let mut failed = false;
+	let mut used_stems = HashSet::new();
+	for input in inputs {
+		let input = match input {
+			Ok(input) => input,
+			Err(err) => {
+				eprintln!("error: {err}");
+				failed = true;
+				continue;
+			}
+		};
+		let stem = unique_stem(&file_stem(&input.name), &mut used_stems);
+		for &format in &args.formats {
+			let path = match output_file {
+				Some(file) => file.clone(),
+				None => output_dir.join(format!("{stem}.{}", format.extension()))
+			};
+			let result =
+				render(&input, format, args).and_then(|content| Ok(fs::write(&path, content)?));
+			match result {
+				Ok(()) => println!("{}", path.display()),
+				Err(err) => {
+					eprintln!("error: {}: {err}", path.display());
+					failed = true;
+				}
+			}
+		}
+	}
+
+	!failed
+}
+
+fn main() -> ExitCode {
+	let args = match parse_args(env::args().skip(1)) {
+		Ok(Some(args)) => args,
+		Ok(None) => {
+			print!("{USAGE}");
+			return ExitCode::SUCCESS;
+		}
+		Err(err) => {
+			eprintln!("error: {err}\n\n{USAGE}");
+			return ExitCode::FAILURE;
+		}
+	};
+
+	if run(&args) {
+		ExitCode::SUCCESS
+	} else {
+		ExitCode::FAILURE
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use goldbeck_pattern::{
+		components::pattern::TileColor, document::PatternData, pattern_file::PatternInfo,
+		tiling::TilingFormat
+	};
+
+	use super::*;
+
+	fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+		parse_args(args.iter().map(|arg| arg.to_string()))
+	}
+
+	/// An empty directory for a test, removing what an earlier run left.
+	fn test_dir(name: &str) -> PathBuf {
+		let dir = env::temp_dir().join(format!(
+			"goldbeck-pattern-cli-{}-{name}",
+			std::process::id()
+		));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn shared(color: TileColor) -> SharedPattern {
+		let format = TilingFormat::F5X5;
+		SharedPattern {
+			format,
+			theme: Theme::Bubbles,
+			colors: vec![color; Tiling::load(format).num_tiles()]
+		}
+	}
+
+	fn pattern_file(name: &str, color: TileColor) -> String {
+		let shared = shared(color);
+		PatternFile::new(
+			PatternInfo {
+				name: name.to_string(),
+				author: String::new(),
+				created: String::new()
+			},
+			String::new(),
+			PatternData::from_colors(shared.format, shared.theme, shared.colors)
+		)
+		.to_json()
+	}
+
+	#[test]
+	fn parses_lengths() {
+		assert_eq!(parse_length("210mm"), Ok((210.0, LengthUnit::Mm)));
+		assert_eq!(parse_length("1.5in"), Ok((1.5, LengthUnit::In)));
+		assert_eq!(parse_length("20 cm"), Ok((20.0, LengthUnit::Cm)));
+		assert_eq!(parse_length("1920px"), Ok((1920.0, LengthUnit::Px)));
+		assert_eq!(parse_length("1920"), Ok((1920.0, LengthUnit::Px)));
+		for invalid in ["", "mm", "0mm", "-5cm", "12ft", "abc"] {
+			assert!(parse_length(invalid).is_err(), "{invalid:?} was accepted");
+		}
+	}
+
+	#[test]
+	fn parses_arguments() {
+		let args = parse(&[
+			"-f",
+			"svg,pdf",
+			"--theme",
+			"noble",
+			"-x",
+			"3",
+			"--reps-y",
+			"2",
+			"--width",
+			"210mm",
+			"--dpi",
+			"150",
+			"-o",
+			"out",
+			"a.gbpattern",
+			"b.svg"
+		])
+		.unwrap()
+		.unwrap();
+		assert_eq!(args.inputs, ["a.gbpattern", "b.svg"]);
+		assert_eq!(args.formats, [OutputFormat::Svg, OutputFormat::Pdf]);
+		assert_eq!(args.theme, Some(Theme::Noble));
+		assert_eq!((args.reps_x, args.reps_y), (3, 2));
+		assert_eq!(args.output, Some(PathBuf::from("out")));
+		let size = args.options.size;
+		assert_eq!(size.unit, LengthUnit::Mm);
+		assert_eq!(
+			(size.width, size.height, size.dpi),
+			(Some(210.0), None, 150)
+		);
+	}
+
+	#[test]
+	fn uses_defaults() {
+		let args = parse(&["code"]).unwrap().unwrap();
+		assert_eq!(args.formats, [OutputFormat::Svg]);
+		assert_eq!(args.theme, None);
+		assert_eq!((args.reps_x, args.reps_y), (1, 1));
+		assert_eq!(args.options, ExportOptions::default());
+		assert!(parse(&["code", "--help"]).unwrap().is_none());
+	}
+
+	#[test]
+	fn rejects_invalid_arguments() {
+		for invalid in [
+			&[][..],
+			&["code", "--bogus"],
+			&["code", "-f"],
+			&["code", "-f", "svg,gif"],
+			&["code", "-t", "dark"],
+			&["code", "-x", "0"],
+			&["code", "--dpi", "0"],
+			&["code", "--width", "10cm", "--height", "5in"]
+		] {
+			assert!(parse(invalid).is_err(), "{invalid:?} was accepted");
+		}
+	}
+
+	#[test]
+	fn makes_stems_unique() {
+		let mut used = HashSet::new();
+		let stems: Vec<String> = ["Pattern", "Pattern", "pattern", "Pattern-2", "Other"]
+			.iter()
+			.map(|stem| unique_stem(stem, &mut used))
+			.collect();
+		assert_eq!(
+			stems,
+			["Pattern", "Pattern-2", "pattern-3", "Pattern-2-2", "Other"]
+		);
+	}
+
+	#[test]
+	fn keeps_share_codes_apart() {
+		let out = test_dir("codes");
+		let codes = [
+			shared(TileColor::Primary).encode(),
+			shared(TileColor::Secondary).encode()
+		];
+		let args = parse(&[&codes[0], &codes[1], "-o", out.to_str().unwrap()])
+			.unwrap()
+			.unwrap();
+		assert!(run(&args));
+		let first = fs::read_to_string(out.join("Pattern.svg")).unwrap();
+		let second = fs::read_to_string(out.join("Pattern-2.svg")).unwrap();
+		assert_ne!(first, second);
+		fs::remove_dir_all(out).unwrap();
+	}
+
+	#[test]
+	fn keeps_batch_files_with_the_same_name_apart() {
+		let dir = test_dir("batch");
+		let input = dir.join("in");
+		let out = dir.join("out");
+		fs::create_dir(&input).unwrap();
+		fs::write(
+			input.join(format!("a.{EXTENSION}")),
+			pattern_file("Gleich", TileColor::Primary)
+		)
+		.unwrap();
+		fs::write(
+			input.join(format!("b.{EXTENSION}")),
+			pattern_file("Gleich", TileColor::Secondary)
+		)
+		.unwrap();
+		fs::write(input.join("notes.txt"), "not a pattern").unwrap();
+
+		let args = parse(&[
+			input.to_str().unwrap(),
+			"-f",
+			"svg,png",
+			"-o",
+			out.to_str().unwrap()
+		])
+		.unwrap()
+		.unwrap();
+		assert!(run(&args));
+		let mut written: Vec<String> = fs::read_dir(&out)
+			.unwrap()
+			.map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+			.collect();
+		written.sort();
+		assert_eq!(
+			written,
+			["Gleich-2.png", "Gleich-2.svg", "Gleich.png", "Gleich.svg"]
+		);
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
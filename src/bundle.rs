@@ -0,0 +1,174 @@
+use std::error::Error;
+
+use enum_iterator::all;
+use serde::Serialize;
+
+use crate::{
+	artwork::Artwork,
+	export::{write_artwork, ExportOptions, OutputFormat},
+	pattern_file::PatternFile,
+	theme::Theme,
+	zip::ZipWriter
+};
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+#[derive(Debug, Serialize)]
+struct ManifestFile {
+	path: String,
+	theme: &'static str,
+	format: &'static str
+}
+
+/// Describes the contents of a bundle, so that they can be processed without
+/// guessing from the paths.
+#[derive(Debug, Serialize)]
+struct Manifest<'a> {
+	name: &'a str,
+	author: &'a str,
+	created: &'a str,
+	modified: &'a str,
+	tiling: &'static str,
+	repetitions: [usize; 2],
+	/// The pattern file, from which all other files were rendered.
+	source: String,
+	files: Vec<ManifestFile>
+}
+
+/// Writes a ZIP archive with the artwork in every theme and each of the
+/// given formats, one directory per theme. The pattern file and a manifest
+/// listing all files are included at the top level. The theme of `artwork`
+/// is ignored.
+pub fn write_bundle(
+	artwork: &Artwork,
+	file: &PatternFile,
+	formats: &[OutputFormat],
+	options: &ExportOptions
+) -> Result<Vec<u8>, Box<dyn Error>> {
+	let stem = file.file_stem();
+	let title = if file.info.name.is_empty() {
+		"Pattern"
+	} else {
+		&file.info.name
+	};
+
+	let mut zip = ZipWriter::new();
+	let mut files = Vec::new();
+	for theme in all::<Theme>() {
+		let artwork = Artwork { theme, ..*artwork };
+		let mut themed_file = file.clone();
+		themed_file.pattern.theme = theme;
+		let metadata = themed_file.to_json();
+		for &format in formats {
+			let content = write_artwork(&artwork, title, format, Some(&metadata), options)
+				.map_err(|err| format!("{} {format}: {err}", theme.label()))?;
+			let path = format!("{}/{stem}.{}", theme.name(), format.extension());
+			zip.add_file(&path, &content);
+			files.push(ManifestFile {
+				path,
+				theme: theme.name(),
+				format: format.extension()
+			});
+		}
+	}
+
+	let source = file.file_name();
+	zip.add_file(&source, file.to_json().as_bytes());
+	let manifest = Manifest {
+		name: &file.info.name,
+		author: &file.info.author,
+		created: &file.info.created,
+		modified: &file.modified,
+		tiling: file.pattern.format.label(),
+		repetitions: [artwork.reps_x, artwork.reps_y],
+		source,
+		files
+	};
+	let manifest = serde_json::to_string_pretty(&manifest)?;
+	zip.add_file(MANIFEST_NAME, manifest.as_bytes());
+
+	Ok(zip.finish())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		components::pattern::TileColor,
+		document::PatternData,
+		pattern_file::PatternInfo,
+		tiling::{Tiling, TilingFormat},
+		zip::tests::read_archive
+	};
+
+	use super::*;
+
+	#[test]
+	fn bundles_every_theme_and_format() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let colors: Vec<TileColor> = (0..tiling.num_tiles())
+			.map(|i| [TileColor::None, TileColor::Primary, TileColor::Secondary][i % 3])
+			.collect();
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme: Theme::Noble,
+			reps_x: 2,
+			reps_y: 1
+		};
+		let file = PatternFile::new(
+			PatternInfo {
+				name: "Fassade".to_string(),
+				author: "Team".to_string(),
+				created: String::new()
+			},
+			String::new(),
+			PatternData::from_colors(TilingFormat::F5X5, Theme::Noble, colors.clone())
+		);
+		let formats = [OutputFormat::Svg, OutputFormat::Pdf, OutputFormat::Png];
+		// Small images keep the test fast.
+		let mut options = ExportOptions::default();
+		options.size.height = Some(100.0);
+		let bundle = write_bundle(&artwork, &file, &formats, &options).unwrap();
+		let entries = read_archive(&bundle);
+
+		let mut expected: Vec<String> = all::<Theme>()
+			.flat_map(|theme| {
+				formats
+					.iter()
+					.map(move |format| format!("{}/Fassade.{}", theme.name(), format.extension()))
+			})
+			.collect();
+		expected.push(file.file_name());
+		expected.push(MANIFEST_NAME.to_string());
+		let names: Vec<&String> = entries.iter().map(|(name, _)| name).collect();
+		assert_eq!(names, expected.iter().collect::<Vec<_>>());
+
+		let content = |name: &str| &entries.iter().find(|(n, _)| n == name).unwrap().1;
+		assert_eq!(content(&file.file_name()), file.to_json().as_bytes());
+		let svgs: Vec<&Vec<u8>> = all::<Theme>()
+			.map(|theme| content(&format!("{}/Fassade.svg", theme.name())))
+			.collect();
+		for (i, svg) in svgs.iter().enumerate() {
+			assert!(
+				svgs[..i].iter().all(|other| other != svg),
+				"themes look alike"
+			);
+		}
+		for theme in all::<Theme>() {
+			let png = content(&format!("{}/Fassade.png", theme.name()));
+			assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+			let pdf = content(&format!("{}/Fassade.pdf", theme.name()));
+			assert!(pdf.starts_with(b"%PDF-"));
+		}
+
+		let manifest: serde_json::Value = serde_json::from_slice(content(MANIFEST_NAME)).unwrap();
+		assert_eq!(manifest["name"], "Fassade");
+		assert_eq!(manifest["source"], file.file_name());
+		assert_eq!(manifest["repetitions"], serde_json::json!([2, 1]));
+		let files = manifest["files"].as_array().unwrap();
+		assert_eq!(files.len(), 4 * formats.len());
+		for (file, path) in files.iter().zip(&expected) {
+			assert_eq!(file["path"], path.as_str());
+		}
+	}
+}
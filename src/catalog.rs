@@ -0,0 +1,358 @@
+use enum_iterator::all;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+use crate::{
+	artwork::{Artwork, SEAM_STROKE_WIDTH},
+	components::pattern::TileColor,
+	pattern_file::PatternFile,
+	print::draw_tiles,
+	theme::{Rgb, Theme, ThemeColor},
+	tiling::{Tiling, TilingFormat}
+};
+
+/// A4 in points.
+const PAGE_WIDTH: f32 = 595.28;
+const PAGE_HEIGHT: f32 = 841.89;
+const MARGIN: f32 = 56.69;
+/// The height of the area the pattern is drawn in, below the heading.
+const PATTERN_HEIGHT: f32 = 460.0;
+/// How often the tiling is repeated in each direction, to show how the
+/// pattern continues.
+const REPETITIONS: usize = 2;
+const SWATCH_SIZE: f32 = 12.0;
+const LINE_HEIGHT: f32 = 20.0;
+
+/// One pattern in one theme, on a page of its own.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogPage {
+	pub name: String,
+	pub format: TilingFormat,
+	pub theme: Theme,
+	pub colors: Vec<TileColor>
+}
+
+impl CatalogPage {
+	pub fn from_file(file: &PatternFile) -> Self {
+		Self {
+			name: file.info.name.clone(),
+			format: file.pattern.format,
+			theme: file.pattern.theme,
+			colors: file.pattern.composite()
+		}
+	}
+
+	/// The share of tiles in `color`, between 0 and 1.
+	fn share(&self, color: TileColor) -> f32 {
+		if self.colors.is_empty() {
+			return 0.0;
+		}
+		self.colors.iter().filter(|c| **c == color).count() as f32 / self.colors.len() as f32
+	}
+}
+
+/// The pages that show a pattern in each of the themes.
+pub fn theme_pages(file: &PatternFile) -> Vec<CatalogPage> {
+	all::<Theme>()
+		.map(|theme| CatalogPage {
+			theme,
+			..CatalogPage::from_file(file)
+		})
+		.collect()
+}
+
+/// Encodes text for the standard fonts in `WinAnsiEncoding`, which agrees
+/// with Latin-1 on the characters used here. Other characters are replaced.
+fn win_ansi(text: &str) -> Vec<u8> {
+	text.chars()
+		.map(|ch| match ch as u32 {
+			code @ (0x20..=0x7e | 0xa0..=0xff) => code as u8,
+			_ => b'?'
+		})
+		.collect()
+}
+
+fn rgb(color: Rgb) -> [f32; 3] {
+	[color.0, color.1, color.2].map(|c| c as f32 / 255.0)
+}
+
+fn text(content: &mut Content, font: &[u8], size: f32, x: f32, y: f32, text: &str) {
+	content
+		.begin_text()
+		.set_font(Name(font), size)
+		.next_line(x, y)
+		.show(Str(&win_ansi(text)))
+		.end_text();
+}
+
+fn percent(value: f32) -> i32 {
+	(value * 100.0).round() as i32
+}
+
+/// Draws a page: the name of the pattern with its format and theme, the
+/// pattern itself and a legend of its colors with their coverage.
+fn draw_page(content: &mut Content, page: &CatalogPage, number: usize, count: usize) {
+	let tiling = Tiling::load(page.format);
+	let artwork = Artwork {
+		tiling: &tiling,
+		colors: &page.colors,
+		theme: page.theme,
+		reps_x: REPETITIONS,
+		reps_y: REPETITIONS
+	};
+	let palette = page.theme.palette();
+	let [r, g, b] = rgb(ThemeColor::Carbon.rgb());
+	content.set_fill_rgb(r, g, b);
+
+	let name = if page.name.is_empty() {
+		"Unbenanntes Muster"
+	} else {
+		&page.name
+	};
+	let mut y = PAGE_HEIGHT - MARGIN - 20.0;
+	text(content, b"F2", 20.0, MARGIN, y, name);
+	y -= LINE_HEIGHT;
+	text(
+		content,
+		b"F1",
+		11.0,
+		MARGIN,
+		y,
+		&format!(
+			"Format {} · Thema {}",
+			page.format.label(),
+			page.theme.label()
+		)
+	);
+	text(
+		content,
+		b"F1",
+		9.0,
+		MARGIN,
+		MARGIN - 20.0,
+		&format!("{number} / {count}")
+	);
+
+	let area_width = PAGE_WIDTH - 2.0 * MARGIN;
+	let scale = (area_width / artwork.width()).min(PATTERN_HEIGHT / artwork.height());
+	let left = MARGIN + (area_width - artwork.width() * scale) / 2.0;
+	let top = y - LINE_HEIGHT;
+	content.save_state();
+	// Flip the y axis, which points downwards in the artwork.
+	content.transform([scale, 0.0, 0.0, -scale, left, top]);
+	content.set_line_width(SEAM_STROKE_WIDTH);
+	for color in artwork.used_colors() {
+		let [r, g, b] = rgb(palette.tile_color(color));
+		content.set_fill_rgb(r, g, b).set_stroke_rgb(r, g, b);
+		draw_tiles(content, &artwork, color);
+	}
+	content.restore_state();
+
+	y = top - artwork.height() * scale - 2.0 * LINE_HEIGHT;
+	content.set_line_width(0.5);
+	for color in all::<TileColor>() {
+		let [r, g, b] = rgb(palette.tile_color(color));
+		content
+			.set_fill_rgb(r, g, b)
+			.set_stroke_rgb(0.5, 0.5, 0.5)
+			.rect(MARGIN, y - 2.0, SWATCH_SIZE, SWATCH_SIZE)
+			.fill_nonzero_and_stroke();
+		let [r, g, b] = rgb(ThemeColor::Carbon.rgb());
+		content.set_fill_rgb(r, g, b);
+		text(
+			content,
+			b"F1",
+			11.0,
+			MARGIN + SWATCH_SIZE + 8.0,
+			y,
+			&format!("{} · {}", color.label(), palette.tile_color_name(color))
+		);
+		text(
+			content,
+			b"F1",
+			11.0,
+			MARGIN + 220.0,
+			y,
+			&format!("{} %", percent(page.share(color)))
+		);
+		y -= LINE_HEIGHT;
+	}
+	let coverage = 1.0 - page.share(TileColor::None);
+	text(
+		content,
+		b"F2",
+		11.0,
+		MARGIN,
+		y - 4.0,
+		&format!("Gefüllt: {} %", percent(coverage))
+	);
+}
+
+/// Writes a catalog with one A4 page per entry of `pages`, for handing out
+/// several patterns at once.
+pub fn write_catalog(title: &str, pages: &[CatalogPage]) -> Vec<u8> {
+	let mut next_id = 1;
+	let mut alloc = || {
+		let id = Ref::new(next_id);
+		next_id += 1;
+		id
+	};
+	let catalog_id = alloc();
+	let info_id = alloc();
+	let pages_id = alloc();
+	let regular_id = alloc();
+	let bold_id = alloc();
+	let page_ids: Vec<(Ref, Ref)> = pages.iter().map(|_| (alloc(), alloc())).collect();
+
+	let mut pdf = Pdf::new();
+	pdf.catalog(catalog_id).pages(pages_id);
+	pdf.document_info(info_id).title(TextStr(title));
+	pdf.pages(pages_id)
+		.kids(page_ids.iter().map(|(page_id, _)| *page_id))
+		.count(pages.len() as i32);
+	for (id, font) in [(regular_id, "Helvetica"), (bold_id, "Helvetica-Bold")] {
+		pdf.type1_font(id)
+			.base_font(Name(font.as_bytes()))
+			.encoding_predefined(Name(b"WinAnsiEncoding"));
+	}
+
+	for (i, (page, (page_id, content_id))) in pages.iter().zip(&page_ids).enumerate() {
+		let mut pdf_page = pdf.page(*page_id);
+		pdf_page
+			.parent(pages_id)
+			.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+			.contents(*content_id);
+		let mut resources = pdf_page.resources();
+		let mut fonts = resources.fonts();
+		fonts.pair(Name(b"F1"), regular_id);
+		fonts.pair(Name(b"F2"), bold_id);
+		fonts.finish();
+		resources.finish();
+		pdf_page.finish();
+
+		let mut content = Content::new();
+		draw_page(&mut content, page, i + 1, pages.len());
+		pdf.stream(*content_id, &content.finish());
+	}
+
+	pdf.finish()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{document::PatternData, pattern_file::PatternInfo};
+
+	use super::*;
+
+	fn page(theme: Theme, colors: Vec<TileColor>) -> CatalogPage {
+		CatalogPage {
+			name: String::from("Fassade Süd"),
+			format: TilingFormat::F5X5,
+			theme,
+			colors
+		}
+	}
+
+	/// The strings shown with `Tj`, decoded from literal or hex strings.
+	fn shown_text(pdf: &[u8]) -> Vec<Vec<u8>> {
+		String::from_utf8_lossy(pdf)
+			.lines()
+			.filter_map(|line| line.strip_suffix(" Tj"))
+			.map(|operand| {
+				if let Some(hex) = operand.strip_prefix('<') {
+					let hex = hex.trim_end_matches('>');
+					(0..hex.len())
+						.step_by(2)
+						.map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+						.collect()
+				} else {
+					operand
+						.trim_matches(|c| c == '(' || c == ')')
+						.bytes()
+						.collect()
+				}
+			})
+			.collect()
+	}
+
+	#[test]
+	fn encodes_umlauts_in_win_ansi() {
+		assert_eq!(win_ansi("Muster"), b"Muster");
+		assert_eq!(
+			win_ansi("Größe äü ÄÖÜ"),
+			b"Gr\xf6\xdfe \xe4\xfc \xc4\xd6\xdc"
+		);
+		// Characters outside of Latin-1 are replaced.
+		assert_eq!(win_ansi("5×5 · €"), b"5\xd75 \xb7 ?");
+	}
+
+	#[test]
+	fn writes_a_page_per_entry() {
+		let num_tiles = Tiling::load(TilingFormat::F5X5).num_tiles();
+		for count in [0, 1, 4] {
+			let pages: Vec<CatalogPage> = (0..count)
+				.map(|_| page(Theme::Icy, vec![TileColor::Primary; num_tiles]))
+				.collect();
+			let pdf = write_catalog("Katalog", &pages);
+			let text = String::from_utf8_lossy(&pdf);
+			assert!(text.contains(&format!("/Count {count}")));
+			assert_eq!(text.matches("/Type /Page\n").count(), count);
+			let numbers: Vec<Vec<u8>> = (1..=count)
+				.map(|number| format!("{number} / {count}").into_bytes())
+				.collect();
+			let shown = shown_text(&pdf);
+			assert!(numbers.iter().all(|number| shown.contains(number)));
+		}
+	}
+
+	#[test]
+	fn lists_coverage_per_color() {
+		let num_tiles = Tiling::load(TilingFormat::F5X5).num_tiles();
+		assert_eq!(num_tiles % 10, 0);
+		let colors: Vec<TileColor> = (0..num_tiles)
+			.map(|i| {
+				if i < num_tiles / 5 {
+					TileColor::Primary
+				} else if i < num_tiles * 7 / 10 {
+					TileColor::Secondary
+				} else {
+					TileColor::None
+				}
+			})
+			.collect();
+		let page = page(Theme::Glisten, colors);
+		assert_eq!(page.share(TileColor::Primary), 0.2);
+		assert_eq!(page.share(TileColor::Secondary), 0.5);
+		assert_eq!(page.share(TileColor::None), 0.3);
+
+		let shown = shown_text(&write_catalog("Katalog", &[page]));
+		assert!(shown.contains(&win_ansi("Fassade Süd")));
+		for share in ["20 %", "50 %", "30 %"] {
+			assert!(shown.contains(&share.as_bytes().to_vec()), "{share}");
+		}
+		assert!(shown.contains(&win_ansi("Gefüllt: 70 %")));
+	}
+
+	#[test]
+	fn shows_every_theme() {
+		let file = PatternFile::new(
+			PatternInfo {
+				name: String::new(),
+				author: String::new(),
+				created: String::new()
+			},
+			String::new(),
+			PatternData::from_colors(
+				TilingFormat::F5X5,
+				Theme::Noble,
+				vec![TileColor::Primary; Tiling::load(TilingFormat::F5X5).num_tiles()]
+			)
+		);
+		let pages = theme_pages(&file);
+		assert_eq!(
+			pages.iter().map(|page| page.theme).collect::<Vec<_>>(),
+			all::<Theme>().collect::<Vec<_>>()
+		);
+		let shown = shown_text(&write_catalog("Themen", &pages));
+		assert!(shown.contains(&b"Unbenanntes Muster".to_vec()));
+	}
+}
@@ -1,34 +1,136 @@
 use std::rc::Rc;
 
-use leptos::*;
+use leptos::{
+	leptos_dom::{helpers::window_event_listener_untyped, logging::console_error},
+	*
+};
 
 use crate::{
 	components::{
-		canvas::Canvas,
+		autosave::Autosave,
+		canvas::{Brush, Canvas},
 		controls::Controls,
 		export_dialog::ExportDialog,
-		pattern::{GridColors, TileColor},
+		file_actions::{open_pattern_file, SaveDialog},
+		layers::{LayerPanel, LayerStack},
+		lettering_dialog::LetteringDialog,
+		library_dialog::LibraryDialog,
+		pattern::TileColor,
+		qr_dialog::QrDialog,
+		rules_dialog::RulesDialog,
+		share_link::take_shared_pattern,
+		stamp_dialog::StampDialog,
 		theme_selector::ThemeSelector
 	},
+	document::PatternData,
+	pattern_file::PatternFile,
+	rules::RuleSet,
+	theme::ThemeCtx,
 	tiling::{Tiling, TilingFormat}
 };
 
 #[component]
 pub fn App() -> impl IntoView {
+	let theme = use_context::<ThemeCtx>().expect("App is missing theme context!");
 	let format = create_rw_signal(TilingFormat::F5X5);
 	let tiling = create_memo(move |_| Rc::new(Tiling::load(format())));
-	let colors = create_memo(move |_| GridColors::new(tiling.with(|t| t.num_tiles())));
-	let brush = create_rw_signal(TileColor::Primary);
+	let layers = create_memo(move |_| LayerStack::new(tiling.with(|t| t.num_tiles())));
+	let colors = create_memo(move |_| layers.with(|l| l.composite()));
+	let brush = create_rw_signal(Brush::Paint(TileColor::Primary));
 	let exporting = create_rw_signal(false);
+	let lettering = create_rw_signal(false);
+	let stamping = create_rw_signal(false);
+	let selection = create_rw_signal(None);
+	let stamp = create_rw_signal(None);
+	let checking_rules = create_rw_signal(false);
+	let saving = create_rw_signal(false);
+	let browsing_library = create_rw_signal(false);
+	let showing_qr = create_rw_signal(false);
+	let info = create_rw_signal(None);
+	let library_entry = create_rw_signal(None);
+	let rules = create_rw_signal(RuleSet::default());
+	let violations = create_memo(move |_| {
+		tiling.with(|t| colors.with(|c| rules.with(|r| r.check(t, &c.snapshot()))))
+	});
+
+	let load_pattern = move |pattern: &PatternData| {
+		format.set(pattern.format);
+		theme.set(pattern.theme);
+		layers.with_untracked(|l| l.load(&pattern.layers, &pattern.locks))
+	};
+
+	let on_load_file = Callback::new(move |file: PatternFile| match load_pattern(&file.pattern) {
+		Ok(()) => {
+			info.set(Some(file.info));
+			library_entry.set(None);
+		}
+		Err(err) => {
+			console_error(&format!("Failed to load pattern: {err}"));
+			window()
+				.alert_with_message("Das Muster konnte nicht geladen werden.")
+				.unwrap_or(());
+		}
+	});
+	let on_open = Callback::new(move |file| open_pattern_file(file, on_load_file));
+
+	// Returns whether the URL held a shared pattern.
+	let load_shared_pattern = move || {
+		let Some(shared) = take_shared_pattern() else {
+			return false;
+		};
+		let pattern = PatternData::from_colors(shared.format, shared.theme, shared.colors);
+		match load_pattern(&pattern) {
+			Ok(()) => {
+				info.set(None);
+				library_entry.set(None);
+			}
+			Err(err) => console_error(&format!("Failed to load shared pattern: {err}"))
+		}
+		true
+	};
+	let opened_shared = load_shared_pattern();
+	// Share links opened while the app is already running only change the
+	// fragment, without reloading the page.
+	window_event_listener_untyped("hashchange", move |_| {
+		load_shared_pattern();
+	});
 
 	view! {
 		<main class="w-screen h-screen flex flex-col items-center overflow-hidden">
 			<ThemeSelector />
 			<div class="w-full min-h-0 p-3 sm:px-16">
-				<Canvas tiling colors brush />
+				<Canvas tiling colors layers brush violations selection stamp on_drop_file=on_open />
 			</div>
-			<Controls brush exporting format />
-			<ExportDialog open=exporting tiling colors />
+			<Controls
+				brush
+				exporting
+				lettering
+				stamping
+				checking_rules
+				violations
+				format
+				colors
+				saving
+				browsing_library
+				showing_qr
+				on_open
+			/>
+			<LayerPanel stack=layers />
+			<ExportDialog open=exporting tiling colors violations format layers info />
+			<LetteringDialog open=lettering tiling layers />
+			<RulesDialog open=checking_rules rules violations />
+			<StampDialog open=stamping tiling colors brush selection stamp />
+			<SaveDialog open=saving info format layers />
+			<QrDialog open=showing_qr format colors />
+			<LibraryDialog
+				open=browsing_library
+				info
+				entry_id=library_entry
+				format
+				layers
+				on_load=on_load_file
+			/>
+			<Autosave format layers brush restore=!opened_shared />
 		</main>
 	}
 }
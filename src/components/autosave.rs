@@ -0,0 +1,113 @@
+use leptos::{
+	leptos_dom::{
+		helpers::{set_interval, window_event_listener_untyped},
+		logging::console_error
+	},
+	*
+};
+
+use crate::{
+	components::{canvas::Brush, dialog::Dialog, layers::LayerStack},
+	document::PatternData,
+	session::{self, Session},
+	theme::ThemeCtx,
+	tiling::TilingFormat
+};
+
+/// Saves the current pattern to the browser storage after every change and
+/// restores the last session of a closed tab on startup, unless `restore` is
+/// unset. Every tab has a session of its own. If the previous session ended
+/// unexpectedly, the user is asked whether to recover it instead.
+#[component]
+pub fn Autosave(
+	format: RwSignal<TilingFormat>,
+	#[prop(into)] layers: Signal<LayerStack>,
+	brush: RwSignal<Brush>,
+	#[prop(default = true)] restore: bool
+) -> impl IntoView {
+	let theme = use_context::<ThemeCtx>().expect("Autosave is missing theme context!");
+
+	let previous = store_value(session::begin());
+	let recovering = create_rw_signal(false);
+
+	let restore_previous = move || {
+		previous.with_value(|previous| {
+			let Some(previous) = previous else {
+				return;
+			};
+			let pattern = &previous.session.pattern;
+			format.set(pattern.format);
+			theme.set(pattern.theme);
+			brush.set(previous.session.brush);
+			let loaded = layers.with_untracked(|l| l.load(&pattern.layers, &pattern.locks));
+			if let Err(err) = loaded {
+				console_error(&format!("Failed to restore session: {err}"));
+			}
+		});
+	};
+	// This tab's session replaces the previous one.
+	let forget_previous = move || {
+		previous.with_value(|previous| {
+			if let Some(previous) = previous {
+				session::forget(&previous.id);
+			}
+		});
+	};
+
+	if restore {
+		if previous.with_value(|p| p.as_ref().is_some_and(|p| p.crashed)) {
+			recovering.set(true);
+		} else {
+			restore_previous();
+			forget_previous();
+		}
+	}
+
+	window_event_listener_untyped("pagehide", |_| session::end());
+	set_interval(session::heartbeat, session::HEARTBEAT_INTERVAL);
+
+	create_effect(move |_| {
+		if recovering() {
+			return;
+		}
+		let (layer_data, locks) = layers.with(|l| l.data());
+		session::save(&Session {
+			pattern: PatternData {
+				format: format(),
+				theme: theme(),
+				layers: layer_data,
+				locks
+			},
+			brush: brush()
+		});
+	});
+
+	view! {
+		<Dialog id="recoveryDialog" open=recovering title="Sitzung wiederherstellen">
+			<p class="mb-4">
+				"Die letzte Sitzung wurde unerwartet beendet. Möchtest du das letzte Muster wiederherstellen?"
+			</p>
+			<div class="flex justify-end gap-4">
+				<button
+					class="px-6 py-2 h-12 bg-background text-backgroundText hover:outline outline-2 outline-highlight"
+					on:click=move |_| {
+						forget_previous();
+						recovering.set(false);
+					}
+				>
+					"Verwerfen"
+				</button>
+				<button
+					class="px-6 py-2 h-12 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight"
+					on:click=move |_| {
+						restore_previous();
+						forget_previous();
+						recovering.set(false);
+					}
+				>
+					"Wiederherstellen"
+				</button>
+			</div>
+		</Dialog>
+	}
+}
@@ -1,43 +1,99 @@
 use std::rc::Rc;
 
 use leptos::{
-	ev::{MouseEvent, TouchEvent},
+	ev::{DragEvent, MouseEvent, TouchEvent},
 	leptos_dom::logging::console_log,
 	*
 };
-use web_sys::{MouseEventInit, SvgElement};
+use serde::{Deserialize, Serialize};
+use web_sys::{File, MouseEventInit, SvgElement};
 
 use crate::{
-	components::pattern::{GridColors, Pattern, TileColor},
+	cls,
+	components::{
+		file_actions::handle_file_drop,
+		layers::LayerStack,
+		pattern::{GridColors, Pattern, TileColor}
+	},
+	rules::Violation,
+	stamps::{Selection, Stamp},
 	tiling::{Shape, Tiling}
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Brush {
+	Paint(TileColor),
+	Lock,
+	Unlock,
+	Select,
+	Stamp
+}
+
 #[component]
 fn TileOverlay(
 	shape: Shape,
-	color: RwSignal<TileColor>,
-	brush: Signal<TileColor>
+	index: usize,
+	tiling: Signal<Rc<Tiling>>,
+	layers: Signal<LayerStack>,
+	brush: Signal<Brush>,
+	selection: RwSignal<Option<Selection>>,
+	stamp: Signal<Option<Stamp>>,
+	hover: RwSignal<Option<usize>>
 ) -> impl IntoView {
-	let paint = move || color.set(brush.get_untracked());
+	let paint = move |stroke_start: bool| match brush.get_untracked() {
+		Brush::Paint(color) => {
+			layers.with_untracked(|l| l.paint(index, color));
+		}
+		Brush::Lock => layers.with_untracked(|l| l.set_locked(index, true)),
+		Brush::Unlock => layers.with_untracked(|l| l.set_locked(index, false)),
+		Brush::Select => {
+			let (x, y, _) = tiling.with_untracked(|t| t.tile_position(index));
+			if stroke_start {
+				selection.set(Some(Selection::new((x, y))));
+			} else {
+				selection.update(|s| {
+					if let Some(s) = s {
+						s.end = (x, y);
+					}
+				});
+			}
+		}
+		Brush::Stamp => {
+			if !stroke_start {
+				return;
+			}
+			let placements = stamp.with_untracked(|s| {
+				s.as_ref()
+					.map(|s| tiling.with_untracked(|t| s.placements(t, index)))
+					.unwrap_or_default()
+			});
+			layers.with_untracked(|l| {
+				for (i, color) in placements {
+					l.paint(i, color);
+				}
+			});
+		}
+	};
 
-	let handle_mouse = move |buttons: u16| {
+	let handle_mouse = move |buttons: u16, stroke_start: bool| {
 		if buttons & 0b01 == 1 {
-			paint();
+			paint(stroke_start);
 		}
 	};
 
 	let on_mouse_enter = move |evt: MouseEvent| {
 		let target: SvgElement = event_target(&evt);
 		target.focus().unwrap_or(());
-		handle_mouse(evt.buttons());
+		hover.set(Some(index));
+		handle_mouse(evt.buttons(), false);
 	};
 
 	let on_mouse_down = move |evt: MouseEvent| {
-		handle_mouse(evt.buttons());
+		handle_mouse(evt.buttons(), true);
 	};
 
 	let on_touch_start = move |_: TouchEvent| {
-		paint();
+		paint(true);
 	};
 
 	view! {
@@ -86,12 +142,135 @@ fn GridLines(tiling: Signal<Rc<Tiling>>, width: Signal<f32>, height: Signal<f32>
 	}
 }
 
+#[component]
+fn ViolationHighlights(
+	tiling: Signal<Rc<Tiling>>,
+	violations: Signal<Vec<Violation>>
+) -> impl IntoView {
+	move || {
+		tiling.with(|t| {
+			violations
+				.with(|v| v.iter().flat_map(|v| v.tiles(t)).collect::<Vec<usize>>())
+				.into_iter()
+				.map(|i| {
+					view! {
+						<polygon
+							class="fill-highlight/50 stroke-highlight pointer-events-none"
+							points=t.tile(i).svg_path()
+							vector-effect="non-scaling-stroke"
+							stroke-width="2"
+							stroke-linejoin="round"
+						/>
+					}
+				})
+				.collect_view()
+		})
+	}
+}
+
+#[component]
+fn LockedTiles(tiling: Signal<Rc<Tiling>>, layers: Signal<LayerStack>) -> impl IntoView {
+	let hatch = move || {
+		tiling.with(|t| {
+			let locks = layers.with(|l| l.locks());
+			t.iter_tiles()
+				.enumerate()
+				.filter(|(i, _)| locks.get_locked(*i).get())
+				.map(|(_, shape)| {
+					view! {
+						<polygon
+							class="pointer-events-none"
+							points=shape.svg_path()
+							fill="url(#LockedTiles__hatch)"
+						/>
+					}
+				})
+				.collect_view()
+		})
+	};
+
+	view! {
+		<defs>
+			<pattern
+				id="LockedTiles__hatch"
+				width="0.2"
+				height="0.2"
+				patternUnits="userSpaceOnUse"
+				patternTransform="rotate(45)"
+			>
+				<line class="stroke-misc" x1="0" y1="0" x2="0" y2="0.2" stroke-width="0.04"/>
+			</pattern>
+		</defs>
+		{hatch}
+	}
+}
+
+#[component]
+fn SelectionOutline(selection: Signal<Option<Selection>>) -> impl IntoView {
+	move || {
+		selection().map(|selection| {
+			let (x, y, width, height) = selection.bounds();
+			view! {
+				<rect
+					class="fill-highlight/20 stroke-highlight pointer-events-none"
+					x=x
+					y=y
+					width=width
+					height=height
+					vector-effect="non-scaling-stroke"
+					stroke-width="2"
+					stroke-dasharray="6 4"
+				/>
+			}
+		})
+	}
+}
+
+#[component]
+fn StampGhost(
+	tiling: Signal<Rc<Tiling>>,
+	brush: Signal<Brush>,
+	stamp: Signal<Option<Stamp>>,
+	hover: Signal<Option<usize>>
+) -> impl IntoView {
+	move || {
+		let (Brush::Stamp, Some(anchor)) = (brush(), hover()) else {
+			return View::default();
+		};
+		tiling.with(|t| {
+			stamp
+				.with(|s| s.as_ref().map(|s| s.placements(t, anchor)))
+				.unwrap_or_default()
+				.into_iter()
+				.map(|(i, color)| {
+					view! {
+						<polygon
+							class=cls! {
+								"opacity-60 pointer-events-none",
+								match color { TileColor::Primary => "fill-primary",
+								TileColor::Secondary => "fill-secondary", TileColor::None =>
+								"fill-transparent" }
+							}
+							points=t.tile(i).svg_path()
+						/>
+					}
+				})
+				.collect_view()
+		})
+	}
+}
+
 #[component]
 fn Overlay(
 	tiling: Signal<Rc<Tiling>>,
-	colors: Signal<GridColors>,
-	brush: Signal<TileColor>
+	layers: Signal<LayerStack>,
+	brush: Signal<Brush>,
+	violations: Signal<Vec<Violation>>,
+	selection: RwSignal<Option<Selection>>,
+	stamp: Signal<Option<Stamp>>
 ) -> impl IntoView {
+	let hover = create_rw_signal(None);
+
 	let view_box =
 		move || tiling.with(|t| format!("0 0 {} {}", t.viewport_width(), t.viewport_height()));
 	let width = Signal::derive(move || tiling.with(|t| t.viewport_width()));
@@ -125,14 +304,19 @@ fn Overlay(
 			width="100%"
 			class="block touch-none"
 			on:touchmove=on_touch_move
+			on:mouseleave=move |_| hover.set(None)
 		>
 			<GridLines tiling width height />
+			<LockedTiles tiling layers />
+			<ViolationHighlights tiling violations />
+			<SelectionOutline selection=selection.into() />
+			<StampGhost tiling brush stamp hover=hover.into() />
 			{move || tiling
 				.with(|t| t.iter_tiles())
 				.enumerate()
-				.map(|(i, shape)| {
+				.map(|(index, shape)| {
 					view! {
-						<TileOverlay shape color=colors.with(|c| c.get_color(i)) brush />
+						<TileOverlay shape index tiling layers brush selection stamp hover />
 					}
 				})
 				.collect_view()
@@ -145,7 +329,13 @@ fn Overlay(
 pub fn Canvas(
 	#[prop(into)] tiling: Signal<Rc<Tiling>>,
 	#[prop(into)] colors: Signal<GridColors>,
-	brush: RwSignal<TileColor>
+	#[prop(into)] layers: Signal<LayerStack>,
+	brush: RwSignal<Brush>,
+	#[prop(into)] violations: Signal<Vec<Violation>>,
+	selection: RwSignal<Option<Selection>>,
+	#[prop(into)] stamp: Signal<Option<Stamp>>,
+	/// Called with files that are dropped onto the canvas.
+	on_drop_file: Callback<File>
 ) -> impl IntoView {
 	let aspect_ratio = move || {
 		tiling
@@ -153,7 +343,12 @@ pub fn Canvas(
 			.to_string()
 	};
 	view! {
-		<div class="relative h-full max-w-full m-auto" style:aspect-ratio=aspect_ratio>
+		<div
+			class="relative h-full max-w-full m-auto"
+			style:aspect-ratio=aspect_ratio
+			on:dragover=|ev: DragEvent| ev.prevent_default()
+			on:drop=move |ev: DragEvent| handle_file_drop(ev, on_drop_file)
+		>
 			<div class="absolute flex inset-0 mx-[-100%] h-full z-0">
 				<Pattern id="canvas" tiling colors reps_x=3 reps_y=1 />
 				<div class="absolute flex inset-0 w-full h-full">
@@ -163,7 +358,7 @@ pub fn Canvas(
 				</div>
 			</div>
 			<div class="relative z-1 w-full outline outline-2 outline-misc shadow-2xl ">
-				<Overlay tiling colors brush=brush.into() />
+				<Overlay tiling layers brush=brush.into() violations selection stamp />
 			</div>
 		</div>
 	}
@@ -1,15 +1,26 @@
 use std::mem;
 
 use leptos::{ev::Event, *};
+use web_sys::File;
 
-use crate::{cls, components::pattern::TileColor, tiling::TilingFormat};
+use crate::{
+	cls,
+	components::{
+		canvas::Brush,
+		file_actions::{OpenButton, SaveButton},
+		pattern::{GridColors, TileColor},
+		share_link::ShareButton
+	},
+	rules::Violation,
+	tiling::TilingFormat
+};
 
 #[component]
 fn BrushButton(
 	name: &'static str,
 	icon: &'static str,
-	color: TileColor,
-	brush: RwSignal<TileColor>
+	color: Brush,
+	brush: RwSignal<Brush>
 ) -> impl IntoView {
 	view! {
 		<button
@@ -18,9 +29,10 @@ fn BrushButton(
 			class=move || {
 				cls! {
 					"inline-flex h-full flex-1 justify-center items-center transition-colors sm:aspect-square",
-					match color { TileColor::Primary => "bg-primary text-primaryText ",
-					TileColor::Secondary => "bg-secondary text-secondaryText ", TileColor::None =>
-					"bg-background text-backgroundText " }, if brush() == color {
+					match color { Brush::Paint(TileColor::Primary) => "bg-primary text-primaryText ",
+					Brush::Paint(TileColor::Secondary) => "bg-secondary text-secondaryText ",
+					Brush::Paint(TileColor::None) | Brush::Lock | Brush::Unlock | Brush::Select |
+					Brush::Stamp => "bg-background text-backgroundText " }, if brush() == color {
 					"relative z-100 outline outline-3 outline-highlight " } else { " " }
 				}
 			}
@@ -33,12 +45,15 @@ fn BrushButton(
 }
 
 #[component]
-fn BrushControls(brush: RwSignal<TileColor>) -> impl IntoView {
+fn BrushControls(brush: RwSignal<Brush>) -> impl IntoView {
 	view! {
 		<div role="radiogroup" aria-label="Werkzeug auswählen" class="flex border-2 border-misc h-12">
-			<BrushButton name="Pinsel 1" icon="brush" color=TileColor::Primary brush/>
-			<BrushButton name="Pinsel 2" icon="brush" color=TileColor::Secondary brush/>
-			<BrushButton name="Radiergummi" icon="eraser" color=TileColor::None brush/>
+			<BrushButton name="Pinsel 1" icon="brush" color=Brush::Paint(TileColor::Primary) brush/>
+			<BrushButton name="Pinsel 2" icon="brush" color=Brush::Paint(TileColor::Secondary) brush/>
+			<BrushButton name="Radiergummi" icon="eraser" color=Brush::Paint(TileColor::None) brush/>
+			<BrushButton name="Sperren" icon="lock" color=Brush::Lock brush/>
+			<BrushButton name="Entsperren" icon="lock-open" color=Brush::Unlock brush/>
+			<BrushButton name="Auswählen" icon="selection" color=Brush::Select brush/>
 		</div>
 	}
 }
@@ -55,6 +70,92 @@ fn ExportButton(exporting: RwSignal<bool>) -> impl IntoView {
 	}
 }
 
+#[component]
+fn LetteringButton(lettering: RwSignal<bool>) -> impl IntoView {
+	view! {
+		<button
+			class="inline-flex justify-center items-center h-12 sm:aspect-square font-semibold border-2 border-misc transition-all hover:shadow-lg"
+			aria-label="Text einfügen"
+			on:click=move |_| lettering.set(true)
+		>
+			<box-icon name="text" size="md" color="currentColor"></box-icon>
+		</button>
+	}
+}
+
+#[component]
+fn LibraryButton(browsing_library: RwSignal<bool>) -> impl IntoView {
+	view! {
+		<button
+			class="inline-flex justify-center items-center h-12 sm:aspect-square font-semibold border-2 border-misc transition-all hover:shadow-lg"
+			aria-label="Bibliothek"
+			title="Bibliothek"
+			on:click=move |_| browsing_library.set(true)
+		>
+			<box-icon name="library" size="md" color="currentColor"></box-icon>
+		</button>
+	}
+}
+
+#[component]
+fn QrButton(showing_qr: RwSignal<bool>) -> impl IntoView {
+	view! {
+		<button
+			class="inline-flex justify-center items-center h-12 sm:aspect-square font-semibold border-2 border-misc transition-all hover:shadow-lg"
+			aria-label="QR-Code"
+			title="QR-Code"
+			on:click=move |_| showing_qr.set(true)
+		>
+			<box-icon name="qr" size="md" color="currentColor"></box-icon>
+		</button>
+	}
+}
+
+#[component]
+fn StampButton(stamping: RwSignal<bool>, brush: RwSignal<Brush>) -> impl IntoView {
+	view! {
+		<button
+			class=move || {
+				cls! {
+					"inline-flex justify-center items-center h-12 sm:aspect-square font-semibold border-2 transition-all hover:shadow-lg",
+					if brush() == Brush::Stamp { "border-highlight" } else { "border-misc" }
+				}
+			}
+			aria-label="Stempel"
+			on:click=move |_| stamping.set(true)
+		>
+			<box-icon name="shape-polygon" size="md" color="currentColor"></box-icon>
+		</button>
+	}
+}
+
+#[component]
+fn RulesButton(
+	checking_rules: RwSignal<bool>,
+	violations: Signal<Vec<Violation>>
+) -> impl IntoView {
+	let has_violations = move || violations.with(|v| !v.is_empty());
+
+	view! {
+		<button
+			class=move || {
+				cls! {
+					"inline-flex justify-center items-center h-12 sm:aspect-square font-semibold border-2 transition-all hover:shadow-lg",
+					if has_violations() { "border-highlight text-highlight" } else { "border-misc" }
+				}
+			}
+			aria-label="Gestaltungsregeln"
+			on:click=move |_| checking_rules.set(true)
+		>
+			<box-icon
+				name=move || if has_violations() { "error" } else { "check-shield" }
+				size="md"
+				color="currentColor"
+			></box-icon>
+		</button>
+	}
+}
+
 #[component]
 fn FormatSelector(format: RwSignal<TilingFormat>) -> impl IntoView {
 	let on_format_change = move |ev: Event| {
@@ -72,10 +173,30 @@ fn FormatSelector(format: RwSignal<TilingFormat>) -> impl IntoView {
 				aria-label="Format auswählen"
 				on:change=on_format_change
 			>
-				<option value=TilingFormat::F5X5 as u8>"5×5"</option>
-				<option value=TilingFormat::F10X10 as u8>"10×10"</option>
-				<option value=TilingFormat::F10X15 as u8>"10×15"</option>
-				<option value=TilingFormat::F15X15 as u8>"15×15"</option>
+				<option
+					value=TilingFormat::F5X5 as u8
+					selected=move || format() == TilingFormat::F5X5
+				>
+					"5×5"
+				</option>
+				<option
+					value=TilingFormat::F10X10 as u8
+					selected=move || format() == TilingFormat::F10X10
+				>
+					"10×10"
+				</option>
+				<option
+					value=TilingFormat::F10X15 as u8
+					selected=move || format() == TilingFormat::F10X15
+				>
+					"10×15"
+				</option>
+				<option
+					value=TilingFormat::F15X15 as u8
+					selected=move || format() == TilingFormat::F15X15
+				>
+					"15×15"
+				</option>
 			</select>
 			<box-icon
 				class="absolute right-0 z-[-1] h-full mx-1"
@@ -88,16 +209,32 @@ fn FormatSelector(format: RwSignal<TilingFormat>) -> impl IntoView {
 
 #[component]
 pub fn Controls(
-	brush: RwSignal<TileColor>,
+	brush: RwSignal<Brush>,
 	exporting: RwSignal<bool>,
-	format: RwSignal<TilingFormat>
+	lettering: RwSignal<bool>,
+	stamping: RwSignal<bool>,
+	checking_rules: RwSignal<bool>,
+	#[prop(into)] violations: Signal<Vec<Violation>>,
+	format: RwSignal<TilingFormat>,
+	#[prop(into)] colors: Signal<GridColors>,
+	saving: RwSignal<bool>,
+	browsing_library: RwSignal<bool>,
+	showing_qr: RwSignal<bool>,
+	on_open: Callback<File>
 ) -> impl IntoView {
 	view! {
-		<div class="p-3 sm:p-6 w-full max-w-sm flex justify-between gap-2 sm:gap-4 flex-col sm:flex-row">
+		<div class="p-3 sm:p-6 w-full max-w-2xl flex justify-between gap-2 sm:gap-4 flex-col sm:flex-row">
 			<FormatSelector format/>
 			<BrushControls brush/>
+			<LetteringButton lettering/>
+			<StampButton stamping brush/>
+			<RulesButton checking_rules violations/>
+			<ShareButton format colors/>
+			<QrButton showing_qr/>
+			<OpenButton on_open/>
+			<SaveButton saving/>
+			<LibraryButton browsing_library/>
 			<ExportButton exporting/>
 		</div>
 	}
 }
-
@@ -1,13 +1,26 @@
 use std::{mem, rc::Rc};
 
-use leptos::{ev::Event, *};
+use leptos::{ev::Event, leptos_dom::logging::console_error, *};
 
 use enum_iterator::all;
 
 use crate::{
-	components::pattern::Pattern,
-	export::{export_pattern, OutputFormat},
-	tiling::Tiling
+	artwork::{Artwork, RepeatFit},
+	bundle::write_bundle,
+	catalog::{theme_pages, write_catalog},
+	components::{
+		dialog::Dialog, file_actions::create_pattern_file, layers::LayerStack, pattern::Pattern,
+		rules_dialog::ViolationList, web_snippet::WebSnippet
+	},
+	export::{export_artwork, save_file, ExportOptions, ExportSize, LengthUnit, OutputFormat},
+	pattern_file::PatternInfo,
+	print::{self, ColorMode, PageSize, PrintOptions},
+	raster::{is_supported_size, MAX_DIMENSION, MAX_PIXELS},
+	rules::Violation,
+	storage,
+	template::TEMPLATE_FORMATS,
+	theme::{Rgb, ThemeColor, ThemeCtx},
+	tiling::{Tiling, TilingFormat}
 };
 
 use super::pattern::GridColors;
@@ -16,72 +29,593 @@ use super::pattern::GridColors;
 pub fn ExportDialog(
 	open: RwSignal<bool>,
 	#[prop(into)] tiling: Signal<Rc<Tiling>>,
-	#[prop(into)] colors: Signal<GridColors>
+	#[prop(into)] colors: Signal<GridColors>,
+	#[prop(into)] violations: Signal<Vec<Violation>>,
+	#[prop(into)] format: Signal<TilingFormat>,
+	#[prop(into)] layers: Signal<LayerStack>,
+	#[prop(into)] info: Signal<Option<PatternInfo>>
 ) -> impl IntoView {
-	let (reps, set_reps) = create_signal(3);
-	let (format, set_format) = create_signal(OutputFormat::Svg);
+	let theme = use_context::<ThemeCtx>().expect("ExportDialog is missing theme context!");
+	let (reps_x, set_reps_x) = create_signal(3);
+	let (reps_y, set_reps_y) = create_signal(1);
+	let fit = create_rw_signal(RepeatFit::Manual);
+	let repetitions = Signal::derive(move || {
+		tiling.with(|t| fit.get().repetitions(t, reps_x.get(), reps_y.get()))
+	});
+	let (output_format, set_output_format) = create_signal(OutputFormat::Svg);
+	let bundle_formats = create_rw_signal(vec![
+		OutputFormat::Svg,
+		OutputFormat::Pdf,
+		OutputFormat::Png,
+	]);
+	let options = create_rw_signal(ExportOptions {
+		print: storage::load(print::STORAGE_KEY).unwrap_or_default(),
+		..ExportOptions::default()
+	});
+	create_effect(move |_| options.with(|o| storage::save(print::STORAGE_KEY, &o.print)));
+	// Fitting to a size determines the export size, so that the squares are
+	// exported at the chosen size.
+	create_effect(move |_| {
+		let (reps_x, reps_y) = repetitions();
+		if let Some(size) = tiling.with(|t| fit().export_size(t, reps_x, reps_y)) {
+			options.update(|o| {
+				o.size = ExportSize {
+					dpi: o.size.dpi,
+					..size
+				}
+			});
+		}
+	});
 
-	let on_reps_change = move |ev: Event| {
+	let aspect_ratio = move || {
+		let (reps_x, reps_y) = repetitions();
+		tiling.with(|t| t.viewport_width() * reps_x as f32 / (t.viewport_height() * reps_y as f32))
+	};
+	let missing_inks = move || options.with(|o| o.print.missing_inks());
+	// Print PDFs can't be written without inks; templates are always RGB.
+	let needs_inks = move |format: OutputFormat| {
+		format == OutputFormat::Pdf
+			&& options.with(|o| !o.template.enabled)
+			&& !missing_inks().is_empty()
+	};
+	let size_fitted = move || matches!(fit(), RepeatFit::Size { .. });
+	let aspect_locked = move || options.with(|o| o.size.width.is_none() || o.size.height.is_none());
+	// Shows the side that follows from the aspect ratio.
+	let size_hint = move |side: usize| {
+		let (width, height) = options.with(|o| o.size.dimensions(aspect_ratio()));
+		let length = [width, height][side];
+		format!("{}", (length * 10.0).round() / 10.0)
+	};
+
+	let on_reps_x_change = move |ev: Event| {
+		let value = event_target_value(&ev);
+		set_reps_x(value.parse().expect("Range had unexpected value!"));
+	};
+
+	let on_reps_y_change = move |ev: Event| {
+		let value = event_target_value(&ev);
+		set_reps_y(value.parse().expect("Range had unexpected value!"));
+	};
+
+	let on_fit_change = move |ev: Event| {
 		let value = event_target_value(&ev);
-		set_reps(value.parse().expect("Range had unexpected value!"));
+		let index: usize = value.parse().expect("Select had unexpected value!");
+		fit.set(RepeatFit::ALL[index]);
 	};
 
 	let on_format_change = move |ev: Event| {
 		let value = event_target_value(&ev);
 		let value_u8: u8 = value.parse().expect("Select had unexpected value!");
-		set_format(unsafe { mem::transmute(value_u8) });
+		set_output_format(unsafe { mem::transmute(value_u8) });
 	};
 
 	let on_export = move |_| {
-		export_pattern("#export", "Pattern", format.get_untracked());
+		let info = info.get_untracked();
+		let theme = theme.get_untracked();
+		let output_format = output_format.get_untracked();
+		let colors = colors.with_untracked(|c| c.snapshot());
+		let tiling = tiling.get_untracked();
+		let (reps_x, reps_y) = repetitions.get_untracked();
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme,
+			reps_x,
+			reps_y
+		};
+		let title = info
+			.as_ref()
+			.map(|info| info.name.clone())
+			.filter(|name| !name.is_empty())
+			.unwrap_or_else(|| "Pattern".to_string());
+		let pattern_file =
+			layers.with_untracked(|l| create_pattern_file(info, format.get_untracked(), theme, l));
+		export_artwork(
+			&artwork,
+			&title,
+			"Pattern",
+			output_format,
+			Some(&pattern_file.to_json()),
+			&options.get_untracked()
+		);
 		open.set(false);
 	};
 
+	let on_export_catalog = move |_| {
+		let pattern_file = layers.with_untracked(|l| {
+			create_pattern_file(
+				info.get_untracked(),
+				format.get_untracked(),
+				theme.get_untracked(),
+				l
+			)
+		});
+		let title = if pattern_file.info.name.is_empty() {
+			"Pattern".to_string()
+		} else {
+			pattern_file.info.name.clone()
+		};
+		save_file(
+			write_catalog(&title, &theme_pages(&pattern_file)),
+			"Pattern-Themen.pdf"
+		);
+	};
+
+	let on_export_bundle = move |_| {
+		let colors = colors.with_untracked(|c| c.snapshot());
+		let tiling = tiling.get_untracked();
+		let (reps_x, reps_y) = repetitions.get_untracked();
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme: theme.get_untracked(),
+			reps_x,
+			reps_y
+		};
+		let pattern_file = layers.with_untracked(|l| {
+			create_pattern_file(
+				info.get_untracked(),
+				format.get_untracked(),
+				theme.get_untracked(),
+				l
+			)
+		});
+		// Keep the order of the format select, regardless of the order in
+		// which formats were checked.
+		let formats: Vec<OutputFormat> = bundle_formats.with_untracked(|f| {
+			all::<OutputFormat>()
+				.filter(|format| f.contains(format))
+				.collect()
+		});
+		match write_bundle(&artwork, &pattern_file, &formats, &options.get_untracked()) {
+			Ok(content) => save_file(content, "Pattern-Themen.zip"),
+			Err(err) => console_error(&format!("Failed to export bundle: {err}"))
+		}
+	};
+
 	view! {
-		<Show when=open>
-			<div class="z-40 w-screen h-screen absolute inset-0 bg-background/70 flex items-center justify-center overflow-hidden">
-				<section class="flex-1 max-w-2xl p-4 m-4 bg-primary text-primaryText shadow-xl">
-					<div class="flex mb-4">
-						<h1 class="flex-1 font-bold text-xl">"Muster exportieren"</h1>
-						<button
-							class="inline-block"
-							aria-label="Schließen"
-							on:click=move |_| open.set(false)
+		<Dialog id="exportDialog" open title="Muster exportieren">
+			<Show when=move || violations.with(|v| !v.is_empty())>
+				<div role="alert" class="mb-4 p-4 border-2 border-highlight">
+					<p class="font-semibold">"Das Muster verletzt die Gestaltungsregeln:"</p>
+					<ViolationList violations/>
+				</div>
+			</Show>
+			<div class="mb-2 p-4 flex justify-center h-80 bg-misc shadow-inner">
+				<Pattern
+					id="export"
+					export=true
+					tiling
+					colors
+					reps_x=Signal::derive(move || repetitions().0)
+					reps_y=Signal::derive(move || repetitions().1)
+				/>
+			</div>
+			<p class="mb-4 text-sm opacity-75">
+				{move || {
+					let (reps_x, reps_y) = repetitions();
+					match tiling.with(|t| fit().export_size(t, reps_x, reps_y)) {
+						Some(ExportSize { width: Some(width), height: Some(height), .. }) => {
+							format!("{reps_x} × {reps_y} Wiederholungen, {width} × {height} mm")
+						}
+						_ => format!("{reps_x} × {reps_y} Wiederholungen"),
+					}
+				}}
+			</p>
+			<div class="mb-4 flex flex-col sm:flex-row sm:flex-wrap sm:items-center gap-4">
+				<span class="inline-block h-12 bg-background text-backgroundText relative isolate">
+					<select
+						aria-label="Wiederholungen anpassen"
+						on:change=on_fit_change
+						class="appearance-none bg-transparent pl-6 pr-8 py-2 h-full"
+					>
+						{RepeatFit::ALL
+							.iter()
+							.enumerate()
+							.map(|(i, option)| {
+								let kind = mem::discriminant(option);
+								view! {
+									<option value=i selected=move || mem::discriminant(&fit()) == kind>
+										{option.label()}
+									</option>
+								}
+							})
+							.collect_view()}
+					</select>
+					<box-icon
+						class="absolute right-0 z-[-1] h-full mx-1"
+						name="chevron-down"
+						color="currentColor"
+					></box-icon>
+				</span>
+				<Show when=move || fit() == RepeatFit::Manual>
+					<span class="inline-flex flex-col">
+						<label for="exportRepsXRange">"Horizontal: " {reps_x}</label>
+						<input
+							type="range"
+							id="exportRepsXRange"
+							min="1"
+							max="6"
+							value=reps_x
+							on:input=on_reps_x_change
+						/>
+					</span>
+				</Show>
+				<Show when=move || !matches!(fit(), RepeatFit::Size { .. })>
+					<span class="inline-flex flex-col">
+						<label for="exportRepsYRange">"Vertikal: " {reps_y}</label>
+						<input
+							type="range"
+							id="exportRepsYRange"
+							min="1"
+							max="6"
+							value=reps_y
+							on:input=on_reps_y_change
+						/>
+					</span>
+				</Show>
+				{move || match fit() {
+					RepeatFit::Manual => ().into_view(),
+					RepeatFit::AspectRatio { width, height } => {
+						view! {
+							<label class="inline-flex items-center gap-2">
+								<input
+									type="number"
+									class="w-20 h-12 px-4 bg-background text-backgroundText"
+									aria-label="Seitenverhältnis Breite"
+									min="1"
+									step="any"
+									prop:value=width
+									on:change=move |ev| {
+										if let Ok(width) = event_target_value(&ev).parse::<f32>() {
+											if width > 0.0 {
+												fit.set(RepeatFit::AspectRatio { width, height });
+											}
+										}
+									}
+								/>
+								":"
+								<input
+									type="number"
+									class="w-20 h-12 px-4 bg-background text-backgroundText"
+									aria-label="Seitenverhältnis Höhe"
+									min="1"
+									step="any"
+									prop:value=height
+									on:change=move |ev| {
+										if let Ok(height) = event_target_value(&ev).parse::<f32>() {
+											if height > 0.0 {
+												fit.set(RepeatFit::AspectRatio { width, height });
+											}
+										}
+									}
+								/>
+							</label>
+						}
+							.into_view()
+					}
+					RepeatFit::Size { width, height, square_size } => {
+						let size_input = move |label: &'static str, value: f32, set: fn(&mut RepeatFit, f32)| {
+							view! {
+								<input
+									type="number"
+									class="w-24 h-12 px-4 bg-background text-backgroundText"
+									aria-label=label
+									min="1"
+									step="any"
+									prop:value=value
+									on:change=move |ev| {
+										if let Ok(value) = event_target_value(&ev).parse::<f32>() {
+											if value > 0.0 {
+												fit.update(|fit| set(fit, value));
+											}
+										}
+									}
+								/>
+							}
+						};
+						view! {
+							<label class="inline-flex items-center gap-2">
+								{size_input(
+									"Breite",
+									width,
+									|fit, value| {
+										if let RepeatFit::Size { width, .. } = fit {
+											*width = value;
+										}
+									},
+								)}
+								"×"
+								{size_input(
+									"Höhe",
+									height,
+									|fit, value| {
+										if let RepeatFit::Size { height, .. } = fit {
+											*height = value;
+										}
+									},
+								)}
+								"mm"
+							</label>
+							<label class="inline-flex items-center gap-2">
+								"Quadrat"
+								{size_input(
+									"Quadratgröße",
+									square_size,
+									|fit, value| {
+										if let RepeatFit::Size { square_size, .. } = fit {
+											*square_size = value;
+										}
+									},
+								)}
+								"mm"
+							</label>
+						}
+							.into_view()
+					}
+				}}
+			</div>
+			<Show when=move || output_format() != OutputFormat::Dxf>
+				<div class="mb-4 flex flex-col sm:flex-row sm:flex-wrap sm:items-center gap-4">
+					<label class="inline-flex items-center gap-2">
+						<input
+							type="number"
+							class="w-28 h-12 px-4 bg-background text-backgroundText"
+							aria-label="Breite"
+							disabled=size_fitted
+							min="0"
+							step="any"
+							placeholder=move || size_hint(0)
+							prop:value=move || {
+								options.with(|o| o.size.width.map(|w| w.to_string()).unwrap_or_default())
+							}
+							on:change=move |ev| {
+								let width = event_target_value(&ev).parse::<f32>().ok().filter(|w| *w > 0.0);
+								options
+									.update(|o| {
+										o.size.width = width;
+										if width.is_some() && aspect_locked() {
+											o.size.height = None;
+										}
+									});
+							}
+						/>
+						"×"
+						<input
+							type="number"
+							class="w-28 h-12 px-4 bg-background text-backgroundText"
+							aria-label="Höhe"
+							disabled=size_fitted
+							min="0"
+							step="any"
+							placeholder=move || size_hint(1)
+							prop:value=move || {
+								options.with(|o| o.size.height.map(|h| h.to_string()).unwrap_or_default())
+							}
+							on:change=move |ev| {
+								let height = event_target_value(&ev).parse::<f32>().ok().filter(|h| *h > 0.0);
+								options
+									.update(|o| {
+										o.size.height = height;
+										if height.is_some() && aspect_locked() {
+											o.size.width = None;
+										}
+									});
+							}
+						/>
+					</label>
+					<span class="inline-block h-12 bg-background text-backgroundText relative isolate">
+						<select
+							aria-label="Einheit"
+							disabled=size_fitted
+							class="appearance-none bg-transparent pl-6 pr-8 py-2 h-full"
+							on:change=move |ev| {
+								let value: u8 = event_target_value(&ev)
+									.parse()
+									.expect("Select had unexpected value!");
+								let unit: LengthUnit = unsafe { mem::transmute(value) };
+								options.update(|o| o.size = o.size.with_unit(unit));
+							}
 						>
-							<box-icon name="x" color="currentColor"></box-icon>
-						</button>
-					</div>
-					<div class="mb-4 p-4 flex justify-center h-80 bg-misc shadow-inner">
-						<Pattern id="export" export=true tiling colors reps_x=reps reps_y=1/>
-					</div>
-					<div class="flex flex-col w-full sm:flex-row justify-between gap-4">
-						<span class="inline-flex flex-col">
-							<label for="exportRepsRange">Wiederholungen: {reps}</label>
-							<br/>
+							{all::<LengthUnit>()
+								.map(|unit| {
+									view! {
+										<option
+											value=unit as u8
+											selected=move || options.with(|o| o.size.unit == unit)
+										>
+											{unit.label()}
+										</option>
+									}
+								})
+								.collect_view()}
+						</select>
+						<box-icon
+							class="absolute right-0 z-[-1] h-full mx-1"
+							name="chevron-down"
+							color="currentColor"
+						></box-icon>
+					</span>
+					<label class="inline-flex items-center gap-2">
+						"Auflösung"
+						<input
+							type="number"
+							class="w-24 h-12 px-4 bg-background text-backgroundText"
+							min="1"
+							prop:value=move || options.with(|o| o.size.dpi)
+							on:change=move |ev| {
+								if let Ok(dpi) = event_target_value(&ev).parse::<u32>() {
+									options.update(|o| o.size.dpi = dpi.max(1));
+								}
+							}
+						/>
+						"dpi"
+					</label>
+					<span class="inline-flex gap-2 items-center">
+						<input
+							type="checkbox"
+							id="exportAspectLock"
+							disabled=size_fitted
+							prop:checked=aspect_locked
+							on:change=move |ev| {
+								let locked = event_target_checked(&ev);
+								let (width, height) = options.with_untracked(|o| o.size.dimensions(aspect_ratio()));
+								options
+									.update(|o| {
+										if locked {
+											o.size.height = None;
+											o.size.width.get_or_insert(width);
+										} else {
+											o.size.width = Some(width);
+											o.size.height = Some(height);
+										}
+									});
+							}
+						/>
+						<label for="exportAspectLock">"Seitenverhältnis beibehalten"</label>
+					</span>
+				</div>
+			</Show>
+			<Show when=move || TEMPLATE_FORMATS.contains(&output_format())>
+				<div class="mb-4 flex flex-col sm:flex-row sm:flex-wrap sm:items-center gap-4">
+					<span class="inline-flex gap-2 items-center">
+						<input
+							type="checkbox"
+							id="exportTemplate"
+							prop:checked=move || options.with(|o| o.template.enabled)
+							on:change=move |ev| {
+								options.update(|o| o.template.enabled = event_target_checked(&ev))
+							}
+						/>
+						<label for="exportTemplate">"Rastervorlage"</label>
+					</span>
+					<Show when=move || options.with(|o| o.template.enabled)>
+						<span class="inline-flex gap-2 items-center">
 							<input
-								type="range"
-								id="exportRepsRange"
-								min="1"
-								max="6"
-								value=reps
-								on:input=on_reps_change
+								type="checkbox"
+								id="exportTemplateFilled"
+								prop:checked=move || options.with(|o| o.template.filled)
+								on:change=move |ev| {
+									options.update(|o| o.template.filled = event_target_checked(&ev))
+								}
 							/>
+							<label for="exportTemplateFilled">"Kacheln ausfüllen"</label>
 						</span>
-						<span class="flex-1"></span>
+						<span class="inline-flex gap-2 items-center">
+							<input
+								type="checkbox"
+								id="exportTemplateNumbering"
+								prop:checked=move || options.with(|o| o.template.numbering)
+								on:change=move |ev| {
+									options.update(|o| o.template.numbering = event_target_checked(&ev))
+								}
+							/>
+							<label for="exportTemplateNumbering">"Zeilen und Spalten nummerieren"</label>
+						</span>
+						<label class="inline-flex items-center gap-2">
+							"Linienstärke"
+							<input
+								type="number"
+								class="w-24 h-12 px-4 bg-background text-backgroundText"
+								min="0.05"
+								step="0.05"
+								prop:value=move || options.with(|o| o.template.line_width)
+								on:change=move |ev| {
+									if let Ok(width) = event_target_value(&ev).parse::<f32>() {
+										if width > 0.0 {
+											options.update(|o| o.template.line_width = width);
+										}
+									}
+								}
+							/>
+							"mm"
+						</label>
+						<label class="inline-flex items-center gap-2">
+							"Linienfarbe"
+							<input
+								type="color"
+								class="w-12 h-12 bg-background"
+								prop:value=move || options.with(|o| o.template.line_color.hex())
+								on:input=move |ev| {
+									if let Some(color) = Rgb::parse(&event_target_value(&ev)) {
+										options.update(|o| o.template.line_color = color);
+									}
+								}
+							/>
+						</label>
+					</Show>
+				</div>
+			</Show>
+			<Show when=move || output_format() == OutputFormat::Png>
+				<div class="mb-4 flex flex-col sm:flex-row sm:items-center gap-4">
+					<span>
+						{move || {
+							let (width, height) = options.with(|o| o.size.pixels(aspect_ratio()));
+							if !is_supported_size(width, height) {
+								format!(
+									"{width} × {height} px, höchstens {MAX_DIMENSION} px je Seite und {} Megapixel möglich",
+									MAX_PIXELS / 1_000_000
+								)
+							} else {
+								format!("{width} × {height} px")
+							}
+						}}
+					</span>
+					<span class="inline-flex gap-2 items-center">
+						<input
+							type="checkbox"
+							id="exportAntiAliasing"
+							prop:checked=move || options.with(|o| o.raster.anti_aliasing)
+							on:change=move |ev| {
+								options.update(|o| o.raster.anti_aliasing = event_target_checked(&ev))
+							}
+						/>
+						<label for="exportAntiAliasing">"Kantenglättung"</label>
+					</span>
+				</div>
+			</Show>
+			<Show when=move || output_format() == OutputFormat::Pdf>
+				<div class="mb-4 flex flex-col gap-4">
+					<div class="flex flex-col sm:flex-row sm:items-center gap-4">
 						<span class="inline-block h-12 bg-background text-backgroundText relative isolate">
 							<select
-								aria-label="Dateiformat"
-								on:change=on_format_change
+								aria-label="Farbmodus"
 								class="appearance-none bg-transparent pl-6 pr-8 py-2 h-full"
+								on:change=move |ev| {
+									let value: u8 = event_target_value(&ev)
+										.parse()
+										.expect("Select had unexpected value!");
+									options.update(|o| o.print.mode = unsafe { mem::transmute(value) });
+								}
 							>
-								{all::<OutputFormat>()
-									.map(|opt_format| {
+								{all::<ColorMode>()
+									.map(|mode| {
 										view! {
 											<option
-												value=opt_format as u8
-												selected=move || format() == opt_format
+												value=mode as u8
+												selected=move || options.with(|o| o.print.mode == mode)
 											>
-												{opt_format.to_string()}
+												{mode.label()}
 											</option>
 										}
 									})
@@ -93,16 +627,360 @@ pub fn ExportDialog(
 								color="currentColor"
 							></box-icon>
 						</span>
+						<span class="flex-1"></span>
 						<button
-							class="px-6 py-2 h-12 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight"
-							on:click=on_export
+							class="px-6 py-2 h-12 bg-background text-backgroundText hover:outline outline-2 outline-highlight"
+							on:click=move |_| {
+								options
+									.update(|o| {
+										o.print = PrintOptions {
+											layout: o.print.layout,
+											..PrintOptions::default()
+										};
+									})
+							}
 						>
-							"Exportieren"
+							"Standardwerte"
+						</button>
+						<button
+							class="px-6 py-2 h-12 bg-background text-backgroundText hover:outline outline-2 outline-highlight"
+							on:click=on_export_catalog
+						>
+							"Katalog aller Themen"
 						</button>
 					</div>
-				</section>
+					<div class="flex flex-col sm:flex-row sm:flex-wrap sm:items-center gap-4">
+						<span class="inline-block h-12 bg-background text-backgroundText relative isolate">
+							<select
+								aria-label="Seitenformat"
+								class="appearance-none bg-transparent pl-6 pr-8 py-2 h-full"
+								on:change=move |ev| {
+									let value: u8 = event_target_value(&ev)
+										.parse()
+										.expect("Select had unexpected value!");
+									options
+										.update(|o| o.print.layout.page = unsafe { mem::transmute(value) });
+								}
+							>
+								{all::<PageSize>()
+									.map(|page| {
+										view! {
+											<option
+												value=page as u8
+												selected=move || options.with(|o| o.print.layout.page == page)
+											>
+												{page.label()}
+											</option>
+										}
+									})
+									.collect_view()}
+							</select>
+							<box-icon
+								class="absolute right-0 z-[-1] h-full mx-1"
+								name="chevron-down"
+								color="currentColor"
+							></box-icon>
+						</span>
+						<Show when=move || options.with(|o| o.print.layout.page == PageSize::Custom)>
+							<label class="inline-flex items-center gap-2">
+								<input
+									type="number"
+									class="w-24 h-12 px-4 bg-background text-backgroundText"
+									aria-label="Seitenbreite"
+									min="1"
+									step="any"
+									prop:value=move || options.with(|o| o.print.layout.custom_size.0)
+									on:change=move |ev| {
+										if let Ok(width) = event_target_value(&ev).parse::<f32>() {
+											if width > 0.0 {
+												options.update(|o| o.print.layout.custom_size.0 = width);
+											}
+										}
+									}
+								/>
+								"×"
+								<input
+									type="number"
+									class="w-24 h-12 px-4 bg-background text-backgroundText"
+									aria-label="Seitenhöhe"
+									min="1"
+									step="any"
+									prop:value=move || options.with(|o| o.print.layout.custom_size.1)
+									on:change=move |ev| {
+										if let Ok(height) = event_target_value(&ev).parse::<f32>() {
+											if height > 0.0 {
+												options.update(|o| o.print.layout.custom_size.1 = height);
+											}
+										}
+									}
+								/>
+								"mm"
+							</label>
+						</Show>
+						<Show when=move || options.with(|o| o.print.layout.page != PageSize::Pattern)>
+							<span class="inline-flex gap-2 items-center">
+								<input
+									type="checkbox"
+									id="exportPrintLandscape"
+									prop:checked=move || options.with(|o| o.print.layout.landscape)
+									on:change=move |ev| {
+										options.update(|o| o.print.layout.landscape = event_target_checked(&ev))
+									}
+								/>
+								<label for="exportPrintLandscape">"Querformat"</label>
+							</span>
+						</Show>
+						<label class="inline-flex items-center gap-2">
+							"Beschnitt"
+							<input
+								type="number"
+								class="w-20 h-12 px-4 bg-background text-backgroundText"
+								min="0"
+								step="any"
+								prop:value=move || options.with(|o| o.print.layout.bleed)
+								on:change=move |ev| {
+									if let Ok(bleed) = event_target_value(&ev).parse::<f32>() {
+										options.update(|o| o.print.layout.bleed = bleed.max(0.0));
+									}
+								}
+							/>
+							"mm"
+						</label>
+						<span class="inline-flex gap-2 items-center">
+							<input
+								type="checkbox"
+								id="exportPrintMarks"
+								prop:checked=move || options.with(|o| o.print.layout.marks)
+								on:change=move |ev| {
+									options.update(|o| o.print.layout.marks = event_target_checked(&ev))
+								}
+							/>
+							<label for="exportPrintMarks">"Schnitt- und Passermarken"</label>
+						</span>
+					</div>
+					<Show when=move || options.with(|o| o.print.mode != ColorMode::Rgb)>
+						<table class="w-full">
+							<thead>
+								<tr class="text-left">
+									<th>"Farbe"</th>
+									<th>"C"</th>
+									<th>"M"</th>
+									<th>"Y"</th>
+									<th>"K"</th>
+									<Show when=move || options.with(|o| o.print.mode == ColorMode::Spot)>
+										<th>"Sonderfarbe"</th>
+									</Show>
+								</tr>
+							</thead>
+							<tbody>
+								{all::<ThemeColor>()
+									.map(|color| {
+										view! {
+											<tr>
+												<td>
+													<span class="inline-flex gap-2 items-center">
+														<span
+															class="inline-block w-4 h-4 border border-backgroundText"
+															style:background-color=color.rgb().to_string()
+														></span>
+														{color.name()}
+													</span>
+												</td>
+												{(0..4)
+													.map(|channel| {
+														view! {
+															<td>
+																<input
+																	type="number"
+																	class="w-20 h-12 px-2 bg-background text-backgroundText"
+																	min="0"
+																	max="100"
+																	step="any"
+																	aria-label=format!("{} {}", color.name(), ["C", "M", "Y", "K"][channel])
+																	prop:value=move || {
+																		options
+																			.with(|o| {
+																				o.print
+																					.ink(color)
+																					.cmyk
+																					.map(|cmyk| (cmyk[channel] * 100.0).to_string())
+																					.unwrap_or_default()
+																			})
+																	}
+																	on:change=move |ev| {
+																		if let Ok(percent) = event_target_value(&ev).parse::<f32>() {
+																			options
+																				.update(|o| {
+																					let cmyk = o.print.ink_mut(color).cmyk.get_or_insert([0.0; 4]);
+																					cmyk[channel] = percent.clamp(0.0, 100.0) / 100.0;
+																				});
+																		}
+																	}
+																/>
+															</td>
+														}
+													})
+													.collect_view()}
+												<Show when=move || options.with(|o| o.print.mode == ColorMode::Spot)>
+													<td>
+														<input
+															type="text"
+															class="w-full h-12 px-2 bg-background text-backgroundText"
+															aria-label=format!("{} Sonderfarbe", color.name())
+															placeholder="Keine"
+															prop:value=move || options.with(|o| o.print.ink(color).spot.clone())
+															on:change=move |ev| {
+																options.update(|o| o.print.ink_mut(color).spot = event_target_value(&ev))
+															}
+														/>
+													</td>
+												</Show>
+											</tr>
+										}
+									})
+									.collect_view()}
+							</tbody>
+						</table>
+						<p class="text-sm opacity-75">
+							"Für die Themenfarben gibt es keine freigegebenen Druckfarben. Bitte die CMYK-Werte der Druckerei für alle Farben eintragen, bevor ein PDF exportiert wird. Farben ohne Sonderfarbe werden in CMYK gedruckt."
+						</p>
+						<Show when=move || !missing_inks().is_empty()>
+							<p role="alert" class="p-4 border-2 border-highlight">
+								{move || {
+									let names: Vec<&str> = missing_inks()
+										.into_iter()
+										.map(|color| color.name())
+										.collect();
+									format!("CMYK-Werte fehlen für: {}", names.join(", "))
+								}}
+							</p>
+						</Show>
+					</Show>
+				</div>
+			</Show>
+			<Show when=move || output_format() == OutputFormat::Dxf>
+				<div class="mb-4 flex flex-col sm:flex-row sm:items-center gap-4">
+					<label class="inline-flex items-center gap-2">
+						"Quadrat"
+						<input
+							type="number"
+							class="w-28 h-12 px-4 bg-background text-backgroundText"
+							min="1"
+							step="any"
+							prop:value=move || options.with(|o| o.dxf.square_size)
+							on:change=move |ev| {
+								if let Ok(size) = event_target_value(&ev).parse::<f32>() {
+									if size > 0.0 {
+										options.update(|o| o.dxf.square_size = size);
+									}
+								}
+							}
+						/>
+						"mm"
+					</label>
+					<span class="inline-flex gap-2 items-center">
+						<input
+							type="checkbox"
+							id="exportDxfBackground"
+							prop:checked=move || options.with(|o| o.dxf.include_background)
+							on:change=move |ev| {
+								options.update(|o| o.dxf.include_background = event_target_checked(&ev))
+							}
+						/>
+						<label for="exportDxfBackground">"Hintergrund als eigene Ebene"</label>
+					</span>
+					<span class="inline-flex gap-2 items-center">
+						<input
+							type="checkbox"
+							id="exportDxfCutLines"
+							prop:checked=move || options.with(|o| o.dxf.cut_lines_only)
+							on:change=move |ev| {
+								options.update(|o| o.dxf.cut_lines_only = event_target_checked(&ev))
+							}
+						/>
+						<label for="exportDxfCutLines">"Nur Schnittlinien"</label>
+					</span>
+				</div>
+			</Show>
+			<WebSnippet tiling colors/>
+			<div class="mb-4 flex flex-col sm:flex-row sm:flex-wrap sm:items-center gap-4">
+				<span>"Alle Themen:"</span>
+				{all::<OutputFormat>()
+					.map(|opt_format| {
+						let id = format!("exportBundle{}", opt_format.extension());
+						view! {
+							<span class="inline-flex gap-2 items-center">
+								<input
+									type="checkbox"
+									id=id.clone()
+									prop:checked=move || bundle_formats.with(|f| f.contains(&opt_format))
+									on:change=move |ev| {
+										bundle_formats
+											.update(|f| {
+												f.retain(|format| *format != opt_format);
+												if event_target_checked(&ev) {
+													f.push(opt_format);
+												}
+											})
+									}
+								/>
+								<label for=id>{opt_format.to_string()}</label>
+							</span>
+						}
+					})
+					.collect_view()}
+				<span class="flex-1"></span>
+				<button
+					class="px-6 py-2 h-12 bg-background text-backgroundText hover:outline outline-2 outline-highlight disabled:opacity-50"
+					disabled=move || {
+						bundle_formats.with(|f| f.is_empty() || f.iter().any(|format| needs_inks(*format)))
+					}
+					on:click=on_export_bundle
+				>
+					"Als ZIP exportieren"
+				</button>
+			</div>
+			<div class="flex flex-col w-full sm:flex-row justify-between gap-4">
+				<span class="flex-1"></span>
+				<span class="inline-block h-12 bg-background text-backgroundText relative isolate">
+					<select
+						aria-label="Dateiformat"
+						on:change=on_format_change
+						class="appearance-none bg-transparent pl-6 pr-8 py-2 h-full"
+					>
+						{all::<OutputFormat>()
+							.map(|opt_format| {
+								view! {
+									<option
+										value=opt_format as u8
+										selected=move || output_format() == opt_format
+									>
+										{opt_format.to_string()}
+									</option>
+								}
+							})
+							.collect_view()}
+					</select>
+					<box-icon
+						class="absolute right-0 z-[-1] h-full mx-1"
+						name="chevron-down"
+						color="currentColor"
+					></box-icon>
+				</span>
+				<button
+					class="px-6 py-2 h-12 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight disabled:opacity-50"
+					disabled=move || needs_inks(output_format())
+					on:click=on_export
+				>
+					{move || {
+						if violations.with(|v| v.is_empty()) {
+							"Exportieren"
+						} else {
+							"Trotzdem exportieren"
+						}
+					}}
+				</button>
 			</div>
-		</Show>
+		</Dialog>
 	}
 }
-
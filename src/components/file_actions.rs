@@ -0,0 +1,268 @@
+use std::{error::Error, path::Path};
+
+use leptos::{ev::Event, leptos_dom::logging::console_error, *};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{DragEvent, File, HtmlInputElement};
+
+use crate::{
+	components::{dialog::Dialog, layers::LayerStack},
+	document::PatternData,
+	export::save_file,
+	pattern_file::{PatternFile, PatternInfo, EXTENSION},
+	storage,
+	svg_import::{import_svg, import_svg_as, ImportedSvg, SvgImportError},
+	theme::{Theme, ThemeCtx},
+	tiling::TilingFormat
+};
+
+const AUTHOR_STORAGE_KEY: &str = "author";
+
+pub fn now() -> String {
+	String::from(js_sys::Date::new_0().to_iso_string())
+}
+
+/// Bundles the current pattern into a file. Without `info`, the pattern is
+/// treated as a new, unnamed one.
+pub fn create_pattern_file(
+	info: Option<PatternInfo>,
+	format: TilingFormat,
+	theme: Theme,
+	layers: &LayerStack
+) -> PatternFile {
+	let timestamp = now();
+	let info = info.unwrap_or_else(|| PatternInfo {
+		name: String::new(),
+		author: String::new(),
+		created: timestamp.clone()
+	});
+	let (layer_data, locks) = layers.data();
+	PatternFile::new(
+		info,
+		timestamp,
+		PatternData {
+			format,
+			theme,
+			layers: layer_data,
+			locks
+		}
+	)
+}
+
+/// Asks the user which of `themes` an SVG without metadata is in.
+fn ask_theme(themes: &[Theme]) -> Result<Theme, Box<dyn Error>> {
+	let labels: Vec<&str> = themes.iter().map(|theme| theme.label()).collect();
+	let answer = window()
+		.prompt_with_message_and_default(
+			&format!(
+				"Die Farben passen zu mehreren Themen: {}. Welches Thema hat das Muster?",
+				labels.join(", ")
+			),
+			labels[0]
+		)
+		.ok()
+		.flatten()
+		.ok_or("No theme was chosen")?;
+	themes
+		.iter()
+		.copied()
+		.find(|theme| theme.label().eq_ignore_ascii_case(answer.trim()))
+		.ok_or_else(|| format!("Unknown theme '{answer}'").into())
+}
+
+/// Reads a pattern file or an SVG that was exported before.
+pub async fn read_pattern_file(file: File) -> Result<PatternFile, Box<dyn Error>> {
+	let text = JsFuture::from(file.text())
+		.await
+		.map_err(|err| format!("Failed to read {}: {err:?}", file.name()))?;
+	let text = text.as_string().unwrap_or_default();
+	let name = file.name();
+	let path = Path::new(&name);
+	if !path
+		.extension()
+		.is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+	{
+		return Ok(PatternFile::from_json(&text)?);
+	}
+	let imported = match import_svg(&text) {
+		Err(SvgImportError::AmbiguousTheme(themes)) => import_svg_as(&text, ask_theme(&themes)?)?,
+		imported => imported?
+	};
+	match imported {
+		ImportedSvg::Document(pattern_file) => Ok(pattern_file),
+		ImportedSvg::Reconstructed(pattern) => {
+			let timestamp = now();
+			let info = PatternInfo {
+				name: path
+					.file_stem()
+					.map(|stem| stem.to_string_lossy().into_owned())
+					.unwrap_or_default(),
+				author: String::new(),
+				created: timestamp.clone()
+			};
+			Ok(PatternFile::new(info, timestamp, pattern))
+		}
+	}
+}
+
+/// Reads a file that was dropped onto the page and passes it on to
+/// `on_open`.
+pub fn handle_file_drop(ev: DragEvent, on_open: Callback<File>) {
+	ev.prevent_default();
+	let Some(file) = ev
+		.data_transfer()
+		.and_then(|data| data.files())
+		.and_then(|files| files.get(0))
+	else {
+		return;
+	};
+	on_open(file);
+}
+
+/// Opens a pattern file, reporting errors to the user.
+pub fn open_pattern_file(file: File, on_load: Callback<PatternFile>) {
+	spawn_local(async move {
+		match read_pattern_file(file).await {
+			Ok(pattern_file) => on_load(pattern_file),
+			Err(err) => {
+				console_error(&format!("Failed to open pattern file: {err}"));
+				window()
+					.alert_with_message("Die Datei konnte nicht geöffnet werden.")
+					.unwrap_or(());
+			}
+		}
+	});
+}
+
+#[component]
+pub fn SaveButton(saving: RwSignal<bool>) -> impl IntoView {
+	view! {
+		<button
+			class="inline-flex justify-center items-center h-12 sm:aspect-square font-semibold border-2 border-misc transition-all hover:shadow-lg"
+			aria-label="Muster speichern"
+			title="Muster speichern"
+			on:click=move |_| saving.set(true)
+		>
+			<box-icon name="save" size="md" color="currentColor"></box-icon>
+		</button>
+	}
+}
+
+#[component]
+pub fn OpenButton(on_open: Callback<File>) -> impl IntoView {
+	let input_ref = create_node_ref::<html::Input>();
+
+	let on_change = move |ev: Event| {
+		let input: HtmlInputElement = event_target(&ev);
+		if let Some(file) = input.files().and_then(|files| files.get(0)) {
+			on_open(file);
+		}
+		// Allows opening the same file again.
+		input.set_value("");
+	};
+
+	view! {
+		<button
+			class="inline-flex justify-center items-center h-12 sm:aspect-square font-semibold border-2 border-misc transition-all hover:shadow-lg"
+			aria-label="Muster öffnen"
+			title="Muster öffnen"
+			on:click=move |_| {
+				if let Some(input) = input_ref.get_untracked() {
+					input.click();
+				}
+			}
+		>
+			<box-icon name="folder-open" size="md" color="currentColor"></box-icon>
+		</button>
+		<input
+			type="file"
+			class="hidden"
+			accept=format!(".{EXTENSION},.svg")
+			node_ref=input_ref
+			on:change=on_change
+		/>
+	}
+}
+
+/// Asks for the name and author of the pattern and downloads it as a
+/// pattern file.
+#[component]
+pub fn SaveDialog(
+	open: RwSignal<bool>,
+	info: RwSignal<Option<PatternInfo>>,
+	#[prop(into)] format: Signal<TilingFormat>,
+	#[prop(into)] layers: Signal<LayerStack>
+) -> impl IntoView {
+	let theme = use_context::<ThemeCtx>().expect("SaveDialog is missing theme context!");
+	let name = create_rw_signal(String::new());
+	let author = create_rw_signal(String::new());
+
+	create_effect(move |_| {
+		if !open() {
+			return;
+		}
+		let current = info.get_untracked();
+		name.set(current.as_ref().map(|i| i.name.clone()).unwrap_or_default());
+		author.set(
+			current
+				.map(|i| i.author)
+				.or_else(|| storage::load(AUTHOR_STORAGE_KEY))
+				.unwrap_or_default()
+		);
+	});
+
+	let on_save = move |_| {
+		let saved_info = PatternInfo {
+			name: name.get_untracked(),
+			author: author.get_untracked(),
+			created: info
+				.with_untracked(|i| i.as_ref().map(|i| i.created.clone()))
+				.unwrap_or_else(now)
+		};
+		let pattern_file = layers.with_untracked(|l| {
+			create_pattern_file(
+				Some(saved_info.clone()),
+				format.get_untracked(),
+				theme.get_untracked(),
+				l
+			)
+		});
+		save_file(
+			pattern_file.to_json().into_bytes(),
+			&pattern_file.file_name()
+		);
+		storage::save(AUTHOR_STORAGE_KEY, &saved_info.author);
+		info.set(Some(saved_info));
+		open.set(false);
+	};
+
+	view! {
+		<Dialog id="saveDialog" open title="Muster speichern">
+			<div class="flex flex-col gap-4">
+				<label class="flex flex-col gap-1">
+					"Name"
+					<input
+						type="text"
+						class="h-12 px-4 bg-background text-backgroundText"
+						prop:value=name
+						on:input=move |ev| name.set(event_target_value(&ev))
+					/>
+				</label>
+				<label class="flex flex-col gap-1">
+					"Autor"
+					<input
+						type="text"
+						class="h-12 px-4 bg-background text-backgroundText"
+						prop:value=author
+						on:input=move |ev| author.set(event_target_value(&ev))
+					/>
+				</label>
+				<button
+					class="self-end px-6 py-2 h-12 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight"
+					on:click=on_save
+				>
+					{format!("Als .{EXTENSION} speichern")}
+				</button>
+			</div>
+		</Dialog>
+	}
+}
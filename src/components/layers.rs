@@ -0,0 +1,619 @@
+use std::{error::Error, fmt, iter, mem};
+
+use leptos::{ev::MouseEvent, *};
+use uuid::Uuid;
+
+use crate::{
+	cls,
+	components::pattern::{GridColors, TileColor},
+	document::LayerData
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+	pub id: Uuid,
+	pub name: RwSignal<String>,
+	pub colors: GridColors,
+	pub visible: RwSignal<bool>,
+	pub locked: RwSignal<bool>
+}
+
+impl Layer {
+	fn new(name: String, size: usize) -> Self {
+		Self {
+			id: Uuid::new_v4(),
+			name: create_rw_signal(name),
+			colors: GridColors::new(size),
+			visible: create_rw_signal(true),
+			locked: create_rw_signal(false)
+		}
+	}
+
+	fn dispose(self) {
+		self.name.dispose();
+		self.colors.dispose();
+		self.visible.dispose();
+		self.locked.dispose();
+	}
+}
+
+/// Layer data that doesn't fit the tiling of the stack it is loaded into.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+	NoLayers,
+	/// A layer or the lock mask has `found` tiles instead of `expected`.
+	SizeMismatch {
+		expected: usize,
+		found: usize
+	}
+}
+
+impl fmt::Display for LoadError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::NoLayers => write!(f, "Pattern has no layers"),
+			Self::SizeMismatch { expected, found } => write!(
+				f,
+				"Pattern has {found} tiles, but the tiling has {expected}"
+			)
+		}
+	}
+}
+
+impl Error for LoadError {}
+
+/// Tiles that are protected from painting, independent of the layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockMask(Vec<RwSignal<bool>>);
+
+impl LockMask {
+	fn new(size: usize) -> Self {
+		Self(
+			iter::repeat_with(|| create_rw_signal(false))
+				.take(size)
+				.collect()
+		)
+	}
+
+	pub fn get_locked(&self, i: usize) -> RwSignal<bool> {
+		self.0[i]
+	}
+}
+
+/// An ordered stack of layers, from bottom to top, together with the
+/// flattened result that is shown in the preview and exported, and the mask
+/// of locked tiles.
+///
+/// Tiles with [`TileColor::None`] are transparent, so each tile of the
+/// flattened result has the color of the topmost visible layer that paints
+/// it.
+#[derive(Debug, Clone, Copy)]
+pub struct LayerStack {
+	layers: RwSignal<Vec<Layer>>,
+	active: RwSignal<Uuid>,
+	composite: StoredValue<GridColors>,
+	locks: StoredValue<LockMask>,
+	size: usize,
+	owner: Option<Owner>
+}
+
+impl PartialEq for LayerStack {
+	fn eq(&self, other: &Self) -> bool {
+		self.layers == other.layers
+	}
+}
+
+impl LayerStack {
+	pub fn new(size: usize) -> Self {
+		let base = Layer::new(String::from("Ebene 1"), size);
+		let stack = Self {
+			active: create_rw_signal(base.id),
+			layers: create_rw_signal(vec![base]),
+			composite: store_value(GridColors::new(size)),
+			locks: store_value(LockMask::new(size)),
+			size,
+			owner: Owner::current()
+		};
+
+		let flattened = create_memo(move |_| stack.flatten());
+		create_effect(move |_| {
+			flattened.with(|colors| {
+				stack.composite.with_value(|composite| {
+					for (i, &color) in colors.iter().enumerate() {
+						let target = composite.get_color(i);
+						if target.get_untracked() != color {
+							target.set(color);
+						}
+					}
+				})
+			})
+		});
+
+		stack
+	}
+
+	/// The flattened colors of all visible layers.
+	pub fn composite(&self) -> GridColors {
+		self.composite.get_value()
+	}
+
+	fn flatten(&self) -> Vec<TileColor> {
+		self.layers.with(|layers| {
+			let visible: Vec<&Layer> = layers
+				.iter()
+				.rev()
+				.filter(|layer| layer.visible.get())
+				.collect();
+			(0..self.size)
+				.map(|i| {
+					visible
+						.iter()
+						.map(|layer| layer.colors.get_color(i).get())
+						.find(|&color| color != TileColor::None)
+						.unwrap_or_default()
+				})
+				.collect()
+		})
+	}
+
+	pub fn locks(&self) -> LockMask {
+		self.locks.get_value()
+	}
+
+	pub fn set_locked(&self, i: usize, locked: bool) {
+		self.locks.with_value(|l| l.get_locked(i).set(locked));
+	}
+
+	pub fn layers(&self) -> Signal<Vec<Layer>> {
+		self.layers.into()
+	}
+
+	pub fn active(&self) -> Signal<Uuid> {
+		self.active.into()
+	}
+
+	pub fn set_active(&self, id: Uuid) {
+		self.active.set(id);
+	}
+
+	fn active_layer(&self) -> Option<Layer> {
+		let active = self.active.get_untracked();
+		self.layers
+			.with_untracked(|layers| layers.iter().find(|layer| layer.id == active).cloned())
+	}
+
+	/// Paints tile `i` of the active layer, unless the layer is hidden or
+	/// locked, or the tile itself is locked. Returns whether the tile was
+	/// painted.
+	pub fn paint(&self, i: usize, color: TileColor) -> bool {
+		let Some(layer) = self.active_layer() else {
+			return false;
+		};
+		if layer.locked.get_untracked() || !layer.visible.get_untracked() {
+			return false;
+		}
+		if self.locks.with_value(|l| l.get_locked(i).get_untracked()) {
+			return false;
+		}
+		layer.colors.get_color(i).set(color);
+		true
+	}
+
+	fn create_layer(&self, name: String) -> Layer {
+		match self.owner {
+			Some(owner) => with_owner(owner, || Layer::new(name, self.size)),
+			None => Layer::new(name, self.size)
+		}
+	}
+
+	/// A plain copy of all layers and the lock mask. When called in a
+	/// reactive context, every tile is tracked.
+	pub fn data(&self) -> (Vec<LayerData>, Vec<bool>) {
+		let layers = self.layers.with(|layers| {
+			layers
+				.iter()
+				.map(|layer| LayerData {
+					name: layer.name.get(),
+					visible: layer.visible.get(),
+					locked: layer.locked.get(),
+					colors: layer.colors.snapshot()
+				})
+				.collect()
+		});
+		let locks = self
+			.locks
+			.with_value(|l| (0..self.size).map(|i| l.get_locked(i).get()).collect());
+		(layers, locks)
+	}
+
+	/// Replaces all layers and the lock mask with the given data. Nothing is
+	/// replaced if the data doesn't match the number of tiles.
+	pub fn load(&self, layers: &[LayerData], locks: &[bool]) -> Result<(), LoadError> {
+		if layers.is_empty() {
+			return Err(LoadError::NoLayers);
+		}
+		let lengths = layers.iter().map(|data| data.colors.len());
+		if let Some(found) = lengths
+			.chain(iter::once(locks.len()))
+			.find(|&len| len != self.size)
+		{
+			return Err(LoadError::SizeMismatch {
+				expected: self.size,
+				found
+			});
+		}
+
+		let loaded: Vec<Layer> = layers
+			.iter()
+			.map(|data| {
+				let layer = self.create_layer(data.name.clone());
+				layer.visible.set(data.visible);
+				layer.locked.set(data.locked);
+				for (i, &color) in data.colors.iter().enumerate() {
+					layer.colors.get_color(i).set(color);
+				}
+				layer
+			})
+			.collect();
+		self.active.set(loaded.last().unwrap().id);
+		let replaced = self
+			.layers
+			.try_update(|l| mem::replace(l, loaded))
+			.unwrap_or_default();
+		for layer in replaced {
+			layer.dispose();
+		}
+
+		for (i, &locked) in locks.iter().enumerate() {
+			self.set_locked(i, locked);
+		}
+		Ok(())
+	}
+
+	pub fn add_layer(&self) {
+		let number = self.layers.with_untracked(|layers| layers.len()) + 1;
+		let layer = self.create_layer(format!("Ebene {number}"));
+		let id = layer.id;
+		self.layers.update(|layers| layers.push(layer));
+		self.active.set(id);
+	}
+
+	/// Removes the layer with the given id. The last remaining layer cannot be
+	/// removed.
+	pub fn remove_layer(&self, id: Uuid) {
+		let removed = self.layers.try_update(|layers| {
+			if layers.len() > 1 {
+				let index = layers.iter().position(|layer| layer.id == id)?;
+				Some(layers.remove(index))
+			} else {
+				None
+			}
+		});
+		if self.active.get_untracked() == id {
+			let top = self
+				.layers
+				.with_untracked(|layers| layers.last().map(|layer| layer.id));
+			if let Some(top) = top {
+				self.active.set(top);
+			}
+		}
+		if let Some(layer) = removed.flatten() {
+			layer.dispose();
+		}
+	}
+
+	/// Moves the layer with the given id up (positive `offset`) or down
+	/// (negative `offset`) in the stack.
+	pub fn move_layer(&self, id: Uuid, offset: isize) {
+		self.layers.update(|layers| {
+			let Some(index) = layers.iter().position(|layer| layer.id == id) else {
+				return;
+			};
+			let target = (index as isize + offset).clamp(0, layers.len() as isize - 1);
+			let layer = layers.remove(index);
+			layers.insert(target as usize, layer);
+		});
+	}
+}
+
+#[component]
+fn LayerIconButton<F>(
+	label: &'static str,
+	#[prop(into)] icon: MaybeSignal<&'static str>,
+	on_click: F
+) -> impl IntoView
+where
+	F: Fn() + 'static
+{
+	let on_click = move |ev: MouseEvent| {
+		ev.stop_propagation();
+		on_click();
+	};
+
+	view! {
+		<button
+			class="inline-flex justify-center items-center w-8 h-8"
+			aria-label=label
+			on:click=on_click
+		>
+			<box-icon name=move || icon.get() color="currentColor"></box-icon>
+		</button>
+	}
+}
+
+#[component]
+fn LayerItem(layer: Layer, #[prop(into)] stack: Signal<LayerStack>) -> impl IntoView {
+	let id = layer.id;
+	let is_active = move || stack.with(|s| s.active()()) == id;
+	let Layer {
+		name,
+		visible,
+		locked,
+		..
+	} = layer;
+
+	view! {
+		<li
+			class=move || {
+				cls! {
+					"flex items-center gap-1 px-2 border-2 transition-colors",
+					if is_active() { "border-highlight" } else { "border-misc" }
+				}
+			}
+			aria-current=move || is_active().to_string()
+			on:click=move |_| stack.with_untracked(|s| s.set_active(id))
+		>
+			<input
+				type="text"
+				aria-label="Name der Ebene"
+				class="flex-1 min-w-0 h-8 px-2 bg-transparent"
+				prop:value=name
+				on:input=move |ev| name.set(event_target_value(&ev))
+			/>
+			<LayerIconButton
+				label="Sichtbarkeit umschalten"
+				icon=Signal::derive(move || if visible() { "show" } else { "hide" })
+				on_click=move || visible.update(|v| *v = !*v)
+			/>
+			<LayerIconButton
+				label="Sperre umschalten"
+				icon=Signal::derive(move || if locked() { "lock" } else { "lock-open" })
+				on_click=move || locked.update(|l| *l = !*l)
+			/>
+			<LayerIconButton
+				label="Nach oben"
+				icon="chevron-up"
+				on_click=move || stack.with_untracked(|s| s.move_layer(id, 1))
+			/>
+			<LayerIconButton
+				label="Nach unten"
+				icon="chevron-down"
+				on_click=move || stack.with_untracked(|s| s.move_layer(id, -1))
+			/>
+			<LayerIconButton
+				label="Ebene löschen"
+				icon="trash"
+				on_click=move || stack.with_untracked(|s| s.remove_layer(id))
+			/>
+		</li>
+	}
+}
+
+#[component]
+pub fn LayerPanel(#[prop(into)] stack: Signal<LayerStack>) -> impl IntoView {
+	let layers = move || {
+		stack
+			.with(|s| s.layers()())
+			.into_iter()
+			.rev()
+			.map(|layer| view! { <LayerItem layer stack/> })
+			.collect_view()
+	};
+
+	view! {
+		<section aria-label="Ebenen" class="px-3 sm:px-6 pb-3 w-full max-w-lg flex flex-col gap-1">
+			<ul class="flex flex-col gap-1 max-h-32 overflow-y-auto">{layers}</ul>
+			<button
+				class="inline-flex justify-center items-center gap-2 h-8 border-2 border-misc transition-all hover:shadow-lg"
+				on:click=move |_| stack.with_untracked(|s| s.add_layer())
+			>
+				<box-icon name="plus" color="currentColor"></box-icon>
+				"Ebene hinzufügen"
+			</button>
+		</section>
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{
+		document::PatternData,
+		lettering::{Alignment, Lettering},
+		pattern_file::{PatternFile, PatternInfo},
+		stamps::builtin_stamps,
+		theme::Theme,
+		tiling::{Tiling, TilingFormat}
+	};
+
+	fn layer_data(colors: Vec<TileColor>) -> LayerData {
+		LayerData {
+			name: String::from("Ebene"),
+			visible: true,
+			locked: false,
+			colors
+		}
+	}
+
+	fn layer(stack: &LayerStack, index: usize) -> Layer {
+		stack
+			.layers()
+			.with_untracked(|layers| layers[index].clone())
+	}
+
+	#[test]
+	fn composites_topmost_visible_layer() {
+		use TileColor::*;
+
+		let stack = LayerStack::new(3);
+		stack
+			.load(
+				&[
+					layer_data(vec![Primary, Primary, None]),
+					layer_data(vec![Secondary, None, None])
+				],
+				&[false; 3]
+			)
+			.unwrap();
+		assert_eq!(stack.composite().snapshot(), [Secondary, Primary, None]);
+
+		let bottom = layer(&stack, 0);
+		stack.move_layer(bottom.id, 1);
+		assert_eq!(stack.composite().snapshot(), [Primary, Primary, None]);
+
+		bottom.visible.set(false);
+		assert_eq!(stack.composite().snapshot(), [Secondary, None, None]);
+
+		assert!(stack.paint(2, Primary));
+		assert_eq!(stack.composite().snapshot(), [Secondary, None, Primary]);
+	}
+
+	#[test]
+	fn paints_only_unlocked_tiles_of_the_active_layer() {
+		let stack = LayerStack::new(2);
+		let base = layer(&stack, 0);
+		assert!(stack.paint(0, TileColor::Primary));
+
+		stack.set_locked(1, true);
+		assert!(!stack.paint(1, TileColor::Primary));
+		stack.set_locked(1, false);
+		assert!(stack.paint(1, TileColor::Secondary));
+
+		base.locked.set(true);
+		assert!(!stack.paint(0, TileColor::Secondary));
+		base.locked.set(false);
+		base.visible.set(false);
+		assert!(!stack.paint(0, TileColor::Secondary));
+		assert_eq!(
+			base.colors.snapshot(),
+			[TileColor::Primary, TileColor::Secondary]
+		);
+	}
+
+	#[test]
+	fn rejects_data_of_another_tiling() {
+		let stack = LayerStack::new(2);
+		stack.paint(0, TileColor::Primary);
+		let data = stack.data();
+
+		assert_eq!(stack.load(&[], &[false; 2]), Err(LoadError::NoLayers));
+		assert_eq!(
+			stack.load(&[layer_data(vec![TileColor::Secondary; 3])], &[false; 2]),
+			Err(LoadError::SizeMismatch {
+				expected: 2,
+				found: 3
+			})
+		);
+		assert_eq!(
+			stack.load(&[layer_data(vec![TileColor::Secondary; 2])], &[false; 3]),
+			Err(LoadError::SizeMismatch {
+				expected: 2,
+				found: 3
+			})
+		);
+		assert_eq!(stack.data(), data);
+	}
+
+	#[test]
+	fn disposes_replaced_layers() {
+		let stack = LayerStack::new(2);
+		let base = layer(&stack, 0);
+		stack.add_layer();
+		let added = layer(&stack, 1);
+
+		stack.remove_layer(added.id);
+		assert_eq!(added.visible.try_get_untracked(), None);
+		assert_eq!(added.colors.get_color(0).try_get_untracked(), None);
+		assert_eq!(stack.active().get_untracked(), base.id);
+
+		// The last layer is kept.
+		stack.remove_layer(base.id);
+		assert_eq!(base.visible.try_get_untracked(), Some(true));
+
+		stack
+			.load(&[layer_data(vec![TileColor::Primary; 2])], &[false; 2])
+			.unwrap();
+		assert_eq!(base.name.try_get_untracked(), None);
+		assert_eq!(stack.composite().snapshot(), [TileColor::Primary; 2]);
+	}
+
+	#[test]
+	fn locked_tiles_reject_lettering_and_stamps() {
+		let tiling = Tiling::load(TilingFormat::F10X10);
+		let lettering = Lettering {
+			text: String::from("GB"),
+			size: 1,
+			horizontal: Alignment::Center,
+			vertical: Alignment::Center
+		};
+		let strokes = [
+			lettering
+				.rasterize(&tiling)
+				.into_iter()
+				.map(|i| (i, TileColor::Secondary))
+				.collect(),
+			builtin_stamps()[0].placements(&tiling, 0)
+		];
+
+		for placements in strokes {
+			let stack = LayerStack::new(tiling.num_tiles());
+			let (locked, unlocked) = placements.split_at(placements.len() / 2);
+			for &(i, _) in locked {
+				stack.set_locked(i, true);
+			}
+			for &(i, color) in &placements {
+				stack.paint(i, color);
+			}
+
+			let colors = layer(&stack, 0).colors.snapshot();
+			assert!(locked.iter().all(|&(i, _)| colors[i] == TileColor::None));
+			assert!(unlocked.iter().all(|&(i, color)| colors[i] == color));
+		}
+	}
+
+	#[test]
+	fn keeps_locks_in_pattern_files() {
+		let format = TilingFormat::F5X5;
+		let num_tiles = Tiling::load(format).num_tiles();
+		let stack = LayerStack::new(num_tiles);
+		stack.paint(0, TileColor::Primary);
+		for i in [1, 7, num_tiles - 1] {
+			stack.set_locked(i, true);
+		}
+
+		let (layers, locks) = stack.data();
+		let file = PatternFile::new(
+			PatternInfo {
+				name: String::from("Schloss"),
+				author: String::new(),
+				created: String::new()
+			},
+			String::new(),
+			PatternData {
+				format,
+				theme: Theme::Bubbles,
+				layers,
+				locks
+			}
+		);
+		let pattern = PatternFile::from_json(&file.to_json()).unwrap().pattern;
+
+		let loaded = LayerStack::new(num_tiles);
+		loaded.load(&pattern.layers, &pattern.locks).unwrap();
+		assert_eq!(loaded.data(), stack.data());
+		let locks = loaded.locks();
+		assert!(locks.get_locked(7).get_untracked());
+		assert!(!locks.get_locked(0).get_untracked());
+		assert!(!loaded.paint(1, TileColor::Secondary));
+	}
+}
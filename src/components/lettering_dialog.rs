@@ -0,0 +1,165 @@
+use std::{mem, rc::Rc};
+
+use leptos::{ev::Event, *};
+
+use crate::{
+	components::{dialog::Dialog, layers::LayerStack, pattern::TileColor},
+	lettering::{Alignment, Lettering},
+	tiling::Tiling
+};
+
+#[component]
+fn AlignmentSelector(
+	label: &'static str,
+	options: [&'static str; 3],
+	alignment: RwSignal<Alignment>
+) -> impl IntoView {
+	let on_change = move |ev: Event| {
+		let value_u8: u8 = event_target_value(&ev)
+			.parse()
+			.expect("Select had unexpected value!");
+		alignment.set(unsafe { mem::transmute(value_u8) });
+	};
+
+	view! {
+		<span class="inline-block h-12 bg-background text-backgroundText relative isolate">
+			<select
+				aria-label=label
+				on:change=on_change
+				class="appearance-none bg-transparent pl-6 pr-8 py-2 h-full"
+			>
+				{[Alignment::Start, Alignment::Center, Alignment::End]
+					.into_iter()
+					.zip(options)
+					.map(|(opt_alignment, name)| {
+						view! {
+							<option
+								value=opt_alignment as u8
+								selected=move || alignment() == opt_alignment
+							>
+								{name}
+							</option>
+						}
+					})
+					.collect_view()}
+			</select>
+			<box-icon
+				class="absolute right-0 z-[-1] h-full mx-1"
+				name="chevron-down"
+				color="currentColor"
+			></box-icon>
+		</span>
+	}
+}
+
+#[component]
+pub fn LetteringDialog(
+	open: RwSignal<bool>,
+	#[prop(into)] tiling: Signal<Rc<Tiling>>,
+	#[prop(into)] layers: Signal<LayerStack>
+) -> impl IntoView {
+	let text = create_rw_signal(String::new());
+	let size = create_rw_signal(1_usize);
+	let horizontal = create_rw_signal(Alignment::Center);
+	let vertical = create_rw_signal(Alignment::Center);
+	let color = create_rw_signal(TileColor::Primary);
+
+	let on_size_change = move |ev: Event| {
+		size.set(
+			event_target_value(&ev)
+				.parse()
+				.expect("Range had unexpected value!")
+		);
+	};
+
+	let on_color_change = move |ev: Event| {
+		let value_u8: u8 = event_target_value(&ev)
+			.parse()
+			.expect("Select had unexpected value!");
+		color.set(unsafe { mem::transmute(value_u8) });
+	};
+
+	let on_apply = move |_| {
+		let lettering = Lettering {
+			text: text.get_untracked(),
+			size: size.get_untracked(),
+			horizontal: horizontal.get_untracked(),
+			vertical: vertical.get_untracked()
+		};
+		let tiles = tiling.with_untracked(|t| lettering.rasterize(t));
+		let color = color.get_untracked();
+		layers.with_untracked(|l| {
+			for i in tiles {
+				l.paint(i, color);
+			}
+		});
+		open.set(false);
+	};
+
+	view! {
+		<Dialog id="letteringDialog" open title="Text einfügen">
+			<div class="flex flex-col gap-4">
+				<input
+					type="text"
+					aria-label="Text"
+					placeholder="Text"
+					class="h-12 px-4 bg-background text-backgroundText"
+					prop:value=text
+					on:input=move |ev| text.set(event_target_value(&ev))
+				/>
+				<span class="inline-flex flex-col">
+					<label for="letteringSizeRange">Größe: {size}</label>
+					<input
+						type="range"
+						id="letteringSizeRange"
+						min="1"
+						max="3"
+						value=size
+						on:input=on_size_change
+					/>
+				</span>
+				<div class="flex flex-col w-full sm:flex-row justify-between gap-4">
+					<AlignmentSelector
+						label="Horizontale Ausrichtung"
+						options=["Links", "Mitte", "Rechts"]
+						alignment=horizontal
+					/>
+					<AlignmentSelector
+						label="Vertikale Ausrichtung"
+						options=["Oben", "Mitte", "Unten"]
+						alignment=vertical
+					/>
+					<span class="inline-block h-12 bg-background text-backgroundText relative isolate">
+						<select
+							aria-label="Farbe"
+							on:change=on_color_change
+							class="appearance-none bg-transparent pl-6 pr-8 py-2 h-full"
+						>
+							<option value=TileColor::Primary as u8 selected=move || color() == TileColor::Primary>
+								"Farbe 1"
+							</option>
+							<option value=TileColor::Secondary as u8 selected=move || color() == TileColor::Secondary>
+								"Farbe 2"
+							</option>
+							<option value=TileColor::None as u8 selected=move || color() == TileColor::None>
+								"Hintergrund"
+							</option>
+						</select>
+						<box-icon
+							class="absolute right-0 z-[-1] h-full mx-1"
+							name="chevron-down"
+							color="currentColor"
+						></box-icon>
+					</span>
+					<span class="flex-1"></span>
+					<button
+						class="px-6 py-2 h-12 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight"
+						on:click=on_apply
+					>
+						"Einfügen"
+					</button>
+				</div>
+			</div>
+		</Dialog>
+	}
+}
@@ -0,0 +1,306 @@
+use std::rc::Rc;
+
+use leptos::{leptos_dom::logging::console_error, *};
+use uuid::Uuid;
+
+use crate::{
+	catalog::{write_catalog, CatalogPage},
+	components::{
+		dialog::Dialog,
+		file_actions::{create_pattern_file, now},
+		layers::LayerStack,
+		pattern::{GridColors, Pattern}
+	},
+	export::save_file,
+	library::{Library, LibraryEntry},
+	pattern_file::{PatternFile, PatternInfo},
+	theme::ThemeCtx,
+	tiling::{Tiling, TilingFormat}
+};
+
+#[component]
+fn Thumbnail(id: Uuid, file: PatternFile) -> impl IntoView {
+	let tiling = Rc::new(Tiling::load(file.pattern.format));
+	let colors = GridColors::from_colors(&file.pattern.composite());
+
+	// The theme attribute scopes the theme colors to the thumbnail.
+	view! {
+		<div class="w-20 shrink-0 bg-background" data-theme=file.pattern.theme.name()>
+			<Pattern
+				id=format!("library-{id}")
+				tiling=Signal::derive(move || tiling.clone())
+				colors=Signal::derive(move || colors.clone())
+				reps_x=1
+				reps_y=1
+			/>
+		</div>
+	}
+}
+
+#[component]
+fn LibraryItem(
+	entry: LibraryEntry,
+	on_load: Callback<LibraryEntry>,
+	on_rename: Callback<(LibraryEntry, String)>,
+	on_duplicate: Callback<LibraryEntry>,
+	on_delete: Callback<Uuid>
+) -> impl IntoView {
+	let entry = store_value(entry);
+	let info = entry.with_value(|e| e.file.info.clone());
+	let details = if info.author.is_empty() {
+		info.created.get(..10).unwrap_or_default().to_string()
+	} else {
+		format!(
+			"{} · {}",
+			info.author,
+			info.created.get(..10).unwrap_or_default()
+		)
+	};
+
+	view! {
+		<li class="flex items-center gap-2">
+			{entry.with_value(|e| view! { <Thumbnail id=e.id file=e.file.clone()/> })}
+			<div class="flex-1 min-w-0 flex flex-col">
+				<input
+					type="text"
+					aria-label="Name des Musters"
+					class="h-8 px-2 bg-transparent"
+					prop:value=info.name
+					on:change=move |ev| on_rename((entry.get_value(), event_target_value(&ev)))
+				/>
+				<span class="px-2 text-sm opacity-75">{details}</span>
+			</div>
+			<button
+				class="px-4 py-1 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight"
+				on:click=move |_| on_load(entry.get_value())
+			>
+				"Laden"
+			</button>
+			<button
+				class="inline-flex justify-center items-center w-8 h-8"
+				aria-label="Muster duplizieren"
+				on:click=move |_| on_duplicate(entry.get_value())
+			>
+				<box-icon name="duplicate" color="currentColor"></box-icon>
+			</button>
+			<button
+				class="inline-flex justify-center items-center w-8 h-8"
+				aria-label="Muster löschen"
+				on:click=move |_| on_delete(entry.with_value(|e| e.id))
+			>
+				<box-icon name="trash" color="currentColor"></box-icon>
+			</button>
+		</li>
+	}
+}
+
+/// Browses the patterns saved in the browser and saves the current one.
+/// `entry_id` is the id of the entry the current pattern was loaded from,
+/// which saving updates.
+#[component]
+pub fn LibraryDialog(
+	open: RwSignal<bool>,
+	info: RwSignal<Option<PatternInfo>>,
+	entry_id: RwSignal<Option<Uuid>>,
+	#[prop(into)] format: Signal<TilingFormat>,
+	#[prop(into)] layers: Signal<LayerStack>,
+	on_load: Callback<PatternFile>
+) -> impl IntoView {
+	let theme = use_context::<ThemeCtx>().expect("LibraryDialog is missing theme context!");
+	let library = store_value(None::<Library>);
+	let entries = create_rw_signal(Vec::<LibraryEntry>::new());
+	let search = create_rw_signal(String::new());
+	// Whether the library is open, and whether opening it failed.
+	let ready = create_rw_signal(false);
+	let failed = create_rw_signal(false);
+
+	spawn_local(async move {
+		let opened = match Library::open().await {
+			Ok(opened) => opened,
+			Err(err) => {
+				console_error(&format!("Failed to open pattern library: {err:?}"));
+				failed.set(true);
+				return;
+			}
+		};
+		match opened.entries().await {
+			Ok(loaded) => entries.set(loaded),
+			Err(err) => console_error(&format!("Failed to read pattern library: {err:?}"))
+		}
+		library.set_value(Some(opened));
+		ready.set(true);
+	});
+
+	let put = move |entry: LibraryEntry| {
+		let Some(library) = library.get_value() else {
+			return;
+		};
+		spawn_local(async move {
+			if let Err(err) = library.put(&entry).await {
+				console_error(&format!("Failed to save to pattern library: {err:?}"));
+				return;
+			}
+			entries.update(
+				|entries| match entries.iter_mut().find(|e| e.id == entry.id) {
+					Some(existing) => *existing = entry,
+					None => entries.push(entry)
+				}
+			);
+		});
+	};
+
+	// Updates the entry the pattern was loaded from, or adds a new one.
+	let save = move |as_new: bool| {
+		let mut file = layers.with_untracked(|l| {
+			create_pattern_file(
+				info.get_untracked(),
+				format.get_untracked(),
+				theme.get_untracked(),
+				l
+			)
+		});
+		if file.info.name.is_empty() {
+			file.info.name = format!("Muster {}", entries.with_untracked(Vec::len) + 1);
+		}
+		let existing = entry_id
+			.get_untracked()
+			.filter(|id| !as_new && entries.with_untracked(|e| e.iter().any(|e| e.id == *id)));
+		let id = existing.unwrap_or_else(Uuid::new_v4);
+		put(LibraryEntry { id, file });
+		entry_id.set(Some(id));
+	};
+	let loaded_entry =
+		move || entry_id().filter(|id| entries.with(|e| e.iter().any(|e| e.id == *id)));
+
+	let on_load_entry = Callback::new(move |entry: LibraryEntry| {
+		on_load(entry.file);
+		entry_id.set(Some(entry.id));
+		open.set(false);
+	});
+
+	let on_rename = Callback::new(move |(mut entry, name): (LibraryEntry, String)| {
+		entry.file.info.name = name;
+		entry.file.modified = now();
+		put(entry);
+	});
+
+	let on_duplicate = Callback::new(move |mut entry: LibraryEntry| {
+		let timestamp = now();
+		entry.id = Uuid::new_v4();
+		entry.file.info.name = format!("{} (Kopie)", entry.file.info.name);
+		entry.file.info.created = timestamp.clone();
+		entry.file.modified = timestamp;
+		put(entry);
+	});
+
+	let on_delete = Callback::new(move |id: Uuid| {
+		let Some(library) = library.get_value() else {
+			return;
+		};
+		spawn_local(async move {
+			if let Err(err) = library.delete(id).await {
+				console_error(&format!("Failed to delete from pattern library: {err:?}"));
+				return;
+			}
+			entries.update(|entries| entries.retain(|e| e.id != id));
+			if entry_id.get_untracked() == Some(id) {
+				entry_id.set(None);
+			}
+		});
+	});
+
+	let filtered = move || {
+		let query = search().to_lowercase();
+		entries.with(|entries| {
+			entries
+				.iter()
+				.filter(|e| e.matches(&query))
+				.cloned()
+				.collect::<Vec<_>>()
+		})
+	};
+
+	let on_export_catalog = move |_| {
+		let pages: Vec<CatalogPage> = filtered()
+			.iter()
+			.map(|entry| CatalogPage::from_file(&entry.file))
+			.collect();
+		if pages.is_empty() {
+			return;
+		}
+		save_file(write_catalog("Musterkatalog", &pages), "Musterkatalog.pdf");
+	};
+
+	view! {
+		<Dialog id="libraryDialog" open title="Bibliothek">
+			<div class="flex flex-col gap-4">
+				<Show when=failed>
+					<p role="alert" class="p-4 border-2 border-highlight">
+						"Die Bibliothek konnte nicht geöffnet werden. Muster können nicht gespeichert werden."
+					</p>
+				</Show>
+				<div class="flex flex-col sm:flex-row gap-2">
+					<input
+						type="search"
+						aria-label="Muster suchen"
+						placeholder="Suchen"
+						class="flex-1 h-12 px-4 bg-background text-backgroundText"
+						prop:value=search
+						on:input=move |ev| search.set(event_target_value(&ev))
+					/>
+					<button
+						class="px-6 py-2 h-12 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight disabled:opacity-50"
+						disabled=move || !ready()
+						on:click=move |_| save(false)
+					>
+						{move || {
+							if loaded_entry().is_some() {
+								"Änderungen speichern"
+							} else {
+								"Aktuelles Muster speichern"
+							}
+						}}
+					</button>
+					<Show when=move || loaded_entry().is_some()>
+						<button
+							class="px-6 py-2 h-12 bg-background text-backgroundText hover:outline outline-2 outline-highlight disabled:opacity-50"
+							disabled=move || !ready()
+							on:click=move |_| save(true)
+						>
+							"Als neues Muster speichern"
+						</button>
+					</Show>
+					<button
+						class="px-6 py-2 h-12 bg-background text-backgroundText hover:outline outline-2 outline-highlight disabled:opacity-50"
+						disabled=move || entries.with(Vec::is_empty)
+						on:click=on_export_catalog
+					>
+						"Katalog exportieren"
+					</button>
+				</div>
+				<Show
+					when=move || entries.with(|e| !e.is_empty())
+					fallback=|| view! { <p class="opacity-75">"Die Bibliothek ist leer."</p> }
+				>
+					<ul class="flex flex-col gap-2">
+						<For
+							each=filtered
+							key=|e| (e.id, e.file.modified.clone())
+							children=move |entry| {
+								view! {
+									<LibraryItem
+										entry
+										on_load=on_load_entry
+										on_rename
+										on_duplicate
+										on_delete
+									/>
+								}
+							}
+						/>
+					</ul>
+				</Show>
+			</div>
+		</Dialog>
+	}
+}
@@ -1,8 +1,17 @@
 pub mod app;
+pub mod autosave;
 pub mod canvas;
 pub mod controls;
+pub mod dialog;
 pub mod export_dialog;
+pub mod file_actions;
+pub mod layers;
+pub mod lettering_dialog;
+pub mod library_dialog;
 pub mod pattern;
+pub mod qr_dialog;
+pub mod rules_dialog;
+pub mod share_link;
+pub mod stamp_dialog;
 pub mod theme_selector;
-
-
+pub mod web_snippet;
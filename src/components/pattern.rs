@@ -1,20 +1,34 @@
 use std::{iter, rc::Rc};
 
+use enum_iterator::Sequence;
 use leptos::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+	artwork::SEAM_STROKE_WIDTH,
 	cls,
 	theme::ThemeData,
 	tiling::{Shape, Tiling}
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Sequence)]
+#[repr(u8)]
 pub enum TileColor {
 	None,
 	Primary,
 	Secondary
 }
 
+impl TileColor {
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::None => "Hintergrund",
+			Self::Primary => "Farbe 1",
+			Self::Secondary => "Farbe 2"
+		}
+	}
+}
+
 impl Default for TileColor {
 	fn default() -> Self {
 		Self::None
@@ -33,9 +47,28 @@ impl GridColors {
 		)
 	}
 
+	pub fn from_colors(colors: &[TileColor]) -> Self {
+		Self(
+			colors
+				.iter()
+				.map(|&color| create_rw_signal(color))
+				.collect()
+		)
+	}
+
 	pub fn get_color(&self, i: usize) -> RwSignal<TileColor> {
 		self.0[i]
 	}
+
+	pub fn snapshot(&self) -> Vec<TileColor> {
+		self.0.iter().map(|color| color.get()).collect()
+	}
+
+	pub fn dispose(self) {
+		for color in self.0 {
+			color.dispose();
+		}
+	}
 }
 
 #[component]
@@ -47,7 +80,7 @@ fn ExportTile<'a>(shape: Shape, color: TileColor, theme_data: &'a ThemeData) ->
 		TileColor::None => theme_data.background.clone()
 	};
 	view! {
-		<polygon points=shape.svg_path() fill=fill.clone() stroke=fill stroke-width="0.05"></polygon>
+		<polygon points=shape.svg_path() fill=fill.clone() stroke=fill stroke-width=SEAM_STROKE_WIDTH></polygon>
 	}
 }
 
@@ -101,7 +134,7 @@ fn Grid(tiling: Signal<Rc<Tiling>>, colors: Signal<GridColors>) -> impl IntoView
 
 #[component]
 pub fn Pattern(
-	id: &'static str,
+	#[prop(into)] id: String,
 	#[prop(into)] tiling: Signal<Rc<Tiling>>,
 	#[prop(into)] colors: Signal<GridColors>,
 	#[prop(into)] reps_x: MaybeSignal<usize>,
@@ -148,4 +181,3 @@ pub fn Pattern(
 		</svg>
 	}
 }
-
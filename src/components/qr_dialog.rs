@@ -0,0 +1,140 @@
+use std::mem;
+
+use enum_iterator::all;
+use leptos::{ev::Event, *};
+
+use crate::{
+	components::{dialog::Dialog, pattern::GridColors, share_link::share_link},
+	export::{export_svg, ExportOptions, ExportSize, OutputFormat},
+	qr::{ErrorCorrection, QrCode},
+	raster::RasterOptions,
+	share::SharedPattern,
+	svg::write_document,
+	theme::ThemeCtx,
+	tiling::TilingFormat
+};
+
+/// Light modules around the code, as required by the standard.
+const QUIET_ZONE: usize = 4;
+
+#[component]
+fn QrExportButton(code: Memo<Option<QrCode>>, format: OutputFormat) -> impl IntoView {
+	view! {
+		<button
+			class="px-6 py-2 h-12 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight"
+			on:click=move |_| {
+				// Modules stay sharp without anti-aliasing.
+				let options = ExportOptions {
+					raster: RasterOptions {
+						anti_aliasing: false
+					},
+					..ExportOptions::default()
+				};
+				let Some(svg) = code.with_untracked(|code| code.as_ref().map(|code| qr_svg(code, &options.size))) else {
+					return;
+				};
+				export_svg(svg, "Pattern-QR", format, &options)
+			}
+		>
+			{format!("Als {format} exportieren")}
+		</button>
+	}
+}
+
+/// The code as an SVG with the same content as the one in the dialog.
+fn qr_svg(code: &QrCode, size: &ExportSize) -> String {
+	let dimension = (code.size() + 2 * QUIET_ZONE) as f32;
+	let content = format!(
+		r##"<rect width="{dimension}" height="{dimension}" fill="#ffffff"/><path d="{}" fill="#000000" shape-rendering="crispEdges"/>"##,
+		code.svg_path(QUIET_ZONE)
+	);
+	write_document(dimension, dimension, size, None, &content)
+}
+
+/// Shows the share link of the current pattern as a QR code.
+#[component]
+pub fn QrDialog(
+	open: RwSignal<bool>,
+	#[prop(into)] format: Signal<TilingFormat>,
+	#[prop(into)] colors: Signal<GridColors>
+) -> impl IntoView {
+	let theme = use_context::<ThemeCtx>().expect("QrDialog is missing theme context!");
+	let (error_correction, set_error_correction) = create_signal(ErrorCorrection::Medium);
+
+	let on_error_correction_change = move |ev: Event| {
+		let value = event_target_value(&ev);
+		let value_u8: u8 = value.parse().expect("Select had unexpected value!");
+		set_error_correction(unsafe { mem::transmute(value_u8) });
+	};
+
+	let link = create_memo(move |_| {
+		if !open() {
+			return String::new();
+		}
+		share_link(&SharedPattern {
+			format: format(),
+			theme: theme(),
+			colors: colors.with(|c| c.snapshot())
+		})
+	});
+	let code =
+		create_memo(move |_| link.with(|l| QrCode::encode(l.as_bytes(), error_correction())));
+
+	let qr_code = move || {
+		code.with(|code| {
+			code.as_ref().map(|code| {
+				let dimension = code.size() + 2 * QUIET_ZONE;
+				view! {
+					<svg
+						id="qrCode"
+						class="w-64 max-w-full"
+						viewBox=format!("0 0 {dimension} {dimension}")
+						shape-rendering="crispEdges"
+					>
+						<rect width=dimension height=dimension fill="#ffffff"></rect>
+						<path d=code.svg_path(QUIET_ZONE) fill="#000000"></path>
+					</svg>
+				}
+			})
+		})
+	};
+
+	view! {
+		<Dialog id="qrDialog" open title="QR-Code">
+			<div class="flex flex-col items-center gap-4">
+				{qr_code}
+				<p class="max-w-full text-sm break-all opacity-75">{link}</p>
+				<div class="flex flex-col sm:flex-row gap-4">
+					<span class="inline-block h-12 bg-background text-backgroundText relative isolate">
+						<select
+							aria-label="Fehlerkorrektur"
+							on:change=on_error_correction_change
+							class="appearance-none bg-transparent pl-6 pr-8 py-2 h-full"
+						>
+							{all::<ErrorCorrection>()
+								.map(|level| {
+									view! {
+										<option
+											value=level as u8
+											selected=move || error_correction() == level
+										>
+											{format!("Fehlerkorrektur {}", level.recovery())}
+										</option>
+									}
+								})
+								.collect_view()}
+						</select>
+						<box-icon
+							class="absolute right-0 z-[-1] h-full mx-1"
+							name="chevron-down"
+							color="currentColor"
+						></box-icon>
+					</span>
+					<QrExportButton code format=OutputFormat::Svg/>
+					<QrExportButton code format=OutputFormat::Pdf/>
+					<QrExportButton code format=OutputFormat::Png/>
+				</div>
+			</div>
+		</Dialog>
+	}
+}
@@ -0,0 +1,122 @@
+use leptos::{ev::Event, *};
+
+use crate::{
+	components::dialog::Dialog,
+	rules::{summarize, RuleSet, Violation}
+};
+
+#[component]
+pub fn ViolationList(#[prop(into)] violations: Signal<Vec<Violation>>) -> impl IntoView {
+	view! {
+		<ul class="list-disc pl-6">
+			{move || {
+				violations
+					.with(|v| summarize(v))
+					.into_iter()
+					.map(|message| view! { <li>{message}</li> })
+					.collect_view()
+			}}
+		</ul>
+	}
+}
+
+#[component]
+fn PercentRange<F>(
+	id: &'static str,
+	label: &'static str,
+	#[prop(into)] value: Signal<f32>,
+	on_change: F
+) -> impl IntoView
+where
+	F: Fn(f32) + 'static
+{
+	let percent = move || (value() * 100.0).round() as i32;
+	let on_input = move |ev: Event| {
+		let value: f32 = event_target_value(&ev)
+			.parse()
+			.expect("Range had unexpected value!");
+		on_change(value / 100.0);
+	};
+
+	view! {
+		<span class="inline-flex flex-col">
+			<label for=id>{label}: {percent} " %"</label>
+			<input type="range" id=id min="0" max="100" step="5" value=percent on:input=on_input/>
+		</span>
+	}
+}
+
+#[component]
+fn RuleToggle<F>(
+	id: &'static str,
+	label: &'static str,
+	#[prop(into)] checked: Signal<bool>,
+	on_change: F
+) -> impl IntoView
+where
+	F: Fn(bool) + 'static
+{
+	view! {
+		<span class="inline-flex gap-2 items-center">
+			<input
+				type="checkbox"
+				id=id
+				prop:checked=checked
+				on:change=move |ev| on_change(event_target_checked(&ev))
+			/>
+			<label for=id>{label}</label>
+		</span>
+	}
+}
+
+#[component]
+pub fn RulesDialog(
+	open: RwSignal<bool>,
+	rules: RwSignal<RuleSet>,
+	#[prop(into)] violations: Signal<Vec<Violation>>
+) -> impl IntoView {
+	view! {
+		<Dialog id="rulesDialog" open title="Gestaltungsregeln">
+			<div class="mb-4 p-4 bg-background text-backgroundText">
+				<Show
+					when=move || violations.with(|v| !v.is_empty())
+					fallback=|| view! { "Das Muster erfüllt alle Regeln." }
+				>
+					<ViolationList violations/>
+				</Show>
+			</div>
+			<div class="flex flex-col gap-4">
+				<PercentRange
+					id="rulesMinCoverage"
+					label="Minimale Füllung"
+					value=Signal::derive(move || rules.with(|r| r.min_coverage))
+					on_change=move |value| rules.update(|r| r.min_coverage = value)
+				/>
+				<PercentRange
+					id="rulesMaxCoverage"
+					label="Maximale Füllung"
+					value=Signal::derive(move || rules.with(|r| r.max_coverage))
+					on_change=move |value| rules.update(|r| r.max_coverage = value)
+				/>
+				<PercentRange
+					id="rulesMinColorRatio"
+					label="Minimales Farbverhältnis"
+					value=Signal::derive(move || rules.with(|r| r.min_color_ratio))
+					on_change=move |value| rules.update(|r| r.min_color_ratio = value)
+				/>
+				<RuleToggle
+					id="rulesIsolatedTiles"
+					label="Keine isolierten Trapeze"
+					checked=Signal::derive(move || rules.with(|r| r.forbid_isolated_tiles))
+					on_change=move |value| rules.update(|r| r.forbid_isolated_tiles = value)
+				/>
+				<RuleToggle
+					id="rulesEmptyRows"
+					label="Keine leeren Zeilen"
+					checked=Signal::derive(move || rules.with(|r| r.forbid_empty_rows))
+					on_change=move |value| rules.update(|r| r.forbid_empty_rows = value)
+				/>
+			</div>
+		</Dialog>
+	}
+}
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+use js_sys::{Function, Promise, Reflect};
+use leptos::{leptos_dom::logging::console_error, *};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+	components::pattern::GridColors, share::SharedPattern, theme::ThemeCtx, tiling::TilingFormat
+};
+
+pub const FRAGMENT_PREFIX: &str = "#p=";
+
+/// The address of the app without any fragment.
+fn base_url() -> String {
+	let href = window().location().href().unwrap_or_default();
+	match href.split_once('#') {
+		Some((base, _)) => String::from(base),
+		None => href
+	}
+}
+
+/// A link to the app that opens the given pattern.
+pub fn share_link(shared: &SharedPattern) -> String {
+	format!("{}{FRAGMENT_PREFIX}{}", base_url(), shared.encode())
+}
+
+/// Reads a shared pattern from the URL fragment, if there is one, and
+/// removes the fragment so that reloading doesn't discard later changes.
+pub fn take_shared_pattern() -> Option<SharedPattern> {
+	let hash = window().location().hash().ok()?;
+	let code = hash.strip_prefix(FRAGMENT_PREFIX)?;
+	let shared = SharedPattern::decode(code);
+	window()
+		.history()
+		.and_then(|h| h.replace_state_with_url(&JsValue::NULL, "", Some(&base_url())))
+		.unwrap_or(());
+	match shared {
+		Ok(shared) => Some(shared),
+		Err(err) => {
+			console_error(&format!("Failed to load shared pattern: {err}"));
+			None
+		}
+	}
+}
+
+/// `web_sys::Clipboard` is still behind an unstable flag, so the API is
+/// called dynamically.
+pub async fn copy_to_clipboard(text: &str) -> Result<(), JsValue> {
+	let clipboard = Reflect::get(&window().navigator(), &JsValue::from_str("clipboard"))?;
+	let write_text: Function =
+		Reflect::get(&clipboard, &JsValue::from_str("writeText"))?.dyn_into()?;
+	let promise: Promise = write_text
+		.call1(&clipboard, &JsValue::from_str(text))?
+		.dyn_into()?;
+	JsFuture::from(promise).await?;
+	Ok(())
+}
+
+/// Shows `text` selected in a prompt, for when the clipboard can't be
+/// written, e.g. because the user denied access.
+pub fn show_for_manual_copy(text: &str) {
+	window()
+		.prompt_with_message_and_default("Zum Kopieren markieren:", text)
+		.unwrap_or(None);
+}
+
+#[component]
+pub fn ShareButton(
+	#[prop(into)] format: Signal<TilingFormat>,
+	#[prop(into)] colors: Signal<GridColors>
+) -> impl IntoView {
+	let theme = use_context::<ThemeCtx>().expect("ShareButton is missing theme context!");
+	let copied = create_rw_signal(false);
+
+	let on_click = move |_| {
+		let shared = SharedPattern {
+			format: format.get_untracked(),
+			theme: theme.get_untracked(),
+			colors: colors.with_untracked(|c| c.snapshot())
+		};
+		let link = share_link(&shared);
+		spawn_local(async move {
+			if let Err(err) = copy_to_clipboard(&link).await {
+				console_error(&format!("Failed to copy share link: {err:?}"));
+				show_for_manual_copy(&link);
+				return;
+			}
+			copied.set(true);
+			set_timeout(move || copied.set(false), Duration::from_secs(2));
+		});
+	};
+
+	view! {
+		<button
+			class="inline-flex justify-center items-center h-12 sm:aspect-square font-semibold border-2 border-misc transition-all hover:shadow-lg"
+			aria-label="Link kopieren"
+			title="Link kopieren"
+			on:click=on_click
+		>
+			<box-icon
+				name=move || if copied() { "check" } else { "link" }
+				size="md"
+				color="currentColor"
+			></box-icon>
+		</button>
+	}
+}
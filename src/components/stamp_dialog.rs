@@ -0,0 +1,224 @@
+use std::rc::Rc;
+
+use leptos::*;
+
+use crate::{
+	cls,
+	components::{
+		canvas::Brush,
+		dialog::Dialog,
+		pattern::{GridColors, TileColor}
+	},
+	stamps::{builtin_stamps, Selection, Stamp, Transform},
+	storage,
+	tiling::Tiling
+};
+
+const STORAGE_KEY: &str = "stamps";
+
+#[component]
+fn StampPreview(stamp: Stamp, #[prop(into)] tiling: Signal<Rc<Tiling>>) -> impl IntoView {
+	// The stamp is shown in the rows of its phase, where it keeps its shapes.
+	let view_box = format!("0 {} {} {}", stamp.phase, stamp.width, stamp.height);
+	let cells = move || {
+		tiling.with(|t| {
+			stamp
+				.placements(t, t.square_tiles(0, stamp.phase)[0])
+				.into_iter()
+				.map(|(i, color)| {
+					view! {
+						<polygon
+							class=cls! {
+								match color { TileColor::Primary => "fill-primary stroke-primary",
+								TileColor::Secondary => "fill-secondary stroke-secondary",
+								TileColor::None => "fill-transparent stroke-transparent" }
+							}
+							points=t.tile(i).svg_path()
+							stroke-width="0.05"
+						/>
+					}
+				})
+				.collect_view()
+		})
+	};
+
+	view! {
+		<svg class="h-12 bg-background" viewBox=view_box>
+			{cells}
+		</svg>
+	}
+}
+
+#[component]
+fn StampItem(
+	stamp: Stamp,
+	#[prop(into)] tiling: Signal<Rc<Tiling>>,
+	on_use: Callback<Stamp>,
+	#[prop(optional)] on_delete: Option<Callback<()>>
+) -> impl IntoView {
+	let name = stamp.name.clone();
+	let preview_stamp = stamp.clone();
+
+	view! {
+		<li class="flex items-center gap-2">
+			<StampPreview stamp=preview_stamp tiling/>
+			<span class="flex-1">{name}</span>
+			<button
+				class="px-4 py-1 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight"
+				on:click=move |_| on_use(stamp.clone())
+			>
+				"Verwenden"
+			</button>
+			{on_delete
+				.map(|on_delete| {
+					view! {
+						<button
+							class="inline-flex justify-center items-center w-8 h-8"
+							aria-label="Stempel löschen"
+							on:click=move |_| on_delete(())
+						>
+							<box-icon name="trash" color="currentColor"></box-icon>
+						</button>
+					}
+				})}
+		</li>
+	}
+}
+
+#[component]
+fn TransformButton(
+	label: &'static str,
+	icon: &'static str,
+	transform: Transform,
+	stamp: RwSignal<Option<Stamp>>
+) -> impl IntoView {
+	view! {
+		<button
+			class="inline-flex justify-center items-center h-10 aspect-square border-2 border-misc"
+			aria-label=label
+			on:click=move |_| {
+				stamp.update(|s| {
+					if let Some(s) = s {
+						*s = s.transformed(transform);
+					}
+				})
+			}
+		>
+			<box-icon name=icon color="currentColor"></box-icon>
+		</button>
+	}
+}
+
+#[component]
+pub fn StampDialog(
+	open: RwSignal<bool>,
+	#[prop(into)] tiling: Signal<Rc<Tiling>>,
+	#[prop(into)] colors: Signal<GridColors>,
+	brush: RwSignal<Brush>,
+	selection: RwSignal<Option<Selection>>,
+	stamp: RwSignal<Option<Stamp>>
+) -> impl IntoView {
+	let saved = create_rw_signal(storage::load::<Vec<Stamp>>(STORAGE_KEY).unwrap_or_default());
+	let name = create_rw_signal(String::new());
+
+	let on_use = Callback::new(move |chosen: Stamp| {
+		stamp.set(Some(chosen));
+		brush.set(Brush::Stamp);
+		open.set(false);
+	});
+
+	let on_save = move |_| {
+		let Some(selection) = selection.get_untracked() else {
+			return;
+		};
+		let mut stamp_name = name.get_untracked();
+		if stamp_name.is_empty() {
+			stamp_name = format!("Stempel {}", saved.with_untracked(|s| s.len()) + 1);
+		}
+		let captured = tiling.with_untracked(|t| {
+			colors.with_untracked(|c| Stamp::capture(stamp_name, t, &c.snapshot(), selection))
+		});
+		saved.update(|s| s.push(captured));
+		saved.with_untracked(|s| storage::save(STORAGE_KEY, s));
+		name.set(String::new());
+	};
+
+	let delete = move |index: usize| {
+		saved.update(|s| {
+			s.remove(index);
+		});
+		saved.with_untracked(|s| storage::save(STORAGE_KEY, s));
+	};
+
+	view! {
+		<Dialog id="stampDialog" open title="Stempel">
+			<div class="flex flex-col gap-4">
+				<Show when=move || stamp.with(Option::is_some)>
+					<div class="flex items-center gap-2 p-2 bg-background text-backgroundText">
+						{move || {
+							stamp().map(|s| view! { <StampPreview stamp=s tiling/> })
+						}}
+						<span class="flex-1"></span>
+						<TransformButton
+							label="Horizontal spiegeln"
+							icon="reflect-vertical"
+							transform=Transform::MirrorHorizontal
+							stamp
+						/>
+						<TransformButton
+							label="Vertikal spiegeln"
+							icon="reflect-horizontal"
+							transform=Transform::MirrorVertical
+							stamp
+						/>
+						<TransformButton
+							label="Um 180° drehen"
+							icon="rotate-right"
+							transform=Transform::Rotate180
+							stamp
+						/>
+					</div>
+				</Show>
+				<div class="flex flex-col sm:flex-row gap-2">
+					<input
+						type="text"
+						aria-label="Name des Stempels"
+						placeholder="Name"
+						class="flex-1 h-12 px-4 bg-background text-backgroundText"
+						prop:value=name
+						on:input=move |ev| name.set(event_target_value(&ev))
+					/>
+					<button
+						class="px-6 py-2 h-12 bg-secondary text-secondaryText hover:outline outline-2 outline-highlight disabled:opacity-50"
+						prop:disabled=move || selection.with(Option::is_none)
+						on:click=on_save
+					>
+						"Auswahl speichern"
+					</button>
+				</div>
+				<ul class="flex flex-col gap-2">
+					{builtin_stamps()
+						.into_iter()
+						.map(|builtin| view! { <StampItem stamp=builtin tiling on_use/> })
+						.collect_view()}
+					{move || {
+						saved()
+							.into_iter()
+							.enumerate()
+							.map(|(index, custom)| {
+								view! {
+									<StampItem
+										stamp=custom
+										tiling
+										on_use
+										on_delete=Callback::new(move |_| delete(index))
+									/>
+								}
+							})
+							.collect_view()
+					}}
+				</ul>
+			</div>
+		</Dialog>
+	}
+}
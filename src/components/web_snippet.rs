@@ -0,0 +1,124 @@
+use std::{rc::Rc, time::Duration};
+
+use leptos::{leptos_dom::logging::console_error, *};
+
+use crate::{
+	components::{
+		pattern::GridColors,
+		share_link::{copy_to_clipboard, show_for_manual_copy}
+	},
+	export::save_file,
+	snippet::{data_uri, write_css, write_snippet, SnippetOptions},
+	theme::ThemeCtx,
+	tiling::Tiling
+};
+
+/// Offers one period of the pattern for use on web pages: as an SVG file, a
+/// data URI and CSS for a repeating background.
+#[component]
+pub fn WebSnippet(
+	#[prop(into)] tiling: Signal<Rc<Tiling>>,
+	#[prop(into)] colors: Signal<GridColors>
+) -> impl IntoView {
+	let theme = use_context::<ThemeCtx>().expect("WebSnippet is missing theme context!");
+	let options = create_rw_signal(SnippetOptions::default());
+	// The label of the button whose text was just copied.
+	let copied = create_rw_signal(None::<&'static str>);
+
+	let snippet = move |theme_variables: bool| {
+		tiling.with_untracked(|t| {
+			write_snippet(
+				t,
+				&colors.with_untracked(|c| c.snapshot()),
+				theme.get_untracked(),
+				theme_variables
+			)
+		})
+	};
+	let css =
+		move || tiling.with(|t| write_css(t, &colors.with(|c| c.snapshot()), theme(), &options()));
+
+	let copy = move |label: &'static str, text: String| {
+		spawn_local(async move {
+			if let Err(err) = copy_to_clipboard(&text).await {
+				console_error(&format!("Failed to copy {label}: {err:?}"));
+				show_for_manual_copy(&text);
+				return;
+			}
+			copied.set(Some(label));
+			set_timeout(move || copied.set(None), Duration::from_secs(2));
+		});
+	};
+	let copy_label = move |label: &'static str| {
+		if copied() == Some(label) {
+			"Kopiert!"
+		} else {
+			label
+		}
+	};
+
+	view! {
+		<div class="mb-4 flex flex-col gap-4">
+			<div class="flex flex-col sm:flex-row sm:flex-wrap sm:items-center gap-4">
+				<span>"Für das Web:"</span>
+				<span class="inline-flex gap-2 items-center">
+					<input
+						type="checkbox"
+						id="exportWebVariables"
+						prop:checked=move || options.with(|o| o.theme_variables)
+						on:change=move |ev| {
+							options.update(|o| o.theme_variables = event_target_checked(&ev))
+						}
+					/>
+					<label for="exportWebVariables">"Themenfarben"</label>
+				</span>
+				<label class="inline-flex items-center gap-2">
+					"Quadrat"
+					<input
+						type="number"
+						class="w-24 h-12 px-4 bg-background text-backgroundText"
+						min="1"
+						step="any"
+						prop:value=move || options.with(|o| o.square_size)
+						on:change=move |ev| {
+							if let Ok(size) = event_target_value(&ev).parse::<f32>() {
+								if size > 0.0 {
+									options.update(|o| o.square_size = size);
+								}
+							}
+						}
+					/>
+					"px"
+				</label>
+				<span class="flex-1"></span>
+				<button
+					class="px-6 py-2 h-12 bg-background text-backgroundText hover:outline outline-2 outline-highlight"
+					on:click=move |_| {
+						let svg = snippet(options.with_untracked(|o| o.theme_variables));
+						save_file(svg.into_bytes(), "Pattern-Kachel.svg");
+					}
+				>
+					"SVG-Kachel"
+				</button>
+				<button
+					class="px-6 py-2 h-12 bg-background text-backgroundText hover:outline outline-2 outline-highlight"
+					on:click=move |_| copy("Data-URI kopieren", data_uri(&snippet(false)))
+				>
+					{move || copy_label("Data-URI kopieren")}
+				</button>
+				<button
+					class="px-6 py-2 h-12 bg-background text-backgroundText hover:outline outline-2 outline-highlight"
+					on:click=move |_| copy("CSS kopieren", css())
+				>
+					{move || copy_label("CSS kopieren")}
+				</button>
+			</div>
+			<textarea
+				aria-label="CSS"
+				class="w-full h-32 p-2 font-mono text-sm bg-background text-backgroundText"
+				readonly
+				prop:value=css
+			></textarea>
+		</div>
+	}
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	components::pattern::TileColor,
+	theme::Theme,
+	tiling::{Tiling, TilingFormat}
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerData {
+	pub name: String,
+	pub visible: bool,
+	pub locked: bool,
+	pub colors: Vec<TileColor>
+}
+
+/// A plain, non-reactive copy of everything that makes up a pattern.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternData {
+	pub format: TilingFormat,
+	pub theme: Theme,
+	/// The layers from bottom to top.
+	pub layers: Vec<LayerData>,
+	pub locks: Vec<bool>
+}
+
+impl PatternData {
+	/// A pattern with a single layer holding the given colors.
+	pub fn from_colors(format: TilingFormat, theme: Theme, colors: Vec<TileColor>) -> Self {
+		let locks = vec![false; colors.len()];
+		Self {
+			format,
+			theme,
+			layers: vec![LayerData {
+				name: String::from("Ebene 1"),
+				visible: true,
+				locked: false,
+				colors
+			}],
+			locks
+		}
+	}
+	/// The flattened colors of all visible layers, like
+	/// [`LayerStack::composite`](crate::components::layers::LayerStack::composite).
+	pub fn composite(&self) -> Vec<TileColor> {
+		(0..Tiling::load(self.format).num_tiles())
+			.map(|i| {
+				self.layers
+					.iter()
+					.rev()
+					.filter(|layer| layer.visible)
+					.filter_map(|layer| layer.colors.get(i).copied())
+					.find(|&color| color != TileColor::None)
+					.unwrap_or_default()
+			})
+			.collect()
+	}
+}
@@ -0,0 +1,473 @@
+use std::{
+	collections::HashMap,
+	fmt::{self, Write}
+};
+
+use crate::{artwork::Artwork, components::pattern::TileColor, tiling::Vertex};
+
+/// Layer of the cut lines, which belong to no single tile color.
+const CUT_LAYER: &str = "Schnittlinien";
+
+/// Precision in millimetres below which coordinates are considered equal when
+/// merging cut lines.
+const CUT_PRECISION: f32 = 0.001;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DxfOptions {
+	/// Edge length of a square of the grid in millimetres.
+	pub square_size: f32,
+	/// Whether background tiles are exported on their own layer, or left out.
+	pub include_background: bool,
+	/// Writes the outlines of the tiles as single lines, with every shared edge
+	/// cut only once, instead of a polyline per tile.
+	pub cut_lines_only: bool
+}
+
+impl Default for DxfOptions {
+	fn default() -> Self {
+		Self {
+			square_size: 100.0,
+			include_background: false,
+			cut_lines_only: false
+		}
+	}
+}
+
+/// Formats a coordinate in millimetres, rounded to [`CUT_PRECISION`].
+fn coordinate(value: f32) -> String {
+	format!("{value:.3}")
+}
+
+/// Writes group code/value pairs and hands out entity handles.
+struct DxfWriter {
+	output: String,
+	next_handle: u32
+}
+
+impl DxfWriter {
+	fn new() -> Self {
+		Self {
+			output: String::new(),
+			next_handle: 1
+		}
+	}
+
+	fn pair(&mut self, code: u16, value: impl fmt::Display) -> &mut Self {
+		writeln!(self.output, "{code:>3}\n{value}").unwrap();
+		self
+	}
+
+	fn handle(&mut self) -> u32 {
+		let handle = self.next_handle;
+		self.next_handle += 1;
+		handle
+	}
+
+	/// Writes a new handle and returns it.
+	fn write_handle(&mut self) -> u32 {
+		let handle = self.handle();
+		self.pair(5, format!("{handle:X}"));
+		handle
+	}
+
+	fn section(&mut self, name: &str) -> &mut Self {
+		self.pair(0, "SECTION").pair(2, name)
+	}
+
+	fn end_section(&mut self) -> &mut Self {
+		self.pair(0, "ENDSEC")
+	}
+
+	/// Starts a symbol table and returns its handle, which its records
+	/// reference as their owner.
+	fn table(&mut self, name: &str, entries: usize) -> u32 {
+		self.pair(0, "TABLE").pair(2, name);
+		let handle = self.write_handle();
+		self.pair(330, 0)
+			.pair(100, "AcDbSymbolTable")
+			.pair(70, entries);
+		handle
+	}
+
+	fn end_table(&mut self) -> &mut Self {
+		self.pair(0, "ENDTAB")
+	}
+
+	fn record(&mut self, kind: &str, table: u32, subclass: &str) -> &mut Self {
+		self.pair(0, kind);
+		self.write_handle();
+		self.pair(330, format!("{table:X}"))
+			.pair(100, "AcDbSymbolTableRecord")
+			.pair(100, subclass)
+	}
+
+	fn entity(&mut self, kind: &str, owner: u32, layer: &str, subclass: &str) -> &mut Self {
+		self.pair(0, kind);
+		self.write_handle();
+		self.pair(330, format!("{owner:X}"))
+			.pair(100, "AcDbEntity")
+			.pair(8, layer)
+			.pair(100, subclass)
+	}
+}
+
+/// A section of a line, between two positions along it.
+#[derive(Debug, Clone, Copy)]
+struct Cut {
+	start: f32,
+	end: f32
+}
+
+/// The cuts on an infinite line, given by its unit direction and its signed
+/// distance from the origin.
+struct CutLine {
+	direction: Vertex,
+	offset: f32,
+	cuts: Vec<Cut>
+}
+
+/// Merges the edges of all polygons into cuts, so that shared and overlapping
+/// edges are cut only once.
+fn cut_lines(polygons: &[Vec<Vertex>]) -> Vec<(Vertex, Vertex)> {
+	let quantize = |value: f32| (value / CUT_PRECISION).round() as i64;
+	let mut lines: HashMap<(i64, i64, i64), CutLine> = HashMap::new();
+	for polygon in polygons {
+		for (i, from) in polygon.iter().enumerate() {
+			let to = polygon[(i + 1) % polygon.len()];
+			let length = (to.x - from.x).hypot(to.y - from.y);
+			if length < CUT_PRECISION {
+				continue;
+			}
+			let mut direction = Vertex::new((to.x - from.x) / length, (to.y - from.y) / length);
+			if direction.x < 0.0 || (direction.x == 0.0 && direction.y < 0.0) {
+				direction = Vertex::new(-direction.x, -direction.y);
+			}
+			let offset = from.x * direction.y - from.y * direction.x;
+			let position = |v: &Vertex| v.x * direction.x + v.y * direction.y;
+			let (a, b) = (position(from), position(&to));
+			// Directions are compared more coarsely than coordinates, as they
+			// are normalized.
+			let key = (
+				(direction.x * 1e4).round() as i64,
+				(direction.y * 1e4).round() as i64,
+				quantize(offset)
+			);
+			lines
+				.entry(key)
+				.or_insert_with(|| CutLine {
+					direction,
+					offset,
+					cuts: Vec::new()
+				})
+				.cuts
+				.push(Cut {
+					start: a.min(b),
+					end: a.max(b)
+				});
+		}
+	}
+
+	let mut result = Vec::new();
+	for CutLine {
+		direction,
+		offset,
+		mut cuts
+	} in lines.into_values()
+	{
+		cuts.sort_by(|a, b| a.start.total_cmp(&b.start));
+		let mut merged: Vec<Cut> = Vec::new();
+		for cut in cuts {
+			match merged.last_mut() {
+				Some(last) if cut.start <= last.end + CUT_PRECISION => {
+					last.end = last.end.max(cut.end)
+				}
+				_ => merged.push(cut)
+			}
+		}
+		// Back from the position along the line to coordinates.
+		let point = |t: f32| {
+			Vertex::new(
+				t * direction.x + offset * direction.y,
+				t * direction.y - offset * direction.x
+			)
+		};
+		result.extend(
+			merged
+				.into_iter()
+				.map(|cut| (point(cut.start), point(cut.end)))
+		);
+	}
+	// Hash map order is random, but exports should be reproducible.
+	result.sort_by(|(a, _), (b, _)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+	result
+}
+
+/// Writes the artwork as an AutoCAD 2000 DXF in millimetres, with the tiles
+/// as closed polylines on a layer per tile color, or only their cut lines.
+pub fn write_dxf(artwork: &Artwork, options: &DxfOptions) -> Vec<u8> {
+	let palette = artwork.theme.palette();
+	let height = artwork.height();
+	// DXF's y axis points upwards.
+	let to_mm = |v: &Vertex| {
+		Vertex::new(
+			v.x * options.square_size,
+			(height - v.y) * options.square_size
+		)
+	};
+	let colors: Vec<TileColor> = artwork
+		.used_colors()
+		.into_iter()
+		.filter(|color| options.include_background || *color != TileColor::None)
+		.collect();
+	let tiles: Vec<(TileColor, Vec<Vertex>)> = artwork
+		.tiles()
+		.filter(|(color, _)| colors.contains(color))
+		.map(|(color, vertices)| {
+			let mut vertices: Vec<Vertex> = vertices.iter().map(to_mm).collect();
+			// Some tiles have two vertices in the same place.
+			vertices.dedup_by(|a, b| (a.x - b.x).hypot(a.y - b.y) < CUT_PRECISION);
+			(color, vertices)
+		})
+		.collect();
+	let layers: Vec<(&str, u32)> = if options.cut_lines_only {
+		vec![(CUT_LAYER, 0xff0000)]
+	} else {
+		colors
+			.iter()
+			.map(|color| {
+				let rgb = palette.tile_color(*color);
+				(
+					color.label(),
+					(rgb.0 as u32) << 16 | (rgb.1 as u32) << 8 | rgb.2 as u32
+				)
+			})
+			.collect()
+	};
+
+	let mut dxf = DxfWriter::new();
+	dxf.section("TABLES");
+	dxf.table("VPORT", 0);
+	dxf.end_table();
+	let table = dxf.table("LTYPE", 1);
+	dxf.record("LTYPE", table, "AcDbLinetypeTableRecord")
+		.pair(2, "CONTINUOUS")
+		.pair(70, 0)
+		.pair(3, "Solid line")
+		.pair(72, 65)
+		.pair(73, 0)
+		.pair(40, 0.0);
+	dxf.end_table();
+	let table = dxf.table("LAYER", layers.len() + 1);
+	for (name, color) in iter_layers(&layers) {
+		dxf.record("LAYER", table, "AcDbLayerTableRecord")
+			.pair(2, name)
+			.pair(70, 0)
+			.pair(62, 7)
+			.pair(6, "CONTINUOUS");
+		if let Some(color) = color {
+			dxf.pair(420, color);
+		}
+	}
+	dxf.end_table();
+	for name in ["STYLE", "VIEW", "UCS"] {
+		dxf.table(name, 0);
+		dxf.end_table();
+	}
+	let table = dxf.table("APPID", 1);
+	dxf.record("APPID", table, "AcDbRegAppTableRecord")
+		.pair(2, "ACAD")
+		.pair(70, 0);
+	dxf.end_table();
+	dxf.table("DIMSTYLE", 0);
+	dxf.pair(100, "AcDbDimStyleTable").pair(71, 0);
+	dxf.end_table();
+	let table = dxf.table("BLOCK_RECORD", 2);
+	let mut block_records = Vec::new();
+	for name in ["*Model_Space", "*Paper_Space"] {
+		dxf.pair(0, "BLOCK_RECORD");
+		block_records.push(dxf.write_handle());
+		dxf.pair(330, format!("{table:X}"))
+			.pair(100, "AcDbSymbolTableRecord")
+			.pair(100, "AcDbBlockTableRecord")
+			.pair(2, name);
+	}
+	dxf.end_table();
+	dxf.end_section();
+
+	dxf.section("BLOCKS");
+	for (name, record) in ["*Model_Space", "*Paper_Space"]
+		.into_iter()
+		.zip(&block_records)
+	{
+		dxf.entity("BLOCK", *record, "0", "AcDbBlockBegin")
+			.pair(2, name)
+			.pair(70, 0)
+			.pair(10, 0.0)
+			.pair(20, 0.0)
+			.pair(30, 0.0)
+			.pair(3, name)
+			.pair(1, "");
+		dxf.entity("ENDBLK", *record, "0", "AcDbBlockEnd");
+	}
+	dxf.end_section();
+
+	let model_space = block_records[0];
+	dxf.section("ENTITIES");
+	if options.cut_lines_only {
+		let polygons: Vec<Vec<Vertex>> = tiles.into_iter().map(|(_, vertices)| vertices).collect();
+		for (from, to) in cut_lines(&polygons) {
+			dxf.entity("LINE", model_space, CUT_LAYER, "AcDbLine")
+				.pair(10, coordinate(from.x))
+				.pair(20, coordinate(from.y))
+				.pair(30, 0.0)
+				.pair(11, coordinate(to.x))
+				.pair(21, coordinate(to.y))
+				.pair(31, 0.0);
+		}
+	} else {
+		for (color, vertices) in &tiles {
+			dxf.entity("LWPOLYLINE", model_space, color.label(), "AcDbPolyline")
+				.pair(90, vertices.len())
+				.pair(70, 1);
+			for vertex in vertices {
+				dxf.pair(10, coordinate(vertex.x))
+					.pair(20, coordinate(vertex.y));
+			}
+		}
+	}
+	dxf.end_section();
+
+	dxf.section("OBJECTS");
+	dxf.pair(0, "DICTIONARY");
+	let root = dxf.write_handle();
+	let group = dxf.handle();
+	dxf.pair(330, 0)
+		.pair(100, "AcDbDictionary")
+		.pair(281, 1)
+		.pair(3, "ACAD_GROUP")
+		.pair(350, format!("{group:X}"));
+	dxf.pair(0, "DICTIONARY")
+		.pair(5, format!("{group:X}"))
+		.pair(330, format!("{root:X}"))
+		.pair(100, "AcDbDictionary")
+		.pair(281, 1);
+	dxf.end_section();
+	dxf.pair(0, "EOF");
+
+	// The header comes first, but needs to know the handles in use.
+	let mut header = DxfWriter::new();
+	header
+		.section("HEADER")
+		.pair(9, "$ACADVER")
+		.pair(1, "AC1015")
+		.pair(9, "$HANDSEED")
+		.pair(5, format!("{:X}", dxf.next_handle))
+		.pair(9, "$INSUNITS")
+		.pair(70, 4)
+		.pair(9, "$MEASUREMENT")
+		.pair(70, 1)
+		.end_section();
+	(header.output + &dxf.output).into_bytes()
+}
+
+/// The layers with their true colors, after layer "0" that every DXF has.
+fn iter_layers<'a>(layers: &'a [(&'a str, u32)]) -> impl Iterator<Item = (&'a str, Option<u32>)> {
+	[("0", None)]
+		.into_iter()
+		.chain(layers.iter().map(|(name, color)| (*name, Some(*color))))
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		theme::Theme,
+		tiling::{Tiling, TilingFormat}
+	};
+
+	use super::*;
+
+	fn polygon(points: &[(f32, f32)]) -> Vec<Vertex> {
+		points.iter().map(|&(x, y)| Vertex::new(x, y)).collect()
+	}
+
+	fn lines_on(lines: &[(Vertex, Vertex)], y: f32) -> Vec<(f32, f32)> {
+		lines
+			.iter()
+			.filter(|(from, to)| {
+				(from.y - y).abs() < CUT_PRECISION && (to.y - y).abs() < CUT_PRECISION
+			})
+			.map(|(from, to)| (from.x.min(to.x), from.x.max(to.x)))
+			.collect()
+	}
+
+	fn count_entities(dxf: &[u8], kind: &str) -> usize {
+		String::from_utf8_lossy(dxf)
+			.matches(&format!("  0\n{kind}\n"))
+			.count()
+	}
+
+	#[test]
+	fn cuts_shared_edges_once() {
+		let lower = polygon(&[(0.0, 0.0), (4.0, 0.0), (3.0, 1.0), (1.0, 1.0)]);
+		let upper = polygon(&[(1.0, 1.0), (3.0, 1.0), (4.0, 2.0), (0.0, 2.0)]);
+		let lines = cut_lines(&[lower, upper]);
+		assert_eq!(lines.len(), 7);
+		let shared = lines_on(&lines, 1.0);
+		assert_eq!(shared.len(), 1);
+		let (start, end) = shared[0];
+		assert!((start - 1.0).abs() < CUT_PRECISION && (end - 3.0).abs() < CUT_PRECISION);
+	}
+
+	#[test]
+	fn merges_collinear_edges() {
+		// The squares overlap along y = 1 and touch along y = 0 and y = 2.
+		let left = polygon(&[(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (0.0, 1.0)]);
+		let right = polygon(&[(2.0, 0.0), (4.0, 0.0), (4.0, 1.0), (2.0, 1.0)]);
+		let top = polygon(&[(1.0, 1.0), (3.0, 1.0), (3.0, 2.0), (1.0, 2.0)]);
+		let lines = cut_lines(&[left, right, top]);
+		for (y, expected) in [(0.0, (0.0, 4.0)), (1.0, (0.0, 4.0)), (2.0, (1.0, 3.0))] {
+			let on_line = lines_on(&lines, y);
+			assert_eq!(on_line.len(), 1, "lines on y = {y}");
+			let (start, end) = on_line[0];
+			assert!(
+				(start - expected.0).abs() < CUT_PRECISION
+					&& (end - expected.1).abs() < CUT_PRECISION,
+				"line on y = {y} from {start} to {end}"
+			);
+		}
+		// The vertical edges at x = 0, 2 (twice, merged), 4, 1 and 3.
+		assert_eq!(lines.len(), 3 + 5);
+	}
+
+	#[test]
+	fn leaves_out_background_tiles() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let colors: Vec<TileColor> = (0..tiling.num_tiles())
+			.map(|i| [TileColor::None, TileColor::Primary, TileColor::Secondary][i % 3])
+			.collect();
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme: Theme::Bubbles,
+			reps_x: 2,
+			reps_y: 1
+		};
+		let colored = 2 * colors.iter().filter(|c| **c != TileColor::None).count();
+
+		let without = write_dxf(&artwork, &DxfOptions::default());
+		assert_eq!(count_entities(&without, "LWPOLYLINE"), colored);
+		let text = String::from_utf8_lossy(&without);
+		assert!(!text.contains(&format!("  8\n{}\n", TileColor::None.label())));
+
+		let with = write_dxf(
+			&artwork,
+			&DxfOptions {
+				include_background: true,
+				..DxfOptions::default()
+			}
+		);
+		assert_eq!(count_entities(&with, "LWPOLYLINE"), 2 * colors.len());
+		let text = String::from_utf8_lossy(&with);
+		assert!(text.contains(&format!("  8\n{}\n", TileColor::None.label())));
+	}
+}
@@ -0,0 +1,195 @@
+use std::fmt::Write;
+
+use crate::{
+	artwork::{Artwork, SEAM_STROKE_WIDTH},
+	export::{fit_artwork, ExportSize}
+};
+
+/// Rounds coordinates like the SVG export does, to keep the file small.
+fn number(value: f32) -> f32 {
+	(value * 1000.0).round() / 1000.0
+}
+
+/// Escapes text for a PostScript string. Characters outside of ASCII are
+/// written as octal escapes of their UTF-8 bytes.
+fn ps_string(text: &str) -> String {
+	let mut escaped = String::from("(");
+	for byte in text.bytes() {
+		match byte {
+			b'(' | b')' | b'\\' => {
+				escaped.push('\\');
+				escaped.push(byte as char);
+			}
+			b' '..=b'~' => escaped.push(byte as char),
+			_ => write!(escaped, "\\{byte:03o}").unwrap()
+		}
+	}
+	escaped.push(')');
+	escaped
+}
+
+/// Writes the artwork as an encapsulated PostScript file (language level 2)
+/// with one filled and outlined path per tile. The bounding box is the
+/// export size, in which the artwork is centered.
+pub fn write_eps(artwork: &Artwork, title: &str, size: &ExportSize) -> Vec<u8> {
+	let (width, height) = size.points(artwork.width() / artwork.height());
+	let (scale, offset_x, offset_y) = fit_artwork(artwork, width, height);
+	let palette = artwork.theme.palette();
+
+	let mut eps = String::new();
+	writeln!(eps, "%!PS-Adobe-3.0 EPSF-3.0").unwrap();
+	writeln!(
+		eps,
+		"%%BoundingBox: 0 0 {} {}",
+		width.ceil() as i32,
+		height.ceil() as i32
+	)
+	.unwrap();
+	writeln!(
+		eps,
+		"%%HiResBoundingBox: 0 0 {} {}",
+		number(width),
+		number(height)
+	)
+	.unwrap();
+	writeln!(eps, "%%Title: {}", ps_string(title)).unwrap();
+	writeln!(eps, "%%Creator: {}", env!("CARGO_PKG_NAME")).unwrap();
+	writeln!(eps, "%%LanguageLevel: 2").unwrap();
+	writeln!(eps, "%%Pages: 1").unwrap();
+	writeln!(eps, "%%EndComments").unwrap();
+	writeln!(eps, "%%BeginProlog").unwrap();
+	writeln!(eps, "/m /moveto load def").unwrap();
+	writeln!(eps, "/l /lineto load def").unwrap();
+	writeln!(
+		eps,
+		"/t {{ closepath gsave fill grestore stroke }} bind def"
+	)
+	.unwrap();
+	writeln!(eps, "%%EndProlog").unwrap();
+	writeln!(eps, "%%Page: 1 1").unwrap();
+	writeln!(eps, "gsave").unwrap();
+	// Flip the y axis, which points downwards in the artwork.
+	writeln!(
+		eps,
+		"{} {} translate",
+		number(offset_x),
+		number(height - offset_y)
+	)
+	.unwrap();
+	writeln!(eps, "{scale} {} scale", -scale).unwrap();
+	writeln!(eps, "{SEAM_STROKE_WIDTH} setlinewidth").unwrap();
+	writeln!(eps, "1 setlinejoin").unwrap();
+
+	for color in artwork.used_colors() {
+		let rgb = palette.tile_color(color);
+		writeln!(
+			eps,
+			"% {} ({})",
+			color.label(),
+			palette.tile_color_name(color)
+		)
+		.unwrap();
+		writeln!(
+			eps,
+			"{} {} {} setrgbcolor",
+			number(rgb.0 as f32 / 255.0),
+			number(rgb.1 as f32 / 255.0),
+			number(rgb.2 as f32 / 255.0)
+		)
+		.unwrap();
+		for (_, vertices) in artwork.tiles().filter(|(c, _)| *c == color) {
+			let Some((first, rest)) = vertices.split_first() else {
+				continue;
+			};
+			write!(eps, "{} {} m", number(first.x), number(first.y)).unwrap();
+			for vertex in rest {
+				write!(eps, " {} {} l", number(vertex.x), number(vertex.y)).unwrap();
+			}
+			writeln!(eps, " t").unwrap();
+		}
+	}
+
+	writeln!(eps, "grestore").unwrap();
+	writeln!(eps, "showpage").unwrap();
+	writeln!(eps, "%%EOF").unwrap();
+	eps.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		components::pattern::TileColor,
+		export::LengthUnit,
+		theme::Theme,
+		tiling::{Tiling, TilingFormat}
+	};
+
+	use super::*;
+
+	fn write(colors: &[TileColor], size: &ExportSize) -> String {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors,
+			theme: Theme::Bubbles,
+			reps_x: 2,
+			reps_y: 1
+		};
+		String::from_utf8(write_eps(&artwork, "Muster", size)).unwrap()
+	}
+
+	#[test]
+	fn escapes_strings() {
+		assert_eq!(ps_string("Muster"), "(Muster)");
+		assert_eq!(ps_string("a (b) c"), r"(a \(b\) c)");
+		assert_eq!(ps_string(r"C:\muster"), r"(C:\\muster)");
+		assert_eq!(ps_string("Größe"), r"(Gr\303\266\303\237e)");
+		assert_eq!(ps_string("a\nb"), r"(a\012b)");
+	}
+
+	#[test]
+	fn bounds_the_export_size() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let colors = vec![TileColor::Primary; tiling.num_tiles()];
+		let size = ExportSize {
+			unit: LengthUnit::Mm,
+			width: Some(100.0),
+			height: Some(50.0),
+			dpi: 300
+		};
+		let eps = write(&colors, &size);
+		// 100 × 50 mm are 283.465 × 141.732 pt.
+		assert!(eps.contains("%%BoundingBox: 0 0 284 142\n"));
+		assert!(eps.contains("%%HiResBoundingBox: 0 0 283.465 141.732\n"));
+
+		let size = ExportSize {
+			unit: LengthUnit::In,
+			width: Some(2.0),
+			height: Some(1.0),
+			dpi: 300
+		};
+		let eps = write(&colors, &size);
+		assert!(eps.contains("%%BoundingBox: 0 0 144 72\n"));
+		assert!(eps.contains("%%HiResBoundingBox: 0 0 144 72\n"));
+	}
+
+	#[test]
+	fn writes_one_path_per_tile() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let colors: Vec<TileColor> = (0..tiling.num_tiles())
+			.map(|i| [TileColor::Primary, TileColor::Secondary, TileColor::None][i % 3])
+			.collect();
+		let eps = write(&colors, &ExportSize::default());
+		let paths: Vec<&str> = eps.lines().filter(|line| line.ends_with(" t")).collect();
+		assert_eq!(paths.len(), 2 * tiling.num_tiles());
+		assert!(paths.iter().all(|path| path.matches(" m").count() == 1));
+
+		// The paths follow the comment of their color.
+		for color in [TileColor::Primary, TileColor::Secondary, TileColor::None] {
+			let start = eps.find(&format!("% {} (", color.label())).unwrap();
+			let section = eps[start..].lines().skip(2);
+			let count = section.take_while(|line| line.ends_with(" t")).count();
+			assert_eq!(count, 2 * colors.iter().filter(|c| **c == color).count());
+		}
+	}
+}
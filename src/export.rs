@@ -1,20 +1,35 @@
 use std::{error::Error, fmt, iter};
 
 use enum_iterator::Sequence;
-use js_sys::{Array, JsString, Uint8Array};
-use leptos::{
-	document,
-	leptos_dom::logging::{console_error, console_log}
-};
+use js_sys::{Array, Uint8Array};
+use leptos::{document, leptos_dom::logging::console_error};
+use usvg::TreeParsing;
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::{window, Blob, HtmlAnchorElement, SvgElement, Url, XmlSerializer};
+use web_sys::{Blob, HtmlAnchorElement, Url};
+
+use crate::{
+	artwork::Artwork,
+	dxf::{write_dxf, DxfOptions},
+	eps::write_eps,
+	illustrator::write_ai,
+	print::{write_pdf, PrintOptions},
+	raster::{render_png, RasterOptions},
+	svg::write_svg,
+	template::{write_template, TemplateOptions, TEMPLATE_FORMATS}
+};
+
+/// The height in pixels of exports without a size.
+const EXPORT_HEIGHT: f32 = 1000.0;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
 #[repr(u8)]
 pub enum OutputFormat {
 	Svg,
 	Ai,
-	Pdf
+	Pdf,
+	Png,
+	Eps,
+	Dxf
 }
 
 impl OutputFormat {
@@ -22,11 +37,155 @@ impl OutputFormat {
 		match self {
 			Self::Svg => "svg",
 			Self::Pdf => "pdf",
-			Self::Ai => "ai"
+			Self::Ai => "ai",
+			Self::Png => "png",
+			Self::Eps => "eps",
+			Self::Dxf => "dxf"
 		}
 	}
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+#[repr(u8)]
+pub enum LengthUnit {
+	Px,
+	Mm,
+	Cm,
+	In
+}
+
+impl LengthUnit {
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Px => "px",
+			Self::Mm => "mm",
+			Self::Cm => "cm",
+			Self::In => "in"
+		}
+	}
+
+	/// How many of the unit make an inch, or `None` for pixels, whose size
+	/// depends on the resolution.
+	fn per_inch(self) -> Option<f32> {
+		match self {
+			Self::Px => None,
+			Self::Mm => Some(25.4),
+			Self::Cm => Some(2.54),
+			Self::In => Some(1.0)
+		}
+	}
+}
+
+/// The size of an export. If only one side is given, the other one follows
+/// from the aspect ratio of the pattern. If both are, the pattern is fitted
+/// into that area and centered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportSize {
+	pub unit: LengthUnit,
+	pub width: Option<f32>,
+	pub height: Option<f32>,
+	/// The resolution that converts between pixels and physical units.
+	pub dpi: u32
+}
+
+impl Default for ExportSize {
+	fn default() -> Self {
+		Self {
+			unit: LengthUnit::Px,
+			width: None,
+			height: Some(EXPORT_HEIGHT),
+			dpi: 300
+		}
+	}
+}
+
+impl ExportSize {
+	/// Width and height in the size's unit, for a pattern with the given
+	/// aspect ratio (width / height).
+	pub fn dimensions(&self, aspect_ratio: f32) -> (f32, f32) {
+		match (self.width, self.height) {
+			(Some(width), Some(height)) => (width, height),
+			(Some(width), None) => (width, width / aspect_ratio),
+			(None, Some(height)) => (height * aspect_ratio, height),
+			(None, None) => (EXPORT_HEIGHT * aspect_ratio, EXPORT_HEIGHT)
+		}
+	}
+
+	fn inches(&self, length: f32) -> f32 {
+		match self.unit.per_inch() {
+			Some(per_inch) => length / per_inch,
+			None => length / self.dpi.max(1) as f32
+		}
+	}
+
+	/// The same size in another unit.
+	pub fn with_unit(&self, unit: LengthUnit) -> Self {
+		let convert = |length: f32| {
+			let inches = self.inches(length);
+			match unit.per_inch() {
+				Some(per_inch) => inches * per_inch,
+				None => inches * self.dpi as f32
+			}
+		};
+		Self {
+			unit,
+			width: self.width.map(convert),
+			height: self.height.map(convert),
+			..*self
+		}
+	}
+
+	/// Width and height in pixels at the size's resolution.
+	pub fn pixels(&self, aspect_ratio: f32) -> (u32, u32) {
+		let (width, height) = self.dimensions(aspect_ratio);
+		let pixels = |length: f32| {
+			let pixels = match self.unit {
+				LengthUnit::Px => length,
+				_ => self.inches(length) * self.dpi as f32
+			};
+			(pixels.round() as u32).max(1)
+		};
+		(pixels(width), pixels(height))
+	}
+
+	/// Width and height in PostScript points.
+	pub fn points(&self, aspect_ratio: f32) -> (f32, f32) {
+		let (width, height) = self.dimensions(aspect_ratio);
+		(self.inches(width) * 72.0, self.inches(height) * 72.0)
+	}
+
+	/// The `width` and `height` attributes of an SVG, in the size's unit.
+	pub fn svg_dimensions(&self, aspect_ratio: f32) -> (String, String) {
+		let (width, height) = self.dimensions(aspect_ratio);
+		let unit = match self.unit {
+			LengthUnit::Px => "",
+			unit => unit.label()
+		};
+		(format!("{width}{unit}"), format!("{height}{unit}"))
+	}
+}
+
+/// Scales and centers the artwork in an area of the given size, returning the
+/// scale and the offset of its top left corner.
+pub fn fit_artwork(artwork: &Artwork, width: f32, height: f32) -> (f32, f32, f32) {
+	let scale = (width / artwork.width()).min(height / artwork.height());
+	(
+		scale,
+		(width - artwork.width() * scale) / 2.0,
+		(height - artwork.height() * scale) / 2.0
+	)
+}
+
+/// The settings of the formats that have any.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportOptions {
+	pub size: ExportSize,
+	pub raster: RasterOptions,
+	pub dxf: DxfOptions,
+	pub print: PrintOptions,
+	pub template: TemplateOptions
+}
+
 impl fmt::Display for OutputFormat {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		write!(f, ".{}", self.extension())
@@ -40,107 +199,60 @@ fn create_url(content: Vec<u8>) -> Result<String, JsValue> {
 	Url::create_object_url_with_blob(&blob)
 }
 
-const EXPORT_HEIGHT: i32 = 1000;
-
-fn copy_children(source: &SvgElement, target: &SvgElement) -> Result<(), JsValue> {
-	let children = source.children();
-	let mut i = 0;
-	while let Some(child) = children.item(i) {
-		if let Ok(svg_child) = child.dyn_into::<SvgElement>() {
-			let clone = svg_child.clone_node()?.dyn_into::<SvgElement>().unwrap();
-			copy_children(&svg_child, &clone)?;
-			target.append_child(&clone)?;
+/// Converts an SVG to the given format.
+pub fn convert_from_svg(
+	svg_content: String,
+	format: OutputFormat,
+	options: &ExportOptions
+) -> Result<Vec<u8>, Box<dyn Error>> {
+	match format {
+		OutputFormat::Svg => Ok(svg_content.bytes().collect()),
+		// Layered Illustrator files and print PDFs are written from the tile
+		// geometry by `write_artwork`; other SVGs can only be converted to a
+		// flat RGB PDF.
+		OutputFormat::Pdf | OutputFormat::Ai => {
+			let tree = usvg::Tree::from_str(&svg_content, &usvg::Options::default())?;
+			let view_box = tree.view_box.rect;
+			let (width, height) = options.size.points(view_box.width() / view_box.height());
+			let pdf_options = svg2pdf::Options {
+				viewport: usvg::Size::from_wh(width, height),
+				..svg2pdf::Options::default()
+			};
+			Ok(svg2pdf::convert_tree(&tree, pdf_options))
+		}
+		OutputFormat::Png => Ok(render_png(&svg_content, &options.size, &options.raster)?),
+		OutputFormat::Eps | OutputFormat::Dxf => {
+			Err(format!("{format} can only be written from the tile geometry").into())
 		}
-		i += 1;
 	}
-	Ok(())
 }
 
-fn render_svg(elem_in_page: SvgElement) -> Result<String, JsValue> {
-	let document = window().unwrap().document().unwrap();
-	let svg_doc = document
-		.implementation()?
-		.create_document(Some("http://www.w3.org/2000/svg"), "svg")?;
-
-	let svg_elem = svg_doc
-		.document_element()
-		.unwrap()
-		.dyn_into::<SvgElement>()
-		.unwrap();
-	let view_box = elem_in_page
-		.get_attribute("viewBox")
-		.expect("Export SVG is missing viewBox attribute!");
-	let vb_parts = view_box.split(' ').collect::<Vec<&str>>();
-	let vb_width: i32 = vb_parts[2]
-		.parse()
-		.expect("Failed to parse export SVG's viewBox width");
-	let vb_height: i32 = vb_parts[3]
-		.parse()
-		.expect("Failed to parse export SVG's viewBox height");
-
-	let aspect_ratio = vb_width / vb_height;
-	let export_width = EXPORT_HEIGHT * aspect_ratio;
-
-	svg_elem.set_attribute("viewBox", &view_box)?;
-	svg_elem.set_attribute("width", &export_width.to_string())?;
-	svg_elem.set_attribute("height", &EXPORT_HEIGHT.to_string())?;
-
-	copy_children(&elem_in_page, &svg_elem)?;
-
-	let serializer = XmlSerializer::new()?;
-	let xml = serializer.serialize_to_string(&svg_doc)?;
-	Ok(xml)
-}
-
-fn get_svg_elem(selector: &str) -> Option<SvgElement> {
-	let Some(elem) = window()
-		.unwrap()
-		.document()
-		.unwrap()
-		.query_selector(selector)
-		.unwrap()
-	else {
-		return None;
-	};
-	Some(elem.dyn_into().unwrap())
-}
-
-fn convert_from_svg(svg_content: String, format: OutputFormat) -> Result<Vec<u8>, Box<dyn Error>> {
+/// Writes the artwork in the given format. Most formats are generated from
+/// the tile geometry; PNGs are rendered from the SVG. If given, `metadata` is
+/// embedded in SVG output, so that the pattern can be imported again.
+///
+/// If templates are enabled, the grid is written instead, in the formats that
+/// support it.
+pub fn write_artwork(
+	artwork: &Artwork,
+	title: &str,
+	format: OutputFormat,
+	metadata: Option<&str>,
+	options: &ExportOptions
+) -> Result<Vec<u8>, Box<dyn Error>> {
+	if options.template.enabled && TEMPLATE_FORMATS.contains(&format) {
+		let svg = write_template(artwork, &options.template, &options.size, metadata);
+		return convert_from_svg(svg, format, options);
+	}
 	match format {
-		OutputFormat::Svg => Ok(svg_content.bytes().collect()),
-		OutputFormat::Pdf | OutputFormat::Ai => Ok(svg2pdf::convert_str(
-			&svg_content,
-			svg2pdf::Options::default()
-		)?)
-	}
-}
-
-fn get_download_url(selector: &str, format: OutputFormat) -> Result<String, ()> {
-	let Some(svg_elem) = get_svg_elem(selector) else {
-		console_error("Cannot export SVG; element not found!");
-		return Err(());
-	};
-	let svg_content = match render_svg(svg_elem) {
-		Ok(content) => content,
-		Err(err) => {
-			console_error(&format!(
-				"Failed to render svg: {}",
-				err.dyn_into::<JsString>().unwrap()
-			));
-			return Err(());
-		}
-	};
-
-	let output_content = match convert_from_svg(svg_content, format) {
-		Ok(output_content) => output_content,
-		Err(err) => {
-			console_error(&format!("Failed to convert to {format}: {err}"));
-			return Err(());
+		OutputFormat::Ai => Ok(write_ai(artwork, title, &options.size)),
+		OutputFormat::Pdf => Ok(write_pdf(artwork, title, &options.print, &options.size)?),
+		OutputFormat::Eps => Ok(write_eps(artwork, title, &options.size)),
+		OutputFormat::Dxf => Ok(write_dxf(artwork, &options.dxf)),
+		OutputFormat::Svg | OutputFormat::Png => {
+			convert_from_svg(write_svg(artwork, &options.size, metadata), format, options)
 		}
-	};
-
-	let url = create_url(output_content).expect("Failed to create SVG file blob");
-	Ok(url)
+	}
 }
 
 fn download_file(url: &str, filename: &str) {
@@ -162,16 +274,32 @@ fn download_file(url: &str, filename: &str) {
 	a.remove();
 }
 
-pub fn export_pattern(selector: &str, filename: &str, format: OutputFormat) {
-	let Ok(download_url) = get_download_url(selector, format) else {
-		console_error("SVG File creation failed");
-		return;
-	};
-
-	let complete_filename = format!("{filename}.{}", format.extension());
-
-	console_log(&complete_filename);
+/// Offers arbitrary content for download.
+pub fn save_file(content: Vec<u8>, filename: &str) {
+	let url = create_url(content).expect("Failed to create file blob");
+	download_file(&url, filename);
+}
 
-	download_file(&download_url, &complete_filename);
+/// Offers the artwork for download in the given format, see
+/// [`write_artwork`].
+pub fn export_artwork(
+	artwork: &Artwork,
+	title: &str,
+	filename: &str,
+	format: OutputFormat,
+	metadata: Option<&str>,
+	options: &ExportOptions
+) {
+	match write_artwork(artwork, title, format, metadata, options) {
+		Ok(content) => save_file(content, &format!("{filename}.{}", format.extension())),
+		Err(err) => console_error(&format!("Failed to export {format}: {err}"))
+	}
 }
 
+/// Offers an SVG for download, converted to the given format.
+pub fn export_svg(svg: String, filename: &str, format: OutputFormat, options: &ExportOptions) {
+	match convert_from_svg(svg, format, options) {
+		Ok(content) => save_file(content, &format!("{filename}.{}", format.extension())),
+		Err(err) => console_error(&format!("Failed to convert to {format}: {err}"))
+	}
+}
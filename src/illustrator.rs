@@ -0,0 +1,188 @@
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, TextStr};
+
+use crate::{
+	artwork::{Artwork, SEAM_STROKE_WIDTH},
+	export::{fit_artwork, ExportSize}
+};
+
+/// Writes the artwork as a PDF that Illustrator opens with structure: every
+/// tile color is drawn on its own layer (an optional content group) in a spot
+/// color named after the theme color, which Illustrator lists as a swatch.
+/// Recoloring the swatch recolors every tile of that color.
+pub fn write_ai(artwork: &Artwork, title: &str, size: &ExportSize) -> Vec<u8> {
+	let (width, height) = size.points(artwork.width() / artwork.height());
+	let (scale, offset_x, offset_y) = fit_artwork(artwork, width, height);
+	let palette = artwork.theme.palette();
+	let colors = artwork.used_colors();
+
+	let mut next_id = 1;
+	let mut alloc = || {
+		let id = Ref::new(next_id);
+		next_id += 1;
+		id
+	};
+	let catalog_id = alloc();
+	let info_id = alloc();
+	let pages_id = alloc();
+	let page_id = alloc();
+	let content_id = alloc();
+	let layer_ids: Vec<Ref> = colors.iter().map(|_| alloc()).collect();
+	let swatch_ids: Vec<Ref> = colors.iter().map(|_| alloc()).collect();
+
+	let mut pdf = Pdf::new();
+	pdf.set_version(1, 5);
+
+	let mut catalog = pdf.catalog(catalog_id);
+	catalog.pages(pages_id);
+	let mut properties = catalog.insert(Name(b"OCProperties")).dict();
+	properties
+		.insert(Name(b"OCGs"))
+		.array()
+		.items(layer_ids.iter().copied());
+	let mut config = properties.insert(Name(b"D")).dict();
+	config
+		.insert(Name(b"Order"))
+		.array()
+		.items(layer_ids.iter().copied());
+	config
+		.insert(Name(b"ON"))
+		.array()
+		.items(layer_ids.iter().copied());
+	config.finish();
+	properties.finish();
+	catalog.finish();
+
+	pdf.document_info(info_id).title(TextStr(title));
+
+	pdf.pages(pages_id).kids([page_id]).count(1);
+
+	let mut page = pdf.page(page_id);
+	page.parent(pages_id)
+		.media_box(Rect::new(0.0, 0.0, width, height))
+		.contents(content_id);
+	let mut resources = page.resources();
+	let mut color_spaces = resources.color_spaces();
+	for (i, swatch_id) in swatch_ids.iter().enumerate() {
+		color_spaces.pair(Name(format!("CS{i}").as_bytes()), *swatch_id);
+	}
+	color_spaces.finish();
+	let mut layers = resources.insert(Name(b"Properties")).dict();
+	for (i, layer_id) in layer_ids.iter().enumerate() {
+		layers.pair(Name(format!("L{i}").as_bytes()), *layer_id);
+	}
+	layers.finish();
+	resources.finish();
+	page.finish();
+
+	for ((color, layer_id), swatch_id) in colors.iter().zip(&layer_ids).zip(&swatch_ids) {
+		let name = palette.tile_color_name(*color);
+		let mut layer = pdf.indirect(*layer_id).dict();
+		layer.pair(Name(b"Type"), Name(b"OCG"));
+		layer.pair(
+			Name(b"Name"),
+			TextStr(&format!("{} ({name})", color.label()))
+		);
+		layer.finish();
+
+		let rgb = palette.tile_color(*color);
+		let mut swatch = pdf
+			.color_space(*swatch_id)
+			.separation(Name(name.as_bytes()));
+		swatch.alternate_color_space().device_rgb();
+		swatch
+			.tint_exponential()
+			.domain([0.0, 1.0])
+			.c0([1.0, 1.0, 1.0])
+			.c1([rgb.0, rgb.1, rgb.2].map(|c| c as f32 / 255.0))
+			.n(1.0);
+	}
+
+	let mut content = Content::new();
+	// Flip the y axis, which points downwards in the artwork.
+	content.transform([scale, 0.0, 0.0, -scale, offset_x, height - offset_y]);
+	content.set_line_width(SEAM_STROKE_WIDTH);
+	for (i, color) in colors.iter().enumerate() {
+		let layer = format!("L{i}");
+		let swatch = format!("CS{i}");
+		content
+			.begin_marked_content_with_properties(Name(b"OC"))
+			.properties_named(Name(layer.as_bytes()));
+		content
+			.set_fill_color_space(Name(swatch.as_bytes()))
+			.set_fill_color([1.0])
+			.set_stroke_color_space(Name(swatch.as_bytes()))
+			.set_stroke_color([1.0]);
+		for (_, vertices) in artwork.tiles().filter(|(c, _)| c == color) {
+			let Some((first, rest)) = vertices.split_first() else {
+				continue;
+			};
+			content.move_to(first.x, first.y);
+			for vertex in rest {
+				content.line_to(vertex.x, vertex.y);
+			}
+			// Tiles stay separate paths, so that they can be edited one by one.
+			content.close_fill_nonzero_and_stroke();
+		}
+		content.end_marked_content();
+	}
+	pdf.stream(content_id, &content.finish());
+
+	pdf.finish()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		components::pattern::TileColor,
+		export::LengthUnit,
+		theme::Theme,
+		tiling::{Tiling, TilingFormat}
+	};
+
+	use super::*;
+
+	#[test]
+	fn writes_a_layer_with_one_path_per_tile() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let colors: Vec<TileColor> = (0..tiling.num_tiles())
+			.map(|i| [TileColor::Primary, TileColor::None, TileColor::Secondary][i % 3])
+			.collect();
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme: Theme::Noble,
+			reps_x: 3,
+			reps_y: 2
+		};
+		let size = ExportSize {
+			unit: LengthUnit::Mm,
+			width: Some(300.0),
+			height: None,
+			dpi: 300
+		};
+		let pdf = write_ai(&artwork, "Muster", &size);
+		let pdf = String::from_utf8_lossy(&pdf);
+
+		let (width, height) = size.points(artwork.width() / artwork.height());
+		assert!(pdf.contains(&format!("/MediaBox [0 0 {width} {height}]")));
+		assert_eq!(pdf.matches("/Type /OCG").count(), 3);
+		assert_eq!(pdf.matches("/Separation").count(), 3);
+
+		// Every layer holds one closed path per tile of its color.
+		let content = &pdf[pdf.find("/OC /L0 BDC").unwrap()..];
+		let layers: Vec<&str> = content.split(" BDC\n").skip(1).collect();
+		assert_eq!(layers.len(), 3);
+		for (layer, color) in layers.iter().zip(artwork.used_colors()) {
+			let tiles = colors.iter().filter(|c| **c == color).count() * 6;
+			assert_eq!(
+				layer.lines().filter(|line| *line == "b").count(),
+				tiles,
+				"{color:?}"
+			);
+			assert_eq!(
+				layer.lines().filter(|line| line.ends_with(" m")).count(),
+				tiles
+			);
+		}
+	}
+}
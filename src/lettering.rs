@@ -0,0 +1,272 @@
+use crate::tiling::Tiling;
+
+const GLYPH_WIDTH: usize = 3;
+pub const GLYPH_HEIGHT: usize = 5;
+const GLYPH_SPACING: usize = 1;
+
+type Glyph = [u8; GLYPH_HEIGHT];
+
+const FALLBACK_GLYPH: Glyph = [0b111, 0b001, 0b010, 0b000, 0b010];
+
+fn glyph(ch: char) -> Glyph {
+	match ch.to_uppercase().next().unwrap_or(ch) {
+		' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+		'0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+		'1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+		'2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+		'3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+		'4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+		'5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+		'6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+		'7' => [0b111, 0b001, 0b001, 0b010, 0b010],
+		'8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+		'9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+		'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+		'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+		'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+		'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+		'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+		'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+		'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+		'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+		'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+		'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+		'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+		'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+		'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+		'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+		'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+		'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+		'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+		'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+		'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+		'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+		'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+		'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+		'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+		'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+		'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+		'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+		'Ä' => [0b101, 0b010, 0b101, 0b111, 0b101],
+		'Ö' => [0b101, 0b000, 0b111, 0b101, 0b111],
+		'Ü' => [0b101, 0b000, 0b101, 0b101, 0b111],
+		'.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+		':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+		'-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+		'+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+		'/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+		'!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+		_ => FALLBACK_GLYPH
+	}
+}
+
+/// The width of `text` in pixels of the bitmap font.
+pub fn text_width(text: &str) -> usize {
+	let num_glyphs = text.chars().count();
+	if num_glyphs == 0 {
+		return 0;
+	}
+	num_glyphs * (GLYPH_WIDTH + GLYPH_SPACING) - GLYPH_SPACING
+}
+
+/// The pixels of `text` in the bitmap font that are set, as column and row
+/// from the top left corner of the first glyph.
+pub fn text_pixels(text: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+	text.chars().enumerate().flat_map(|(n, ch)| {
+		let glyph_x = n * (GLYPH_WIDTH + GLYPH_SPACING);
+		glyph(ch)
+			.into_iter()
+			.enumerate()
+			.flat_map(move |(row, bits)| {
+				(0..GLYPH_WIDTH)
+					.filter(move |col| bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0)
+					.map(move |col| (glyph_x + col, row))
+			})
+	})
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Alignment {
+	Start,
+	Center,
+	End
+}
+
+impl Alignment {
+	fn offset(self, available: usize, needed: usize) -> isize {
+		let free = available as isize - needed as isize;
+		match self {
+			Self::Start => 0,
+			Self::Center => free / 2,
+			Self::End => free
+		}
+	}
+}
+
+/// A single line of text set in the built-in 3×5 bitmap font, where every
+/// pixel of a glyph covers `size`×`size` squares of the tiling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lettering {
+	pub text: String,
+	pub size: usize,
+	pub horizontal: Alignment,
+	pub vertical: Alignment
+}
+
+impl Lettering {
+	pub fn width(&self) -> usize {
+		text_width(&self.text) * self.size
+	}
+
+	pub fn height(&self) -> usize {
+		GLYPH_HEIGHT * self.size
+	}
+
+	/// Returns the indices of all tiles covered by the lettering. Pixels that
+	/// fall outside of the tiling are clipped.
+	pub fn rasterize(&self, tiling: &Tiling) -> Vec<usize> {
+		let origin_x = self.horizontal.offset(tiling.columns(), self.width());
+		let origin_y = self.vertical.offset(tiling.rows(), self.height());
+
+		let mut tiles = Vec::new();
+		for (col, row) in text_pixels(&self.text) {
+			let pixel_x = origin_x + (col * self.size) as isize;
+			let pixel_y = origin_y + (row * self.size) as isize;
+			self.rasterize_pixel(tiling, pixel_x, pixel_y, &mut tiles);
+		}
+		tiles
+	}
+
+	fn rasterize_pixel(
+		&self,
+		tiling: &Tiling,
+		pixel_x: isize,
+		pixel_y: isize,
+		tiles: &mut Vec<usize>
+	) {
+		for y in pixel_y..pixel_y + self.size as isize {
+			for x in pixel_x..pixel_x + self.size as isize {
+				if x < 0 || y < 0 || x as usize >= tiling.columns() || y as usize >= tiling.rows() {
+					continue;
+				}
+				tiles.extend(tiling.square_tiles(x as usize, y as usize));
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::BTreeSet;
+
+	use super::*;
+	use crate::tiling::TilingFormat;
+
+	fn lettering(text: &str, size: usize, horizontal: Alignment, vertical: Alignment) -> Lettering {
+		Lettering {
+			text: text.to_string(),
+			size,
+			horizontal,
+			vertical
+		}
+	}
+
+	/// The squares covered by `tiles`, each of which must contain both of
+	/// its trapezoids.
+	fn squares(tiling: &Tiling, tiles: &[usize]) -> BTreeSet<(usize, usize)> {
+		let squares: BTreeSet<_> = tiles
+			.iter()
+			.map(|&i| {
+				let (x, y, _) = tiling.tile_position(i);
+				(x, y)
+			})
+			.collect();
+		for &(x, y) in &squares {
+			for tile in tiling.square_tiles(x, y) {
+				assert!(tiles.contains(&tile), "square {x},{y} only half covered");
+			}
+		}
+		squares
+	}
+
+	#[test]
+	fn measures_glyphs_with_spacing() {
+		assert_eq!(text_width(""), 0);
+		assert_eq!(text_width("A"), 3);
+		assert_eq!(text_width("AB"), 7);
+		assert_eq!(text_width("ÄÖÜ"), 11);
+
+		let lettering = lettering("AB", 2, Alignment::Start, Alignment::Start);
+		assert_eq!(lettering.width(), 14);
+		assert_eq!(lettering.height(), 10);
+	}
+
+	#[test]
+	fn places_glyphs_after_each_other() {
+		let pixels: Vec<_> = text_pixels(" -").collect();
+		assert_eq!(pixels, [(4, 2), (5, 2), (6, 2)]);
+
+		let pixels: Vec<_> = text_pixels("I").collect();
+		assert_eq!(pixels.len(), 9);
+		assert!(pixels
+			.iter()
+			.all(|&(col, row)| col < GLYPH_WIDTH && row < GLYPH_HEIGHT));
+	}
+
+	#[test]
+	fn falls_back_for_unsupported_characters() {
+		assert_eq!(glyph('a'), glyph('A'));
+		assert_eq!(glyph('ä'), glyph('Ä'));
+		assert_eq!(glyph('#'), FALLBACK_GLYPH);
+		assert_eq!(glyph('€'), FALLBACK_GLYPH);
+		assert_eq!(text_pixels("#").count(), text_pixels("€").count());
+	}
+
+	#[test]
+	fn rasterizes_aligned_text() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+
+		let tiles = lettering("-", 1, Alignment::Center, Alignment::Center).rasterize(&tiling);
+		assert_eq!(tiles.len(), 6);
+		assert_eq!(
+			squares(&tiling, &tiles),
+			BTreeSet::from([(1, 2), (2, 2), (3, 2)])
+		);
+
+		let tiles = lettering("-", 1, Alignment::End, Alignment::Start).rasterize(&tiling);
+		assert_eq!(
+			squares(&tiling, &tiles),
+			BTreeSet::from([(2, 2), (3, 2), (4, 2)])
+		);
+	}
+
+	#[test]
+	fn scales_pixels_by_size() {
+		let tiling = Tiling::load(TilingFormat::F15X15);
+		let tiles = lettering(".", 2, Alignment::Start, Alignment::Start).rasterize(&tiling);
+		assert_eq!(
+			squares(&tiling, &tiles),
+			BTreeSet::from([(2, 8), (3, 8), (2, 9), (3, 9)])
+		);
+	}
+
+	#[test]
+	fn clips_text_past_the_edges() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+
+		// 9×15 squares, centered on 5×5: the bar of the minus sign spans the
+		// full width, the rest of the glyph lies outside.
+		let tiles = lettering("-", 3, Alignment::Center, Alignment::Center).rasterize(&tiling);
+		assert_eq!(tiles.len(), 30);
+		assert_eq!(
+			squares(&tiling, &tiles),
+			(0..5).flat_map(|x| (1..4).map(move |y| (x, y))).collect()
+		);
+
+		for alignment in [Alignment::Start, Alignment::Center, Alignment::End] {
+			let tiles = lettering("WIDE TEXT", 2, alignment, alignment).rasterize(&tiling);
+			assert!(tiles.iter().all(|&i| i < tiling.num_tiles()));
+		}
+	}
+}
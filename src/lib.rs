@@ -0,0 +1,30 @@
+#![feature(const_fn_floating_point_arithmetic)]
+
+pub mod artwork;
+pub mod bundle;
+pub mod catalog;
+pub mod components;
+pub mod document;
+pub mod dxf;
+pub mod eps;
+pub mod export;
+pub mod illustrator;
+pub mod lettering;
+pub mod library;
+pub mod pattern_file;
+pub mod print;
+pub mod qr;
+pub mod raster;
+pub mod rules;
+pub mod session;
+pub mod share;
+pub mod snippet;
+pub mod stamps;
+pub mod storage;
+pub mod svg;
+pub mod svg_import;
+pub mod template;
+pub mod theme;
+pub mod tiling;
+pub mod utils;
+pub mod zip;
@@ -0,0 +1,201 @@
+use js_sys::{Array, Function, Promise};
+use uuid::Uuid;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, Event, IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+use crate::pattern_file::PatternFile;
+
+const DATABASE_NAME: &str = "goldbeck-pattern";
+const DATABASE_VERSION: u32 = 1;
+const STORE_NAME: &str = "patterns";
+
+/// A pattern in the library. Entries are stored as pattern file JSON under
+/// their id, so older entries are migrated like older files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryEntry {
+	pub id: Uuid,
+	pub file: PatternFile
+}
+
+impl LibraryEntry {
+	/// Reads an entry as it is stored, with its id as the key.
+	fn from_stored(key: &str, json: &str) -> Option<Self> {
+		Some(Self {
+			id: Uuid::parse_str(key).ok()?,
+			file: PatternFile::from_json(json).ok()?
+		})
+	}
+
+	/// The key and value the entry is stored as.
+	fn to_stored(&self) -> (String, String) {
+		(self.id.to_string(), self.file.to_json())
+	}
+
+	/// Whether the name or author contains the lowercase `query`.
+	pub fn matches(&self, query: &str) -> bool {
+		let info = &self.file.info;
+		info.name.to_lowercase().contains(query) || info.author.to_lowercase().contains(query)
+	}
+}
+
+/// Resolves once the request succeeds, with its result. The handlers are
+/// attached immediately, not when the future is first polled.
+fn complete(request: &IdbRequest) -> JsFuture {
+	let promise = Promise::new(&mut |resolve: Function, reject: Function| {
+		let succeeded = request.clone();
+		let on_success = Closure::once_into_js(move |_: Event| {
+			resolve
+				.call1(
+					&JsValue::NULL,
+					&succeeded.result().unwrap_or(JsValue::UNDEFINED)
+				)
+				.unwrap_or(JsValue::UNDEFINED);
+		});
+		let failed = request.clone();
+		let on_error = Closure::once_into_js(move |_: Event| {
+			let error = failed.error().ok().flatten().map(JsValue::from);
+			reject
+				.call1(&JsValue::NULL, &error.unwrap_or(JsValue::UNDEFINED))
+				.unwrap_or(JsValue::UNDEFINED);
+		});
+		request.set_onsuccess(Some(on_success.unchecked_ref()));
+		request.set_onerror(Some(on_error.unchecked_ref()));
+	});
+	JsFuture::from(promise)
+}
+
+/// A collection of saved patterns in the browser's IndexedDB.
+#[derive(Debug, Clone)]
+pub struct Library {
+	db: IdbDatabase
+}
+
+impl Library {
+	pub async fn open() -> Result<Self, JsValue> {
+		let factory = window()
+			.expect("Window is not available!")
+			.indexed_db()?
+			.ok_or_else(|| JsValue::from_str("IndexedDB is not available"))?;
+		let request = factory.open_with_u32(DATABASE_NAME, DATABASE_VERSION)?;
+		let upgrading = request.clone();
+		let on_upgrade_needed = Closure::once_into_js(move |_: Event| {
+			let Ok(db) = upgrading
+				.result()
+				.and_then(|db| db.dyn_into::<IdbDatabase>())
+			else {
+				return;
+			};
+			if !db.object_store_names().contains(STORE_NAME) {
+				db.create_object_store(STORE_NAME)
+					.expect("Failed to create pattern store");
+			}
+		});
+		request.set_onupgradeneeded(Some(on_upgrade_needed.unchecked_ref()));
+		let db = complete(&request).await?.dyn_into()?;
+		Ok(Self { db })
+	}
+
+	fn store(&self, mode: IdbTransactionMode) -> Result<IdbObjectStore, JsValue> {
+		self.db
+			.transaction_with_str_and_mode(STORE_NAME, mode)?
+			.object_store(STORE_NAME)
+	}
+
+	/// All entries, oldest first. Entries that can't be read are skipped.
+	pub async fn entries(&self) -> Result<Vec<LibraryEntry>, JsValue> {
+		let store = self.store(IdbTransactionMode::Readonly)?;
+		// Both requests return the entries ordered by key.
+		let keys = complete(&store.get_all_keys()?);
+		let values = complete(&store.get_all()?);
+		let keys: Array = keys.await?.dyn_into()?;
+		let values: Array = values.await?.dyn_into()?;
+		let mut entries: Vec<LibraryEntry> = keys
+			.iter()
+			.zip(values.iter())
+			.filter_map(|(key, value)| {
+				LibraryEntry::from_stored(&key.as_string()?, &value.as_string()?)
+			})
+			.collect();
+		entries.sort_by(|a, b| a.file.info.created.cmp(&b.file.info.created));
+		Ok(entries)
+	}
+
+	/// Adds the entry, or replaces the one with the same id.
+	pub async fn put(&self, entry: &LibraryEntry) -> Result<(), JsValue> {
+		let (key, value) = entry.to_stored();
+		let request = self
+			.store(IdbTransactionMode::Readwrite)?
+			.put_with_key(&JsValue::from_str(&value), &JsValue::from_str(&key))?;
+		complete(&request).await?;
+		Ok(())
+	}
+
+	pub async fn delete(&self, id: Uuid) -> Result<(), JsValue> {
+		let request = self
+			.store(IdbTransactionMode::Readwrite)?
+			.delete(&JsValue::from_str(&id.to_string()))?;
+		complete(&request).await?;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		components::pattern::TileColor,
+		document::PatternData,
+		pattern_file::PatternInfo,
+		theme::Theme,
+		tiling::{Tiling, TilingFormat}
+	};
+
+	use super::*;
+
+	fn entry(name: &str, author: &str) -> LibraryEntry {
+		let format = TilingFormat::F5X5;
+		let colors = vec![TileColor::Secondary; Tiling::load(format).num_tiles()];
+		LibraryEntry {
+			id: Uuid::new_v4(),
+			file: PatternFile::new(
+				PatternInfo {
+					name: name.to_string(),
+					author: author.to_string(),
+					created: String::from("2023-09-01T12:00:00.000Z")
+				},
+				String::from("2023-09-02T08:30:00.000Z"),
+				PatternData::from_colors(format, Theme::Glisten, colors)
+			)
+		}
+	}
+
+	#[test]
+	fn stores_entries_under_their_id() {
+		let entry = entry("Fassade Nord", "Anna");
+		let (key, value) = entry.to_stored();
+		assert_eq!(key, entry.id.to_string());
+		assert_eq!(LibraryEntry::from_stored(&key, &value), Some(entry));
+	}
+
+	#[test]
+	fn skips_unreadable_entries() {
+		let (key, value) = entry("Fassade Nord", "Anna").to_stored();
+		assert_eq!(LibraryEntry::from_stored("not-a-uuid", &value), None);
+		assert_eq!(LibraryEntry::from_stored(&key, "{"), None);
+		let newer = value.replace("\"version\": 1", "\"version\": 99");
+		assert_ne!(newer, value);
+		assert_eq!(LibraryEntry::from_stored(&key, &newer), None);
+	}
+
+	#[test]
+	fn filters_by_name_and_author() {
+		let entry = entry("Fassade Nord", "Jörg Müller");
+		assert!(entry.matches(""));
+		assert!(entry.matches("fassade"));
+		assert!(entry.matches("nord"));
+		assert!(entry.matches("müller"));
+		assert!(!entry.matches("süd"));
+		// The query is expected in lowercase.
+		assert!(!entry.matches("Nord"));
+	}
+}
@@ -1,23 +1,17 @@
-#![feature(const_fn_floating_point_arithmetic)]
-
 use std::panic;
 
-use components::app::App;
+use goldbeck_pattern::{
+	components::{app::App, dialog::DialogProvider},
+	session,
+	theme::ThemeManager
+};
 use leptos::*;
-use theme::ThemeManager;
-
-mod components;
-
-mod tiling;
-
-mod export;
-
-mod theme;
-
-mod utils;
 
 fn main() {
-	panic::set_hook(Box::new(console_error_panic_hook::hook));
+	panic::set_hook(Box::new(|info| {
+		session::mark_crashed();
+		console_error_panic_hook::hook(info);
+	}));
 
-	mount_to_body(|| view! { <ThemeManager><App /></ThemeManager>})
+	mount_to_body(|| view! { <ThemeManager><DialogProvider><App /></DialogProvider></ThemeManager>})
 }
@@ -0,0 +1,226 @@
+use std::{error::Error, fmt};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::document::PatternData;
+
+pub const EXTENSION: &str = "gbpattern";
+
+/// The id of the `<metadata>` element that exported SVGs carry a pattern
+/// file in.
+pub const SVG_METADATA_ID: &str = "goldbeck-pattern";
+
+const CURRENT_VERSION: u64 = 1;
+
+/// Upgrades the JSON of a file from one version to the next;
+/// `MIGRATIONS[i]` turns a file of version `i + 1` into one of version
+/// `i + 2`. Whenever the format changes, `CURRENT_VERSION` is bumped and a
+/// migration is appended here, so that older files can always be opened.
+const MIGRATIONS: &[fn(&mut Value)] = &[];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternInfo {
+	pub name: String,
+	pub author: String,
+	/// Creation time as an ISO 8601 string.
+	pub created: String
+}
+
+/// The native document format, a JSON file that holds a pattern together
+/// with information about it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternFile {
+	pub version: u64,
+	#[serde(flatten)]
+	pub info: PatternInfo,
+	/// Time of the last save as an ISO 8601 string.
+	pub modified: String,
+	#[serde(flatten)]
+	pub pattern: PatternData
+}
+
+#[derive(Debug)]
+pub enum PatternFileError {
+	Json(serde_json::Error),
+	MissingVersion,
+	UnsupportedVersion(u64)
+}
+
+impl fmt::Display for PatternFileError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Json(err) => write!(f, "Invalid pattern file: {err}"),
+			Self::MissingVersion => write!(f, "Pattern file has no version"),
+			Self::UnsupportedVersion(version) => write!(
+				f,
+				"Pattern file version {version} is newer than the supported version \
+				 {CURRENT_VERSION}"
+			)
+		}
+	}
+}
+
+impl Error for PatternFileError {}
+
+impl From<serde_json::Error> for PatternFileError {
+	fn from(err: serde_json::Error) -> Self {
+		Self::Json(err)
+	}
+}
+
+impl PatternFile {
+	pub fn new(info: PatternInfo, modified: String, pattern: PatternData) -> Self {
+		Self {
+			version: CURRENT_VERSION,
+			info,
+			modified,
+			pattern
+		}
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("Failed to serialize pattern file")
+	}
+
+	/// Parses a pattern file of any supported version, migrating it to the
+	/// current one.
+	pub fn from_json(json: &str) -> Result<Self, PatternFileError> {
+		let mut value: Value = serde_json::from_str(json)?;
+		let version = value
+			.get("version")
+			.and_then(Value::as_u64)
+			.ok_or(PatternFileError::MissingVersion)?;
+		if version == 0 || version > CURRENT_VERSION {
+			return Err(PatternFileError::UnsupportedVersion(version));
+		}
+		for migrate in &MIGRATIONS[version as usize - 1..] {
+			migrate(&mut value);
+		}
+		value["version"] = Value::from(CURRENT_VERSION);
+		Ok(serde_json::from_value(value)?)
+	}
+
+	/// A file name without extension derived from the pattern's name.
+	pub fn file_stem(&self) -> String {
+		let stem: String = self
+			.info
+			.name
+			.chars()
+			.map(|ch| {
+				if ch.is_alphanumeric() || ch == '-' {
+					ch
+				} else {
+					'_'
+				}
+			})
+			.collect();
+		if stem.is_empty() {
+			"Muster".to_string()
+		} else {
+			stem
+		}
+	}
+
+	/// A file name derived from the pattern's name.
+	pub fn file_name(&self) -> String {
+		format!("{}.{EXTENSION}", self.file_stem())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		components::pattern::TileColor,
+		document::LayerData,
+		theme::Theme,
+		tiling::{Tiling, TilingFormat}
+	};
+
+	use super::*;
+
+	fn pattern_file() -> PatternFile {
+		let format = TilingFormat::F5X5;
+		let num_tiles = Tiling::load(format).num_tiles();
+		let mut locks = vec![false; num_tiles];
+		locks[3] = true;
+		locks[num_tiles - 1] = true;
+		PatternFile::new(
+			PatternInfo {
+				name: String::from("Fassade (Süd)"),
+				author: String::from("Anna"),
+				created: String::from("2023-09-01T12:00:00.000Z")
+			},
+			String::from("2023-09-02T08:30:00.000Z"),
+			PatternData {
+				format,
+				theme: Theme::Noble,
+				layers: vec![
+					LayerData {
+						name: String::from("Grund"),
+						visible: true,
+						locked: true,
+						colors: vec![TileColor::Primary; num_tiles]
+					},
+					LayerData {
+						name: String::from("Schrift"),
+						visible: false,
+						locked: false,
+						colors: (0..num_tiles)
+							.map(|i| [TileColor::None, TileColor::Secondary][i % 2])
+							.collect()
+					},
+				],
+				locks
+			}
+		)
+	}
+
+	#[test]
+	fn round_trips_layers_and_locks() {
+		let file = pattern_file();
+		let json = file.to_json();
+		assert_eq!(PatternFile::from_json(&json).unwrap(), file);
+	}
+
+	#[test]
+	fn rejects_newer_versions() {
+		let json = pattern_file().to_json();
+		let newer = json.replace(
+			"\"version\": 1",
+			&format!("\"version\": {}", CURRENT_VERSION + 1)
+		);
+		assert_ne!(newer, json);
+		assert!(matches!(
+			PatternFile::from_json(&newer),
+			Err(PatternFileError::UnsupportedVersion(version)) if version == CURRENT_VERSION + 1
+		));
+		let unversioned = json.replace("\"version\": 1,", "");
+		assert!(matches!(
+			PatternFile::from_json(&unversioned),
+			Err(PatternFileError::MissingVersion)
+		));
+	}
+
+	#[test]
+	fn rejects_malformed_json() {
+		let json = pattern_file().to_json();
+		assert!(matches!(
+			PatternFile::from_json(&json[..json.len() / 2]),
+			Err(PatternFileError::Json(_))
+		));
+		// Valid JSON that isn't a pattern file.
+		assert!(matches!(
+			PatternFile::from_json("{\"version\": 1, \"name\": 5}"),
+			Err(PatternFileError::Json(_))
+		));
+	}
+
+	#[test]
+	fn derives_file_names() {
+		let mut file = pattern_file();
+		assert_eq!(file.file_name(), "Fassade__Süd_.gbpattern");
+		file.info.name = String::new();
+		assert_eq!(file.file_stem(), "Muster");
+	}
+}
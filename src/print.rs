@@ -0,0 +1,770 @@
+use std::{error::Error, fmt};
+
+use enum_iterator::{all, Sequence};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, TextStr};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	artwork::{Artwork, SEAM_STROKE_WIDTH},
+	components::pattern::TileColor,
+	export::ExportSize,
+	theme::ThemeColor
+};
+
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+/// The smallest printed width of the exported repetitions in mm, which keeps
+/// the number of tiles on large pages in check.
+const MIN_PATTERN_WIDTH: f32 = 10.0;
+
+/// The gap between the bleed and the crop and registration marks, in mm.
+const MARK_GAP: f32 = 1.0;
+/// The length of the crop marks and the diameter of the registration marks.
+const MARK_LENGTH: f32 = 5.0;
+/// The line width of the marks, in points.
+const MARK_LINE_WIDTH: f32 = 0.25;
+
+/// How the theme colors are written to print PDFs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Sequence)]
+#[repr(u8)]
+pub enum ColorMode {
+	Rgb,
+	Cmyk,
+	Spot
+}
+
+impl ColorMode {
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Rgb => "RGB",
+			Self::Cmyk => "CMYK",
+			Self::Spot => "Sonderfarben"
+		}
+	}
+}
+
+impl Default for ColorMode {
+	fn default() -> Self {
+		Self::Rgb
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Sequence)]
+#[repr(u8)]
+pub enum PageSize {
+	/// The page is as large as the pattern.
+	Pattern,
+	A4,
+	A3,
+	A0,
+	Custom
+}
+
+impl PageSize {
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Pattern => "Mustergröße",
+			Self::A4 => "A4",
+			Self::A3 => "A3",
+			Self::A0 => "A0",
+			Self::Custom => "Eigenes Format"
+		}
+	}
+
+	/// Width and height in portrait orientation, in mm.
+	fn dimensions(self) -> Option<(f32, f32)> {
+		match self {
+			Self::A4 => Some((210.0, 297.0)),
+			Self::A3 => Some((297.0, 420.0)),
+			Self::A0 => Some((841.0, 1189.0)),
+			Self::Pattern | Self::Custom => None
+		}
+	}
+}
+
+/// Where the pattern is printed; its size is the export size. The pattern is
+/// centered on the page. On a page of the pattern's size, it is fitted into
+/// the export size like in the other formats and only continues into the
+/// bleed; larger pages are covered by repeating it. All lengths are in mm.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PageLayout {
+	pub page: PageSize,
+	/// The page size for [`PageSize::Custom`].
+	pub custom_size: (f32, f32),
+	pub landscape: bool,
+	/// How far the pattern continues past the trimmed page.
+	pub bleed: f32,
+	/// Whether crop and registration marks are drawn outside of the bleed.
+	pub marks: bool
+}
+
+impl PageLayout {
+	/// The trimmed page size for a pattern of the given size.
+	fn trim_size(&self, pattern: (f32, f32)) -> (f32, f32) {
+		let (width, height) = match self.page {
+			PageSize::Pattern => return pattern,
+			PageSize::Custom => self.custom_size,
+			page => page.dimensions().unwrap()
+		};
+		if self.landscape {
+			(width.max(height), width.min(height))
+		} else {
+			(width.min(height), width.max(height))
+		}
+	}
+
+	/// The space around the trimmed page, which holds the bleed and the marks.
+	fn margin(&self) -> f32 {
+		let bleed = self.bleed.max(0.0);
+		if self.marks {
+			bleed + MARK_GAP + MARK_LENGTH
+		} else {
+			bleed
+		}
+	}
+}
+
+impl Default for PageLayout {
+	fn default() -> Self {
+		Self {
+			page: PageSize::Pattern,
+			custom_size: (500.0, 700.0),
+			landscape: false,
+			bleed: 0.0,
+			marks: false
+		}
+	}
+}
+
+/// What a theme color is printed with. The CMYK values, from 0 to 1, are used
+/// in CMYK mode and as the fallback of the spot color, which printers without
+/// the named ink print instead. An empty spot name prints the color in CMYK
+/// in spot mode, too.
+///
+/// There are no approved print values for the theme colors, so the CMYK
+/// values are unset until the user enters the ones of their printer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ink {
+	pub cmyk: Option<[f32; 4]>,
+	pub spot: String
+}
+
+/// A CMYK or spot color PDF was requested before the CMYK values of these
+/// theme colors were set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingInks(pub Vec<ThemeColor>);
+
+impl fmt::Display for MissingInks {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let names: Vec<&str> = self.0.iter().map(|color| color.name()).collect();
+		write!(f, "no CMYK values set for {}", names.join(", "))
+	}
+}
+
+impl Error for MissingInks {}
+
+/// The settings of print PDFs, which are kept in local storage.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrintOptions {
+	pub mode: ColorMode,
+	/// The inks of the theme colors, in declaration order.
+	pub inks: [Ink; 5],
+	#[serde(default)]
+	pub layout: PageLayout
+}
+
+impl PrintOptions {
+	pub fn ink(&self, color: ThemeColor) -> &Ink {
+		&self.inks[color as usize]
+	}
+
+	pub fn ink_mut(&mut self, color: ThemeColor) -> &mut Ink {
+		&mut self.inks[color as usize]
+	}
+
+	/// The theme colors whose CMYK values the color mode needs but which are
+	/// not set yet.
+	pub fn missing_inks(&self) -> Vec<ThemeColor> {
+		if self.mode == ColorMode::Rgb {
+			return Vec::new();
+		}
+		all::<ThemeColor>()
+			.filter(|color| self.ink(*color).cmyk.is_none())
+			.collect()
+	}
+}
+
+pub const STORAGE_KEY: &str = "print-options";
+
+/// Draws every tile of the artwork in `color` as a separate filled and
+/// outlined path.
+pub fn draw_tiles(content: &mut Content, artwork: &Artwork, color: TileColor) {
+	for (_, vertices) in artwork.tiles().filter(|(c, _)| *c == color) {
+		let Some((first, rest)) = vertices.split_first() else {
+			continue;
+		};
+		content.move_to(first.x, first.y);
+		for vertex in rest {
+			content.line_to(vertex.x, vertex.y);
+		}
+		content.close_fill_nonzero_and_stroke();
+	}
+}
+
+/// Draws a circle as four Bézier curves.
+fn circle(content: &mut Content, x: f32, y: f32, radius: f32) {
+	// The distance of the control points that approximates a quarter circle.
+	let k = radius * 0.552_284_8;
+	content.move_to(x + radius, y);
+	content.cubic_to(x + radius, y + k, x + k, y + radius, x, y + radius);
+	content.cubic_to(x - k, y + radius, x - radius, y + k, x - radius, y);
+	content.cubic_to(x - radius, y - k, x - k, y - radius, x, y - radius);
+	content.cubic_to(x + k, y - radius, x + radius, y - k, x + radius, y);
+	content.close_path();
+}
+
+/// Draws crop marks at the corners of the trimmed page and registration marks
+/// at the middle of its sides, all outside of the bleed. `trim` is given as
+/// left, bottom, right and top in points.
+fn draw_marks(content: &mut Content, trim: [f32; 4], bleed: f32) {
+	let [left, bottom, right, top] = trim;
+	let start = (bleed + MARK_GAP) * POINTS_PER_MM;
+	let end = start + MARK_LENGTH * POINTS_PER_MM;
+	for (x, outwards) in [(left, -1.0), (right, 1.0)] {
+		for y in [bottom, top] {
+			content.move_to(x + outwards * start, y);
+			content.line_to(x + outwards * end, y);
+		}
+	}
+	for (y, outwards) in [(bottom, -1.0), (top, 1.0)] {
+		for x in [left, right] {
+			content.move_to(x, y + outwards * start);
+			content.line_to(x, y + outwards * end);
+		}
+	}
+
+	let radius = (end - start) / 2.0;
+	let center = start + radius;
+	let (middle_x, middle_y) = ((left + right) / 2.0, (bottom + top) / 2.0);
+	for (x, y) in [
+		(left - center, middle_y),
+		(right + center, middle_y),
+		(middle_x, bottom - center),
+		(middle_x, top + center)
+	] {
+		circle(content, x, y, radius * 0.6);
+		content.move_to(x - radius, y);
+		content.line_to(x + radius, y);
+		content.move_to(x, y - radius);
+		content.line_to(x, y + radius);
+	}
+	content.stroke();
+}
+
+/// Writes the artwork as a PDF for print, with the theme colors in the color
+/// mode and inks of `options` instead of the screen colors. The page is laid
+/// out as described by the options' [`PageLayout`]: the trimmed page is
+/// covered by the pattern at the export size, which continues into the bleed.
+///
+/// Fails if the color mode needs CMYK values that are not set.
+pub fn write_pdf(
+	artwork: &Artwork,
+	title: &str,
+	options: &PrintOptions,
+	size: &ExportSize
+) -> Result<Vec<u8>, MissingInks> {
+	let missing = options.missing_inks();
+	if !missing.is_empty() {
+		return Err(MissingInks(missing));
+	}
+	let layout = &options.layout;
+	let (pattern_width, pattern_height) = size.points(artwork.width() / artwork.height());
+	let (trim_width, trim_height) = layout.trim_size((
+		pattern_width / POINTS_PER_MM,
+		pattern_height / POINTS_PER_MM
+	));
+	let margin = layout.margin() * POINTS_PER_MM;
+	let bleed = layout.bleed.max(0.0) * POINTS_PER_MM;
+	let trim = Rect::new(
+		margin,
+		margin,
+		margin + trim_width * POINTS_PER_MM,
+		margin + trim_height * POINTS_PER_MM
+	);
+	let media = Rect::new(0.0, 0.0, trim.x2 + margin, trim.y2 + margin);
+	let bleed_box = Rect::new(
+		trim.x1 - bleed,
+		trim.y1 - bleed,
+		trim.x2 + bleed,
+		trim.y2 + bleed
+	);
+
+	// Points per tiling unit, and the trimmed page and the bleed in tiling
+	// units.
+	let scale = (pattern_width / artwork.width())
+		.min(pattern_height / artwork.height())
+		.max(MIN_PATTERN_WIDTH * POINTS_PER_MM / artwork.width());
+	let (page_width, page_height) = ((trim.x2 - trim.x1) / scale, (trim.y2 - trim.y1) / scale);
+	let bleed_units = bleed / scale;
+	// Repeat the tiling so that the exported repetitions stay centered and the
+	// bleed is covered.
+	let (rep_width, rep_height) = (
+		artwork.tiling.viewport_width(),
+		artwork.tiling.viewport_height()
+	);
+	let origin_x = (page_width - artwork.width()) / 2.0;
+	let origin_y = (page_height - artwork.height()) / 2.0;
+	let first_x = ((-bleed_units - origin_x) / rep_width).floor();
+	let first_y = ((-bleed_units - origin_y) / rep_height).floor();
+	let last_x = ((page_width + bleed_units - origin_x) / rep_width).ceil();
+	let last_y = ((page_height + bleed_units - origin_y) / rep_height).ceil();
+	let covering = Artwork {
+		reps_x: (last_x - first_x).max(1.0) as usize,
+		reps_y: (last_y - first_y).max(1.0) as usize,
+		..*artwork
+	};
+	let offset_x = origin_x + first_x * rep_width;
+	let offset_y = origin_y + first_y * rep_height;
+
+	let palette = artwork.theme.palette();
+	let colors = artwork.used_colors();
+
+	let mut next_id = 1;
+	let mut alloc = || {
+		let id = Ref::new(next_id);
+		next_id += 1;
+		id
+	};
+	let catalog_id = alloc();
+	let info_id = alloc();
+	let pages_id = alloc();
+	let page_id = alloc();
+	let content_id = alloc();
+	let registration_id = layout.marks.then(&mut alloc);
+
+	// Only spot colors need a color space of their own.
+	let spots: Vec<(ThemeColor, Ref)> = if options.mode == ColorMode::Spot {
+		let mut spots: Vec<ThemeColor> = colors
+			.iter()
+			.map(|color| palette.tile_theme_color(*color))
+			.filter(|color| !options.ink(*color).spot.trim().is_empty())
+			.collect();
+		spots.sort();
+		spots.dedup();
+		spots.into_iter().map(|color| (color, alloc())).collect()
+	} else {
+		Vec::new()
+	};
+
+	let mut pdf = Pdf::new();
+	pdf.catalog(catalog_id).pages(pages_id);
+	pdf.document_info(info_id).title(TextStr(title));
+	pdf.pages(pages_id).kids([page_id]).count(1);
+
+	let mut page = pdf.page(page_id);
+	page.parent(pages_id)
+		.media_box(media)
+		.bleed_box(bleed_box)
+		.trim_box(trim)
+		.contents(content_id);
+	let mut resources = page.resources();
+	let mut color_spaces = resources.color_spaces();
+	for (color, id) in &spots {
+		color_spaces.pair(Name(format!("CS{}", *color as u8).as_bytes()), *id);
+	}
+	if let Some(registration_id) = registration_id {
+		color_spaces.pair(Name(b"Registration"), registration_id);
+	}
+	color_spaces.finish();
+	resources.finish();
+	page.finish();
+
+	for (color, id) in &spots {
+		let ink = options.ink(*color);
+		let mut separation = pdf
+			.color_space(*id)
+			.separation(Name(ink.spot.trim().as_bytes()));
+		separation.alternate_color_space().device_cmyk();
+		separation
+			.tint_exponential()
+			.domain([0.0, 1.0])
+			.c0([0.0; 4])
+			.c1(ink.cmyk.unwrap_or_default())
+			.n(1.0);
+	}
+	if let Some(registration_id) = registration_id {
+		// The marks are printed on every separation.
+		let mut registration = pdf.color_space(registration_id).separation(Name(b"All"));
+		registration.alternate_color_space().device_cmyk();
+		registration
+			.tint_exponential()
+			.domain([0.0, 1.0])
+			.c0([0.0; 4])
+			.c1([1.0; 4])
+			.n(1.0);
+	}
+
+	// On a page of the pattern's size, the margins that fitting leaves stay
+	// empty, as in the other formats.
+	let clip = if layout.page == PageSize::Pattern {
+		let left = trim.x1 + origin_x * scale - bleed;
+		let top = trim.y2 - origin_y * scale + bleed;
+		Rect::new(
+			left.max(bleed_box.x1),
+			(top - artwork.height() * scale - 2.0 * bleed).max(bleed_box.y1),
+			(left + artwork.width() * scale + 2.0 * bleed).min(bleed_box.x2),
+			top.min(bleed_box.y2)
+		)
+	} else {
+		bleed_box
+	};
+
+	let mut content = Content::new();
+	content.save_state();
+	content.rect(clip.x1, clip.y1, clip.x2 - clip.x1, clip.y2 - clip.y1);
+	content.clip_nonzero().end_path();
+	// Flip the y axis, which points downwards in the artwork.
+	content.transform([
+		scale,
+		0.0,
+		0.0,
+		-scale,
+		trim.x1 + offset_x * scale,
+		trim.y2 - offset_y * scale
+	]);
+	content.set_line_width(SEAM_STROKE_WIDTH);
+	for color in colors {
+		let theme_color = palette.tile_theme_color(color);
+		let ink = options.ink(theme_color);
+		match options.mode {
+			ColorMode::Rgb => {
+				let rgb = theme_color.rgb();
+				let [r, g, b] = [rgb.0, rgb.1, rgb.2].map(|c| c as f32 / 255.0);
+				content.set_fill_rgb(r, g, b).set_stroke_rgb(r, g, b);
+			}
+			ColorMode::Spot if spots.iter().any(|(c, _)| *c == theme_color) => {
+				let name = format!("CS{}", theme_color as u8);
+				content
+					.set_fill_color_space(Name(name.as_bytes()))
+					.set_fill_color([1.0])
+					.set_stroke_color_space(Name(name.as_bytes()))
+					.set_stroke_color([1.0]);
+			}
+			ColorMode::Cmyk | ColorMode::Spot => {
+				let [c, m, y, k] = ink.cmyk.unwrap_or_default();
+				content
+					.set_fill_cmyk(c, m, y, k)
+					.set_stroke_cmyk(c, m, y, k);
+			}
+		}
+		draw_tiles(&mut content, &covering, color);
+	}
+	content.restore_state();
+
+	if registration_id.is_some() {
+		content
+			.set_stroke_color_space(Name(b"Registration"))
+			.set_stroke_color([1.0])
+			.set_line_width(MARK_LINE_WIDTH);
+		draw_marks(
+			&mut content,
+			[trim.x1, trim.y1, trim.x2, trim.y2],
+			layout.bleed.max(0.0)
+		);
+	}
+	pdf.stream(content_id, &content.finish());
+
+	Ok(pdf.finish())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		export::{fit_artwork, LengthUnit},
+		theme::Theme,
+		tiling::{Tiling, TilingFormat}
+	};
+
+	use super::*;
+
+	fn mm(width: f32, height: Option<f32>) -> ExportSize {
+		ExportSize {
+			unit: LengthUnit::Mm,
+			width: Some(width),
+			height,
+			dpi: 300
+		}
+	}
+
+	fn numbers(text: &str) -> Vec<f32> {
+		text.split_whitespace()
+			.map(|number| number.parse().unwrap())
+			.collect()
+	}
+
+	/// The rectangle stored under `key` in the page dictionary.
+	fn page_box(pdf: &str, key: &str) -> Vec<f32> {
+		let start = pdf.find(&format!("/{key} [")).unwrap() + key.len() + 3;
+		let end = start + pdf[start..].find(']').unwrap();
+		numbers(&pdf[start..end])
+	}
+
+	/// The operands of every `operator` in the content stream, in order.
+	fn operands(pdf: &str, operator: &str) -> Vec<Vec<f32>> {
+		let suffix = format!(" {operator}");
+		pdf.lines()
+			.filter_map(|line| line.strip_suffix(&suffix))
+			.filter_map(|line| line.split_whitespace().map(|n| n.parse().ok()).collect())
+			.collect()
+	}
+
+	fn assert_rect(actual: &[f32], expected: [f32; 4]) {
+		assert_eq!(actual.len(), 4, "{actual:?}");
+		for (a, e) in actual.iter().zip(expected) {
+			assert!((a - e).abs() < 0.01, "{actual:?} != {expected:?}");
+		}
+	}
+
+	fn write(layout: PageLayout, size: &ExportSize) -> String {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let colors = vec![TileColor::Secondary; tiling.num_tiles()];
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme: Theme::Glisten,
+			reps_x: 2,
+			reps_y: 1
+		};
+		let options = PrintOptions {
+			layout,
+			..PrintOptions::default()
+		};
+		let pdf = write_pdf(&artwork, "Muster", &options, size).unwrap();
+		String::from_utf8_lossy(&pdf).into_owned()
+	}
+
+	#[test]
+	fn defaults_to_screen_colors_without_bleed() {
+		let options = PrintOptions::default();
+		assert_eq!(options.mode, ColorMode::Rgb);
+		assert_eq!(options.layout.bleed, 0.0);
+		assert!(!options.layout.marks);
+	}
+
+	#[test]
+	fn leaves_inks_unset() {
+		let options = PrintOptions::default();
+		assert!(options
+			.inks
+			.iter()
+			.all(|ink| ink.cmyk.is_none() && ink.spot.is_empty()));
+		// Screen colors need no inks.
+		assert!(options.missing_inks().is_empty());
+		for mode in [ColorMode::Cmyk, ColorMode::Spot] {
+			let options = PrintOptions {
+				mode,
+				..PrintOptions::default()
+			};
+			assert_eq!(
+				options.missing_inks(),
+				all::<ThemeColor>().collect::<Vec<_>>()
+			);
+		}
+	}
+
+	#[test]
+	fn requires_inks_for_print_colors() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let colors = vec![TileColor::Primary; tiling.num_tiles()];
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme: Theme::Noble,
+			reps_x: 1,
+			reps_y: 1
+		};
+		let size = ExportSize::default();
+		let mut options = PrintOptions {
+			mode: ColorMode::Cmyk,
+			..PrintOptions::default()
+		};
+		options.ink_mut(ThemeColor::Gold).cmyk = Some([0.0, 0.2, 0.6, 0.3]);
+		let err = write_pdf(&artwork, "Muster", &options, &size).unwrap_err();
+		assert!(!err.0.contains(&ThemeColor::Gold));
+		assert_eq!(err.0.len(), 4);
+
+		for color in all::<ThemeColor>() {
+			options
+				.ink_mut(color)
+				.cmyk
+				.get_or_insert([0.0, 0.0, 0.0, 1.0]);
+		}
+		assert!(write_pdf(&artwork, "Muster", &options, &size).is_ok());
+	}
+
+	#[test]
+	fn lays_out_a4_pages_with_bleed_and_marks() {
+		let layout = PageLayout {
+			page: PageSize::A4,
+			bleed: 3.0,
+			marks: true,
+			..PageLayout::default()
+		};
+		let pt = POINTS_PER_MM;
+		let margin = 3.0 + MARK_GAP + MARK_LENGTH;
+		let pdf = write(layout, &mm(100.0, None));
+		let (right, top) = ((margin + 210.0) * pt, (margin + 297.0) * pt);
+		assert_rect(
+			&page_box(&pdf, "TrimBox"),
+			[margin * pt, margin * pt, right, top]
+		);
+		assert_rect(
+			&page_box(&pdf, "BleedBox"),
+			[
+				(margin - 3.0) * pt,
+				(margin - 3.0) * pt,
+				right + 3.0 * pt,
+				top + 3.0 * pt
+			]
+		);
+		assert_rect(
+			&page_box(&pdf, "MediaBox"),
+			[0.0, 0.0, right + margin * pt, top + margin * pt]
+		);
+		// The pattern covers the page and the bleed.
+		assert_rect(
+			&operands(&pdf, "re")[0],
+			[
+				(margin - 3.0) * pt,
+				(margin - 3.0) * pt,
+				216.0 * pt,
+				303.0 * pt
+			]
+		);
+
+		let landscape = write(
+			PageLayout {
+				landscape: true,
+				..layout
+			},
+			&mm(100.0, None)
+		);
+		let (right, top) = ((margin + 297.0) * pt, (margin + 210.0) * pt);
+		assert_rect(
+			&page_box(&landscape, "TrimBox"),
+			[margin * pt, margin * pt, right, top]
+		);
+	}
+
+	#[test]
+	fn lays_out_custom_pages() {
+		let layout = PageLayout {
+			page: PageSize::Custom,
+			custom_size: (700.0, 500.0),
+			..PageLayout::default()
+		};
+		let pt = POINTS_PER_MM;
+		let pdf = write(layout, &mm(100.0, None));
+		// Without bleed and marks, all boxes are the trimmed page.
+		for key in ["MediaBox", "BleedBox", "TrimBox"] {
+			assert_rect(&page_box(&pdf, key), [0.0, 0.0, 500.0 * pt, 700.0 * pt]);
+		}
+		assert!(!pdf.contains("/Registration"));
+
+		let landscape = write(
+			PageLayout {
+				landscape: true,
+				..layout
+			},
+			&mm(100.0, None)
+		);
+		assert_rect(
+			&page_box(&landscape, "TrimBox"),
+			[0.0, 0.0, 700.0 * pt, 500.0 * pt]
+		);
+	}
+
+	#[test]
+	fn draws_marks_outside_of_the_bleed() {
+		let layout = PageLayout {
+			page: PageSize::A4,
+			bleed: 3.0,
+			marks: true,
+			..PageLayout::default()
+		};
+		let pdf = write(layout, &mm(100.0, None));
+		let bleed = page_box(&pdf, "BleedBox");
+		let marks = &pdf[pdf.rfind("/Registration CS").unwrap()..];
+
+		// Four crop marks at each of the two sides of the corners, and the
+		// crosses of the four registration marks.
+		let lines = operands(marks, "l");
+		assert_eq!(lines.len(), 8 + 4 * 2);
+		// The circles of the registration marks.
+		assert_eq!(operands(marks, "c").len(), 4 * 4);
+		for point in operands(marks, "m").into_iter().chain(lines).chain(
+			operands(marks, "c")
+				.into_iter()
+				.map(|curve| curve[4..].to_vec())
+		) {
+			let (x, y) = (point[0], point[1]);
+			assert!(
+				x < bleed[0] || x > bleed[2] || y < bleed[1] || y > bleed[3],
+				"({x}, {y}) is inside of the bleed {bleed:?}"
+			);
+		}
+	}
+
+	#[test]
+	fn keeps_the_minimum_pattern_width() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let artwork_width = tiling.viewport_width() * 2.0;
+		let layout = PageLayout {
+			page: PageSize::A4,
+			..PageLayout::default()
+		};
+
+		let pdf = write(layout, &mm(1.0, None));
+		let scale = operands(&pdf, "cm")[0][0];
+		let expected = MIN_PATTERN_WIDTH * POINTS_PER_MM / artwork_width;
+		assert!((scale - expected).abs() < 1e-4, "{scale} != {expected}");
+
+		let pdf = write(layout, &mm(50.0, None));
+		let scale = operands(&pdf, "cm")[0][0];
+		let expected = 50.0 * POINTS_PER_MM / artwork_width;
+		assert!((scale - expected).abs() < 1e-4, "{scale} != {expected}");
+	}
+
+	#[test]
+	fn centers_the_pattern_like_other_formats() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let colors = vec![TileColor::Secondary; tiling.num_tiles()];
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme: Theme::Glisten,
+			reps_x: 2,
+			reps_y: 1
+		};
+		// Much wider than the artwork, so that the sides stay empty.
+		let size = mm(400.0, Some(50.0));
+		let (width, height) = size.points(artwork.width() / artwork.height());
+		let (scale, offset_x, offset_y) = fit_artwork(&artwork, width, height);
+
+		let pdf = write(PageLayout::default(), &size);
+		assert_rect(&page_box(&pdf, "TrimBox"), [0.0, 0.0, width, height]);
+		let transform = &operands(&pdf, "cm")[0];
+		assert!((transform[0] - scale).abs() < 1e-4);
+		let clip = &operands(&pdf, "re")[0];
+		assert_rect(
+			clip,
+			[
+				offset_x,
+				offset_y,
+				artwork.width() * scale,
+				artwork.height() * scale
+			]
+		);
+		assert!(offset_x > 0.0);
+	}
+}
@@ -0,0 +1,907 @@
+//! A QR code encoder for byte data, following ISO/IEC 18004.
+
+use enum_iterator::Sequence;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+#[repr(u8)]
+pub enum ErrorCorrection {
+	Low,
+	Medium,
+	Quartile,
+	High
+}
+
+impl ErrorCorrection {
+	fn ordinal(self) -> usize {
+		self as usize
+	}
+
+	/// The share of the code that may be damaged while it stays readable.
+	pub fn recovery(self) -> &'static str {
+		match self {
+			Self::Low => "7 %",
+			Self::Medium => "15 %",
+			Self::Quartile => "25 %",
+			Self::High => "30 %"
+		}
+	}
+
+	/// The two bits that identify the level in the format information.
+	fn format_bits(self) -> u32 {
+		match self {
+			Self::Low => 1,
+			Self::Medium => 0,
+			Self::Quartile => 3,
+			Self::High => 2
+		}
+	}
+}
+
+const MIN_VERSION: usize = 1;
+const MAX_VERSION: usize = 40;
+
+/// Error correction codewords per block, by level and version.
+const ECC_CODEWORDS_PER_BLOCK: [[u8; MAX_VERSION + 1]; 4] = [
+	[
+		0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28,
+		30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30
+	],
+	[
+		0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28,
+		28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28
+	],
+	[
+		0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30,
+		30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30
+	],
+	[
+		0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24,
+		30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30
+	]
+];
+
+/// Error correction blocks, by level and version.
+const NUM_ERROR_CORRECTION_BLOCKS: [[u8; MAX_VERSION + 1]; 4] = [
+	[
+		0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13,
+		14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25
+	],
+	[
+		0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21,
+		23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49
+	],
+	[
+		0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29,
+		34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68
+	],
+	[
+		0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32,
+		35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81
+	]
+];
+
+const BYTE_MODE: u32 = 0b0100;
+
+const PENALTY_RUN: i32 = 3;
+const PENALTY_BLOCK: i32 = 3;
+const PENALTY_FINDER_LIKE: i32 = 40;
+const PENALTY_BALANCE: i32 = 10;
+
+/// Modules that can hold data, minus those of function patterns.
+fn num_raw_data_modules(version: usize) -> usize {
+	let mut result = (16 * version + 128) * version + 64;
+	if version >= 2 {
+		let num_align = version / 7 + 2;
+		result -= (25 * num_align - 10) * num_align - 55;
+		if version >= 7 {
+			result -= 36;
+		}
+	}
+	result
+}
+
+fn num_data_codewords(version: usize, ecl: ErrorCorrection) -> usize {
+	let level = ecl.ordinal();
+	num_raw_data_modules(version) / 8
+		- ECC_CODEWORDS_PER_BLOCK[level][version] as usize
+			* NUM_ERROR_CORRECTION_BLOCKS[level][version] as usize
+}
+
+fn char_count_bits(version: usize) -> usize {
+	if version <= 9 {
+		8
+	} else {
+		16
+	}
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x^2 + 1.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+	let mut z: u32 = 0;
+	for i in (0..8).rev() {
+		z = (z << 1) ^ ((z >> 7) * 0x11d);
+		z ^= ((y as u32 >> i) & 1) * x as u32;
+	}
+	z as u8
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+	let mut result = vec![0; degree];
+	result[degree - 1] = 1;
+	let mut root = 1;
+	for _ in 0..degree {
+		for j in 0..degree {
+			result[j] = gf_multiply(result[j], root);
+			if j + 1 < degree {
+				result[j] ^= result[j + 1];
+			}
+		}
+		root = gf_multiply(root, 0x02);
+	}
+	result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+	let mut result = vec![0; divisor.len()];
+	for &byte in data {
+		let factor = byte ^ result.remove(0);
+		result.push(0);
+		for (x, &y) in result.iter_mut().zip(divisor) {
+			*x ^= gf_multiply(y, factor);
+		}
+	}
+	result
+}
+
+struct BitBuffer(Vec<bool>);
+
+impl BitBuffer {
+	fn append(&mut self, value: u32, len: usize) {
+		self.0.extend((0..len).rev().map(|i| (value >> i) & 1 != 0));
+	}
+}
+
+fn bit(value: u32, i: usize) -> bool {
+	(value >> i) & 1 != 0
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrCode {
+	size: usize,
+	modules: Vec<bool>,
+	is_function: Vec<bool>
+}
+
+impl QrCode {
+	/// Encodes the data in byte mode with the smallest version that fits, or
+	/// returns `None` if the data is too long for any version.
+	pub fn encode(data: &[u8], ecl: ErrorCorrection) -> Option<Self> {
+		let version = (MIN_VERSION..=MAX_VERSION).find(|&version| {
+			4 + char_count_bits(version) + 8 * data.len() <= num_data_codewords(version, ecl) * 8
+		})?;
+
+		let mut bits = BitBuffer(Vec::new());
+		bits.append(BYTE_MODE, 4);
+		bits.append(data.len() as u32, char_count_bits(version));
+		for &byte in data {
+			bits.append(byte as u32, 8);
+		}
+		let capacity = num_data_codewords(version, ecl) * 8;
+		let terminator = usize::min(4, capacity - bits.0.len());
+		bits.append(0, terminator);
+		let padding = (8 - bits.0.len() % 8) % 8;
+		bits.append(0, padding);
+		for &pad in [0xec, 0x11].iter().cycle() {
+			if bits.0.len() >= capacity {
+				break;
+			}
+			bits.append(pad, 8);
+		}
+
+		let codewords: Vec<u8> = bits
+			.0
+			.chunks(8)
+			.map(|byte| byte.iter().fold(0, |acc, &b| acc << 1 | b as u8))
+			.collect();
+
+		let mut qr = Self {
+			size: version * 4 + 17,
+			modules: vec![false; (version * 4 + 17).pow(2)],
+			is_function: vec![false; (version * 4 + 17).pow(2)]
+		};
+		qr.draw_function_patterns(version, ecl);
+		let all_codewords = Self::add_error_correction(&codewords, version, ecl);
+		qr.draw_codewords(&all_codewords);
+
+		let mask = (0..8)
+			.min_by_key(|&mask| {
+				qr.apply_mask(mask);
+				qr.draw_format_bits(ecl, mask);
+				let penalty = qr.penalty_score();
+				qr.apply_mask(mask);
+				penalty
+			})
+			.expect("There are always eight masks");
+		qr.apply_mask(mask);
+		qr.draw_format_bits(ecl, mask);
+		Some(qr)
+	}
+
+	/// The number of modules per side.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	/// Whether the module at column `x` and row `y` is dark.
+	pub fn get(&self, x: usize, y: usize) -> bool {
+		self.modules[y * self.size + x]
+	}
+
+	fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+		self.modules[y * self.size + x] = dark;
+		self.is_function[y * self.size + x] = true;
+	}
+
+	fn alignment_pattern_positions(&self, version: usize) -> Vec<usize> {
+		if version == 1 {
+			return Vec::new();
+		}
+		let num_align = version / 7 + 2;
+		let step = if version == 32 {
+			26
+		} else {
+			(version * 4 + num_align * 2 + 1) / (num_align * 2 - 2) * 2
+		};
+		let mut result: Vec<usize> = (0..num_align - 1)
+			.map(|i| self.size - 7 - i * step)
+			.collect();
+		result.push(6);
+		result.reverse();
+		result
+	}
+
+	fn draw_function_patterns(&mut self, version: usize, ecl: ErrorCorrection) {
+		for i in 0..self.size {
+			self.set_function(6, i, i % 2 == 0);
+			self.set_function(i, 6, i % 2 == 0);
+		}
+
+		let far = self.size - 4;
+		for (x, y) in [(3, 3), (far, 3), (3, far)] {
+			self.draw_finder_pattern(x, y);
+		}
+
+		let positions = self.alignment_pattern_positions(version);
+		let n = positions.len();
+		for i in 0..n {
+			for j in 0..n {
+				let overlaps_finder = (i == 0 && (j == 0 || j == n - 1)) || (i == n - 1 && j == 0);
+				if !overlaps_finder {
+					self.draw_alignment_pattern(positions[i], positions[j]);
+				}
+			}
+		}
+
+		// Reserves the format areas, they are redrawn once the mask is known.
+		self.draw_format_bits(ecl, 0);
+		self.draw_version(version);
+	}
+
+	fn draw_finder_pattern(&mut self, x: usize, y: usize) {
+		for dy in -4..=4_isize {
+			for dx in -4..=4_isize {
+				let distance = dx.abs().max(dy.abs());
+				let (Some(xx), Some(yy)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+				else {
+					continue;
+				};
+				if xx < self.size && yy < self.size {
+					self.set_function(xx, yy, distance != 2 && distance != 4);
+				}
+			}
+		}
+	}
+
+	fn draw_alignment_pattern(&mut self, x: usize, y: usize) {
+		for dy in -2..=2_isize {
+			for dx in -2..=2_isize {
+				self.set_function(
+					x.wrapping_add_signed(dx),
+					y.wrapping_add_signed(dy),
+					dx.abs().max(dy.abs()) != 1
+				);
+			}
+		}
+	}
+
+	fn draw_format_bits(&mut self, ecl: ErrorCorrection, mask: u8) {
+		let data = ecl.format_bits() << 3 | mask as u32;
+		let mut remainder = data;
+		for _ in 0..10 {
+			remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+		}
+		let bits = (data << 10 | remainder) ^ 0x5412;
+
+		// First copy, around the top left finder pattern.
+		for i in 0..=5 {
+			self.set_function(8, i, bit(bits, i));
+		}
+		self.set_function(8, 7, bit(bits, 6));
+		self.set_function(8, 8, bit(bits, 7));
+		self.set_function(7, 8, bit(bits, 8));
+		for i in 9..15 {
+			self.set_function(14 - i, 8, bit(bits, i));
+		}
+
+		// Second copy, split between the other two finder patterns.
+		for i in 0..8 {
+			self.set_function(self.size - 1 - i, 8, bit(bits, i));
+		}
+		for i in 8..15 {
+			self.set_function(8, self.size - 15 + i, bit(bits, i));
+		}
+		self.set_function(8, self.size - 8, true);
+	}
+
+	fn draw_version(&mut self, version: usize) {
+		if version < 7 {
+			return;
+		}
+		let mut remainder = version as u32;
+		for _ in 0..12 {
+			remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1f25);
+		}
+		let bits = (version as u32) << 12 | remainder;
+		for i in 0..18 {
+			let a = self.size - 11 + i % 3;
+			let b = i / 3;
+			self.set_function(a, b, bit(bits, i));
+			self.set_function(b, a, bit(bits, i));
+		}
+	}
+
+	/// Splits the data into blocks, appends the error correction to each of
+	/// them and interleaves the result.
+	fn add_error_correction(data: &[u8], version: usize, ecl: ErrorCorrection) -> Vec<u8> {
+		let level = ecl.ordinal();
+		let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[level][version] as usize;
+		let block_ecc_len = ECC_CODEWORDS_PER_BLOCK[level][version] as usize;
+		let raw_codewords = num_raw_data_modules(version) / 8;
+		let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+		let short_block_len = raw_codewords / num_blocks;
+
+		let divisor = reed_solomon_divisor(block_ecc_len);
+		let mut blocks: Vec<Vec<u8>> = Vec::with_capacity(num_blocks);
+		let mut k = 0;
+		for i in 0..num_blocks {
+			let data_len = short_block_len - block_ecc_len + usize::from(i >= num_short_blocks);
+			let mut block = data[k..k + data_len].to_vec();
+			k += data_len;
+			let ecc = reed_solomon_remainder(&block, &divisor);
+			if i < num_short_blocks {
+				// Placeholder, so that all blocks have the same length.
+				block.push(0);
+			}
+			block.extend(ecc);
+			blocks.push(block);
+		}
+
+		let mut result = Vec::with_capacity(raw_codewords);
+		for i in 0..blocks[0].len() {
+			for (j, block) in blocks.iter().enumerate() {
+				if i != short_block_len - block_ecc_len || j >= num_short_blocks {
+					result.push(block[i]);
+				}
+			}
+		}
+		result
+	}
+
+	/// Places the codewords in the zigzag pattern of two module wide columns,
+	/// from the bottom right corner.
+	fn draw_codewords(&mut self, data: &[u8]) {
+		let mut i = 0;
+		let mut right = self.size - 1;
+		while right >= 1 {
+			if right == 6 {
+				right = 5;
+			}
+			for vertical in 0..self.size {
+				for j in 0..2 {
+					let x = right - j;
+					let upward = (right + 1) & 2 == 0;
+					let y = if upward {
+						self.size - 1 - vertical
+					} else {
+						vertical
+					};
+					if !self.is_function[y * self.size + x] && i < data.len() * 8 {
+						self.modules[y * self.size + x] = bit(data[i >> 3] as u32, 7 - (i & 7));
+						i += 1;
+					}
+				}
+			}
+			if right < 2 {
+				break;
+			}
+			right -= 2;
+		}
+	}
+
+	/// Inverts the data modules selected by the mask; applying a mask twice
+	/// undoes it.
+	fn apply_mask(&mut self, mask: u8) {
+		for y in 0..self.size {
+			for x in 0..self.size {
+				let invert = match mask {
+					0 => (x + y) % 2 == 0,
+					1 => y % 2 == 0,
+					2 => x % 3 == 0,
+					3 => (x + y) % 3 == 0,
+					4 => (x / 3 + y / 2) % 2 == 0,
+					5 => x * y % 2 + x * y % 3 == 0,
+					6 => (x * y % 2 + x * y % 3) % 2 == 0,
+					_ => ((x + y) % 2 + x * y % 3) % 2 == 0
+				};
+				let i = y * self.size + x;
+				self.modules[i] ^= invert && !self.is_function[i];
+			}
+		}
+	}
+
+	fn line_penalty(line: &[bool]) -> i32 {
+		let mut penalty = 0;
+		let mut run = 1;
+		for i in 1..=line.len() {
+			if i < line.len() && line[i] == line[i - 1] {
+				run += 1;
+				continue;
+			}
+			if run >= 5 {
+				penalty += PENALTY_RUN + (run - 5);
+			}
+			run = 1;
+		}
+
+		// Patterns that look like a finder pattern, with light space on
+		// either side; the area outside of the symbol counts as light.
+		const FINDER: [bool; 7] = [true, false, true, true, true, false, true];
+		let is_light = |i: isize| i < 0 || i >= line.len() as isize || !line[i as usize];
+		for start in 0..=line.len() as isize - 7 {
+			let matches = FINDER
+				.iter()
+				.enumerate()
+				.all(|(k, &dark)| line[start as usize + k] == dark);
+			if !matches {
+				continue;
+			}
+			let light_before = (start - 4..start).all(is_light);
+			let light_after = (start + 7..start + 11).all(is_light);
+			if light_before || light_after {
+				penalty += PENALTY_FINDER_LIKE;
+			}
+		}
+		penalty
+	}
+
+	fn penalty_score(&self) -> i32 {
+		let size = self.size;
+		let mut penalty = 0;
+		for y in 0..size {
+			penalty += Self::line_penalty(&self.modules[y * size..(y + 1) * size]);
+		}
+		for x in 0..size {
+			let column: Vec<bool> = (0..size).map(|y| self.get(x, y)).collect();
+			penalty += Self::line_penalty(&column);
+		}
+
+		for y in 0..size - 1 {
+			for x in 0..size - 1 {
+				let color = self.get(x, y);
+				if color == self.get(x + 1, y)
+					&& color == self.get(x, y + 1)
+					&& color == self.get(x + 1, y + 1)
+				{
+					penalty += PENALTY_BLOCK;
+				}
+			}
+		}
+
+		let total = (size * size) as i32;
+		let dark = self.modules.iter().filter(|&&dark| dark).count() as i32;
+		let k = ((dark * 20 - total * 10).abs() + total - 1) / total - 1;
+		penalty + k * PENALTY_BALANCE
+	}
+
+	/// An SVG path of the dark modules, offset by a quiet zone of `border`
+	/// modules.
+	pub fn svg_path(&self, border: usize) -> String {
+		let mut path = String::new();
+		for y in 0..self.size {
+			for x in 0..self.size {
+				if self.get(x, y) {
+					path += &format!("M{},{}h1v1h-1z", x + border, y + border);
+				}
+			}
+		}
+		path
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use enum_iterator::all;
+
+	use super::*;
+
+	/// The format information after masking, by level and mask, from table C.1
+	/// of ISO/IEC 18004.
+	const FORMAT_INFO: [[u32; 8]; 4] = [
+		[
+			0b111011111000100,
+			0b111001011110011,
+			0b111110110101010,
+			0b111100010011101,
+			0b110011000101111,
+			0b110001100011000,
+			0b110110001000001,
+			0b110100101110110
+		],
+		[
+			0b101010000010010,
+			0b101000100100101,
+			0b101111001111100,
+			0b101101101001011,
+			0b100010111111001,
+			0b100000011001110,
+			0b100111110010111,
+			0b100101010100000
+		],
+		[
+			0b011010101011111,
+			0b011000001101000,
+			0b011111100110001,
+			0b011101000000110,
+			0b010010010110100,
+			0b010000110000011,
+			0b010111011011010,
+			0b010101111101101
+		],
+		[
+			0b001011010001001,
+			0b001001110111110,
+			0b001110011100111,
+			0b001100111010000,
+			0b000011101100010,
+			0b000001001010101,
+			0b000110100001100,
+			0b000100000111011
+		]
+	];
+
+	/// The version information of versions 7 to 40, from table D.1 of
+	/// ISO/IEC 18004.
+	const VERSION_INFO: [u32; 34] = [
+		0x07c94, 0x085bc, 0x09a99, 0x0a4d3, 0x0bbf6, 0x0c762, 0x0d847, 0x0e60d, 0x0f928, 0x10b78,
+		0x1145d, 0x12a17, 0x13532, 0x149a6, 0x15683, 0x168c9, 0x177ec, 0x18ec4, 0x191e1, 0x1afab,
+		0x1b08e, 0x1cc1a, 0x1d33f, 0x1ed75, 0x1f250, 0x209d5, 0x216f0, 0x228ba, 0x2379f, 0x24b0b,
+		0x2542e, 0x26a64, 0x27541, 0x28c69
+	];
+
+	/// The most bytes per level, from table 7 of ISO/IEC 18004.
+	const BYTE_CAPACITY: [(usize, [usize; 4]); 4] = [
+		(1, [17, 14, 11, 7]),
+		(7, [154, 122, 86, 64]),
+		(10, [271, 213, 151, 119]),
+		(40, [2953, 2331, 1663, 1273])
+	];
+
+	/// Blocks, error correction codewords per block and data codewords.
+	type Blocks = (usize, usize, usize);
+
+	/// The blocks per level, from table 9 of ISO/IEC 18004.
+	const BLOCKS: [(usize, [Blocks; 4]); 3] = [
+		(1, [(1, 7, 19), (1, 10, 16), (1, 13, 13), (1, 17, 9)]),
+		(7, [(2, 20, 156), (4, 18, 124), (6, 18, 88), (5, 26, 66)]),
+		(
+			40,
+			[
+				(25, 30, 2956),
+				(49, 28, 2334),
+				(68, 30, 1666),
+				(81, 30, 1276)
+			]
+		)
+	];
+
+	/// Codewords of versions 1 to 40, from table 1 of ISO/IEC 18004.
+	const TOTAL_CODEWORDS: [usize; 40] = [
+		26, 44, 70, 100, 134, 172, 196, 242, 292, 346, 404, 466, 532, 581, 655, 733, 815, 901, 991,
+		1085, 1156, 1258, 1364, 1474, 1588, 1706, 1828, 1921, 2051, 2185, 2323, 2465, 2611, 2761,
+		2876, 3034, 3196, 3362, 3532, 3706
+	];
+
+	/// Codewords and remainder bits of a symbol, from table 1 of ISO/IEC
+	/// 18004.
+	fn total_codewords(version: usize) -> (usize, usize) {
+		let remainder = match version {
+			2..=6 => 7,
+			14..=20 | 28..=34 => 3,
+			21..=27 => 4,
+			_ => 0
+		};
+		(TOTAL_CODEWORDS[version - 1], remainder)
+	}
+
+	fn test_data(len: usize) -> Vec<u8> {
+		(0..len).map(|i| (i * 37 + 11) as u8).collect()
+	}
+
+	fn read_bits(qr: &QrCode, positions: impl Iterator<Item = (usize, usize)>) -> u32 {
+		positions.fold(0, |acc, (x, y)| acc << 1 | qr.get(x, y) as u32)
+	}
+
+	/// Multiplication in GF(2^8), written independently of the encoder.
+	fn multiply(mut x: u8, mut y: u8) -> u8 {
+		let mut product = 0;
+		while y != 0 {
+			if y & 1 != 0 {
+				product ^= x;
+			}
+			let carry = x & 0x80 != 0;
+			x <<= 1;
+			if carry {
+				x ^= 0x1d;
+			}
+			y >>= 1;
+		}
+		product
+	}
+
+	/// Evaluates a codeword polynomial, highest degree first, at `x`.
+	fn evaluate(codewords: &[u8], x: u8) -> u8 {
+		codewords
+			.iter()
+			.fold(0, |acc, &codeword| multiply(acc, x) ^ codeword)
+	}
+
+	/// Reads the symbol as a decoder would and returns the data it holds,
+	/// checking the function patterns, the format and version information and
+	/// the error correction along the way.
+	fn decode(qr: &QrCode, ecl: ErrorCorrection) -> Vec<u8> {
+		let size = qr.size();
+		let version = (size - 17) / 4;
+		assert_eq!(size, version * 4 + 17);
+
+		// Finder patterns with their separators.
+		for (left, top) in [(0, 0), (size - 7, 0), (0, size - 7)] {
+			for dy in -1..=7_isize {
+				for dx in -1..=7_isize {
+					let (Some(x), Some(y)) =
+						(left.checked_add_signed(dx), top.checked_add_signed(dy))
+					else {
+						continue;
+					};
+					if x >= size || y >= size {
+						continue;
+					}
+					let ring = dx.min(dy).min(6 - dx).min(6 - dy);
+					assert_eq!(qr.get(x, y), ring == 0 || ring >= 2, "finder at {x}, {y}");
+				}
+			}
+		}
+		// Timing patterns.
+		for i in 8..size - 8 {
+			assert_eq!(qr.get(i, 6), i % 2 == 0);
+			assert_eq!(qr.get(6, i), i % 2 == 0);
+		}
+		assert!(qr.get(8, size - 8), "dark module");
+
+		let format = read_bits(
+			qr,
+			(0..6)
+				.map(|x| (x, 8))
+				.chain([(7, 8), (8, 8), (8, 7)])
+				.chain((0..6).rev().map(|y| (8, y)))
+		);
+		let copy = read_bits(
+			qr,
+			(0..7)
+				.map(|y| (8, size - 1 - y))
+				.chain((0..8).map(|x| (size - 8 + x, 8)))
+		);
+		assert_eq!(format, copy);
+		let mask = FORMAT_INFO[ecl as usize]
+			.iter()
+			.position(|&info| info == format)
+			.expect("format information of the level");
+
+		if version >= 7 {
+			let info = VERSION_INFO[version - 7];
+			let bottom_left = read_bits(qr, (0..18).rev().map(|i| (i / 3, size - 11 + i % 3)));
+			let top_right = read_bits(qr, (0..18).rev().map(|i| (size - 11 + i % 3, i / 3)));
+			assert_eq!((bottom_left, top_right), (info, info));
+		}
+
+		// Reads the data modules in their zigzag order, removing the mask.
+		let is_masked = |x: usize, y: usize| {
+			let (row, column) = (y, x);
+			match mask {
+				0 => (row + column) % 2 == 0,
+				1 => row % 2 == 0,
+				2 => column % 3 == 0,
+				3 => (row + column) % 3 == 0,
+				4 => (row / 2 + column / 3) % 2 == 0,
+				5 => row * column % 2 + row * column % 3 == 0,
+				6 => (row * column % 2 + row * column % 3) % 2 == 0,
+				_ => ((row + column) % 2 + row * column % 3) % 2 == 0
+			}
+		};
+		let mut bits = Vec::new();
+		let mut upward = true;
+		let mut right = size - 1;
+		loop {
+			for i in 0..size {
+				let y = if upward { size - 1 - i } else { i };
+				for x in [right, right - 1] {
+					if !qr.is_function[y * size + x] {
+						bits.push(qr.get(x, y) ^ is_masked(x, y));
+					}
+				}
+			}
+			upward = !upward;
+			if right < 3 {
+				break;
+			}
+			right -= 2;
+			if right == 6 {
+				right = 5;
+			}
+		}
+		let (codewords, remainder) = total_codewords(version);
+		assert_eq!(bits.len(), codewords * 8 + remainder);
+		let codewords: Vec<u8> = bits[..codewords * 8]
+			.chunks(8)
+			.map(|byte| byte.iter().fold(0, |acc, &b| acc << 1 | b as u8))
+			.collect();
+
+		// Undoes the interleaving and checks that the syndromes of each block
+		// are zero.
+		let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[ecl as usize][version] as usize;
+		let ecc_len = ECC_CODEWORDS_PER_BLOCK[ecl as usize][version] as usize;
+		let num_data = codewords.len() - num_blocks * ecc_len;
+		let num_short = num_blocks - num_data % num_blocks;
+		let data_lens: Vec<usize> = (0..num_blocks)
+			.map(|block| num_data / num_blocks + usize::from(block >= num_short))
+			.collect();
+		let mut blocks: Vec<Vec<u8>> = vec![Vec::new(); num_blocks];
+		let mut next = codewords.iter();
+		for i in 0..data_lens[num_blocks - 1] {
+			for (block, len) in blocks.iter_mut().zip(&data_lens) {
+				if i < *len {
+					block.push(*next.next().unwrap());
+				}
+			}
+		}
+		for _ in 0..ecc_len {
+			for block in &mut blocks {
+				block.push(*next.next().unwrap());
+			}
+		}
+		assert!(next.next().is_none());
+		let mut root = 1;
+		for _ in 0..ecc_len {
+			for block in &blocks {
+				assert_eq!(evaluate(block, root), 0, "syndrome of a block");
+			}
+			root = multiply(root, 2);
+		}
+
+		let data_bits: Vec<bool> = blocks
+			.iter()
+			.zip(&data_lens)
+			.flat_map(|(block, len)| &block[..*len])
+			.flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1 != 0))
+			.collect();
+		let mut data_bits = data_bits.into_iter();
+		let mut take =
+			|len: usize| (0..len).fold(0, |acc, _| acc << 1 | data_bits.next().unwrap() as usize);
+		assert_eq!(take(4), BYTE_MODE as usize);
+		let len = take(if version <= 9 { 8 } else { 16 });
+		let data: Vec<u8> = (0..len).map(|_| take(8) as u8).collect();
+		data
+	}
+
+	#[test]
+	fn matches_format_information() {
+		for ecl in all::<ErrorCorrection>() {
+			let qr = QrCode::encode(b"Goldbeck", ecl).unwrap();
+			for mask in 0..8 {
+				let mut qr = qr.clone();
+				qr.draw_format_bits(ecl, mask);
+				let format = read_bits(
+					&qr,
+					(0..6)
+						.map(|x| (x, 8))
+						.chain([(7, 8), (8, 8), (8, 7)])
+						.chain((0..6).rev().map(|y| (8, y)))
+				);
+				assert_eq!(format, FORMAT_INFO[ecl as usize][mask as usize]);
+			}
+		}
+	}
+
+	#[test]
+	fn matches_version_information() {
+		for version in 7..=MAX_VERSION {
+			let size = version * 4 + 17;
+			let mut qr = QrCode {
+				size,
+				modules: vec![false; size * size],
+				is_function: vec![false; size * size]
+			};
+			qr.draw_version(version);
+			let bits = read_bits(&qr, (0..18).rev().map(|i| (size - 11 + i % 3, i / 3)));
+			assert_eq!(bits, VERSION_INFO[version - 7], "version {version}");
+		}
+	}
+
+	#[test]
+	fn counts_data_modules() {
+		for version in MIN_VERSION..=MAX_VERSION {
+			let (codewords, remainder) = total_codewords(version);
+			assert_eq!(num_raw_data_modules(version), codewords * 8 + remainder);
+		}
+	}
+
+	#[test]
+	fn matches_block_structure() {
+		for (version, levels) in BLOCKS {
+			for (ecl, (blocks, ecc, data)) in all::<ErrorCorrection>().zip(levels) {
+				let level = ecl as usize;
+				assert_eq!(
+					(
+						NUM_ERROR_CORRECTION_BLOCKS[level][version] as usize,
+						ECC_CODEWORDS_PER_BLOCK[level][version] as usize,
+						num_data_codewords(version, ecl)
+					),
+					(blocks, ecc, data),
+					"version {version} {ecl:?}"
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn chooses_smallest_version() {
+		for (version, capacities) in BYTE_CAPACITY {
+			for (ecl, capacity) in all::<ErrorCorrection>().zip(capacities) {
+				let qr = QrCode::encode(&test_data(capacity), ecl).unwrap();
+				assert_eq!(qr.size(), version * 4 + 17, "{capacity} bytes {ecl:?}");
+				let larger = QrCode::encode(&test_data(capacity + 1), ecl);
+				if version == MAX_VERSION {
+					assert!(larger.is_none(), "{} bytes {ecl:?}", capacity + 1);
+				} else {
+					assert_eq!(larger.unwrap().size(), version * 4 + 21);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn decodes_to_data() {
+		for ecl in all::<ErrorCorrection>() {
+			for len in [0, 1, 5, 14, 40, 100, 300] {
+				let data = test_data(len);
+				let qr = QrCode::encode(&data, ecl).unwrap();
+				assert_eq!(decode(&qr, ecl), data, "{len} bytes {ecl:?}");
+			}
+			for (version, capacities) in BYTE_CAPACITY {
+				let data = test_data(capacities[ecl as usize]);
+				let qr = QrCode::encode(&data, ecl).unwrap();
+				assert_eq!(qr.size(), version * 4 + 17);
+				assert_eq!(decode(&qr, ecl), data, "version {version} {ecl:?}");
+			}
+		}
+	}
+}
@@ -0,0 +1,264 @@
+use std::{error::Error, fmt};
+
+use resvg::tiny_skia::Pixmap;
+use usvg::{Node, NodeExt, NodeKind, ShapeRendering, TreeParsing};
+
+use crate::export::ExportSize;
+
+/// The largest width or height of an image.
+pub const MAX_DIMENSION: u32 = 10_000;
+
+/// The largest number of pixels in an image. Rendering holds about 12 bytes
+/// per pixel at once (the pixmap, the demultiplied copy and the PNG rows),
+/// so this keeps the peak allocation around 300 MB, well within what a wasm
+/// module can allocate.
+pub const MAX_PIXELS: u64 = 25_000_000;
+
+const INCH_PER_METER: f32 = 39.370_08;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RasterOptions {
+	pub anti_aliasing: bool
+}
+
+impl Default for RasterOptions {
+	fn default() -> Self {
+		Self {
+			anti_aliasing: true
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum RasterError {
+	Svg(usvg::Error),
+	InvalidSize(u32, u32),
+	Png(png::EncodingError)
+}
+
+impl fmt::Display for RasterError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Svg(err) => write!(f, "Invalid SVG: {err}"),
+			Self::InvalidSize(width, height) => write!(
+				f,
+				"Image size {width}×{height} is outside of 1×1 to {MAX_DIMENSION}×{MAX_DIMENSION} \
+				 or has more than {MAX_PIXELS} pixels"
+			),
+			Self::Png(err) => write!(f, "Failed to encode PNG: {err}")
+		}
+	}
+}
+
+impl Error for RasterError {}
+
+/// Whether an image of the size can be rendered, see [`MAX_DIMENSION`] and
+/// [`MAX_PIXELS`].
+pub fn is_supported_size(width: u32, height: u32) -> bool {
+	(1..=MAX_DIMENSION).contains(&width)
+		&& (1..=MAX_DIMENSION).contains(&height)
+		&& width as u64 * height as u64 <= MAX_PIXELS
+}
+
+/// Renders every path below `node` with crisp edges, including those in
+/// patterns, clip paths and masks.
+fn disable_anti_aliasing(node: &Node) {
+	for node in node.descendants() {
+		if let NodeKind::Path(path) = &mut *node.borrow_mut() {
+			path.rendering_mode = ShapeRendering::CrispEdges;
+		}
+		node.subroots(|root| disable_anti_aliasing(&root));
+	}
+}
+
+/// Renders the SVG to a PNG of the given size that shows its viewBox. The
+/// resolution of the size is stored in the PNG, which determines its size in
+/// print.
+pub fn render_png(
+	svg: &str,
+	size: &ExportSize,
+	options: &RasterOptions
+) -> Result<Vec<u8>, RasterError> {
+	let mut tree =
+		usvg::Tree::from_str(svg, &usvg::Options::default()).map_err(RasterError::Svg)?;
+	let view_box = tree.view_box.rect;
+	let (width, height) = size.pixels(view_box.width() / view_box.height());
+	if !is_supported_size(width, height) {
+		return Err(RasterError::InvalidSize(width, height));
+	}
+	if !options.anti_aliasing {
+		disable_anti_aliasing(&tree.root);
+	}
+
+	// Like SVG's default `preserveAspectRatio`, the viewBox is centered in
+	// the image.
+	tree.size =
+		usvg::Size::from_wh(width as f32, height as f32).expect("Supported sizes are positive");
+	let mut pixmap = Pixmap::new(width, height).expect("Supported sizes are positive");
+	resvg::Tree::from_usvg(&tree).render(Default::default(), &mut pixmap.as_mut());
+
+	// PNG expects straight, not premultiplied, alpha.
+	let rgba: Vec<u8> = pixmap
+		.pixels()
+		.iter()
+		.flat_map(|pixel| {
+			let color = pixel.demultiply();
+			[color.red(), color.green(), color.blue(), color.alpha()]
+		})
+		.collect();
+
+	let mut png = Vec::new();
+	let mut encoder = png::Encoder::new(&mut png, width, height);
+	encoder.set_color(png::ColorType::Rgba);
+	encoder.set_depth(png::BitDepth::Eight);
+	let pixels_per_meter = (size.dpi as f32 * INCH_PER_METER).round() as u32;
+	encoder.set_pixel_dims(Some(png::PixelDimensions {
+		xppu: pixels_per_meter,
+		yppu: pixels_per_meter,
+		unit: png::Unit::Meter
+	}));
+	let mut writer = encoder.write_header().map_err(RasterError::Png)?;
+	writer.write_image_data(&rgba).map_err(RasterError::Png)?;
+	writer.finish().map_err(RasterError::Png)?;
+	Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::export::LengthUnit;
+
+	use super::*;
+
+	const RED: [u8; 4] = [255, 0, 0, 255];
+	const CLEAR: [u8; 4] = [0; 4];
+
+	/// Renders an SVG with a 10 × 10 viewBox at one pixel per unit.
+	fn render(content: &str, anti_aliasing: bool) -> Vec<[u8; 4]> {
+		let svg = format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">{content}</svg>"#
+		);
+		let size = ExportSize {
+			unit: LengthUnit::Px,
+			width: Some(10.0),
+			height: Some(10.0),
+			dpi: 96
+		};
+		let png = render_png(&svg, &size, &RasterOptions { anti_aliasing }).unwrap();
+		let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+		let mut rgba = vec![0; reader.output_buffer_size()];
+		let info = reader.next_frame(&mut rgba).unwrap();
+		assert_eq!((info.width, info.height), (10, 10));
+		rgba.chunks(4)
+			.map(|pixel| pixel.try_into().unwrap())
+			.collect()
+	}
+
+	fn pixel(pixels: &[[u8; 4]], x: usize, y: usize) -> [u8; 4] {
+		pixels[y * 10 + x]
+	}
+
+	#[test]
+	fn fills_solid_rect() {
+		for anti_aliasing in [true, false] {
+			let pixels = render(
+				r#"<rect x="2" y="3" width="6" height="4" fill="red"/>"#,
+				anti_aliasing
+			);
+			for y in 0..10 {
+				for x in 0..10 {
+					let inside = (2..8).contains(&x) && (3..7).contains(&y);
+					let expected = if inside { RED } else { CLEAR };
+					assert_eq!(pixel(&pixels, x, y), expected, "pixel {x}, {y}");
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn covers_edges_partially() {
+		// The right edge covers a quarter of column 4 and the bottom edge three
+		// quarters of row 5.
+		let content = r#"<rect width="4.25" height="5.75" fill="red"/>"#;
+		let pixels = render(content, true);
+		// Coverage is rounded differently by renderers, so allow for a step.
+		let assert_coverage = |x: usize, y: usize, coverage: f32| {
+			let [r, g, b, a] = pixel(&pixels, x, y);
+			assert_eq!([r, g, b], [255, 0, 0], "color at {x}, {y}");
+			let expected = coverage * 255.0;
+			assert!((a as f32 - expected).abs() <= 2.0, "alpha {a} at {x}, {y}");
+		};
+		assert_eq!(pixel(&pixels, 3, 0), RED);
+		assert_coverage(4, 0, 0.25);
+		assert_coverage(0, 5, 0.75);
+		assert_coverage(4, 5, 0.1875);
+		assert_eq!(pixel(&pixels, 5, 0), CLEAR);
+
+		let pixels = render(content, false);
+		assert!(pixels.iter().all(|p| p[3] == 0 || p[3] == 255));
+		assert_eq!(pixel(&pixels, 4, 0), CLEAR);
+		assert_eq!(pixel(&pixels, 0, 5), RED);
+	}
+
+	#[test]
+	fn repeats_pattern_fill() {
+		let pixels = render(
+			r#"<defs><pattern id="p" width="2" height="2" patternUnits="userSpaceOnUse"><rect width="1" height="1" fill="red"/></pattern></defs><rect width="10" height="10" fill="url(#p)"/>"#,
+			false
+		);
+		for y in 0..10 {
+			for x in 0..10 {
+				let expected = if x % 2 == 0 && y % 2 == 0 { RED } else { CLEAR };
+				assert_eq!(pixel(&pixels, x, y), expected, "pixel {x}, {y}");
+			}
+		}
+	}
+
+	#[test]
+	fn applies_clip_paths() {
+		let pixels = render(
+			r#"<defs><clipPath id="c"><rect width="5" height="10"/></clipPath></defs><rect width="10" height="10" fill="red" clip-path="url(#c)"/>"#,
+			false
+		);
+		for y in 0..10 {
+			for x in 0..10 {
+				let expected = if x < 5 { RED } else { CLEAR };
+				assert_eq!(pixel(&pixels, x, y), expected, "pixel {x}, {y}");
+			}
+		}
+	}
+
+	#[test]
+	fn strokes_with_width() {
+		for (width, rows) in [(2.0, 4..6), (4.0, 3..7)] {
+			let pixels = render(
+				&format!(r#"<path d="M0 5H10" stroke="red" stroke-width="{width}"/>"#),
+				true
+			);
+			for y in 0..10 {
+				let expected = if rows.contains(&y) { RED } else { CLEAR };
+				assert_eq!(pixel(&pixels, 5, y), expected, "width {width}, row {y}");
+			}
+		}
+	}
+
+	#[test]
+	fn limits_image_size() {
+		assert!(is_supported_size(1, 1));
+		assert!(is_supported_size(MAX_DIMENSION, 2500));
+		assert!(!is_supported_size(0, 100));
+		assert!(!is_supported_size(MAX_DIMENSION + 1, 1));
+		assert!(!is_supported_size(MAX_DIMENSION, MAX_DIMENSION));
+
+		let size = ExportSize {
+			unit: LengthUnit::Px,
+			width: Some(MAX_DIMENSION as f32),
+			height: Some(MAX_DIMENSION as f32),
+			dpi: 96
+		};
+		let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 1 1"/>"#;
+		assert!(matches!(
+			render_png(svg, &size, &RasterOptions::default()),
+			Err(RasterError::InvalidSize(MAX_DIMENSION, MAX_DIMENSION))
+		));
+	}
+}
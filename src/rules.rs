@@ -0,0 +1,344 @@
+use std::fmt;
+
+use crate::{components::pattern::TileColor, tiling::Tiling};
+
+/// The corporate design rules a pattern is checked against. Every rule can
+/// be switched off by choosing a permissive value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleSet {
+	/// Minimum share of colored tiles, between 0 and 1.
+	pub min_coverage: f32,
+	/// Maximum share of colored tiles, between 0 and 1.
+	pub max_coverage: f32,
+	pub forbid_isolated_tiles: bool,
+	/// Minimum ratio between the less and the more frequently used color.
+	pub min_color_ratio: f32,
+	pub forbid_empty_rows: bool
+}
+
+impl Default for RuleSet {
+	fn default() -> Self {
+		Self {
+			min_coverage: 0.2,
+			max_coverage: 0.6,
+			forbid_isolated_tiles: true,
+			min_color_ratio: 0.25,
+			forbid_empty_rows: true
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+	CoverageTooLow { coverage: f32, min: f32 },
+	CoverageTooHigh { coverage: f32, max: f32 },
+	IsolatedTile { tile: usize },
+	ColorImbalance { ratio: f32, min: f32 },
+	EmptyRow { row: usize }
+}
+
+impl Violation {
+	/// The tiles that cause the violation; empty for rules that concern the
+	/// pattern as a whole.
+	pub fn tiles(&self, tiling: &Tiling) -> Vec<usize> {
+		match self {
+			Self::IsolatedTile { tile } => vec![*tile],
+			Self::EmptyRow { row } => (0..tiling.columns())
+				.flat_map(|x| tiling.square_tiles(x, *row))
+				.collect(),
+			_ => Vec::new()
+		}
+	}
+}
+
+fn percent(value: f32) -> i32 {
+	(value * 100.0).round() as i32
+}
+
+impl fmt::Display for Violation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::CoverageTooLow { coverage, min } => write!(
+				f,
+				"Zu wenig Fläche gefüllt ({} %, mindestens {} %)",
+				percent(*coverage),
+				percent(*min)
+			),
+			Self::CoverageTooHigh { coverage, max } => write!(
+				f,
+				"Zu viel Fläche gefüllt ({} %, höchstens {} %)",
+				percent(*coverage),
+				percent(*max)
+			),
+			Self::IsolatedTile { .. } => write!(f, "Einzelnes, isoliertes Trapez"),
+			Self::ColorImbalance { ratio, min } => write!(
+				f,
+				"Farben unausgewogen (Verhältnis {} %, mindestens {} %)",
+				percent(*ratio),
+				percent(*min)
+			),
+			Self::EmptyRow { row } => write!(f, "Leere Zeile {}", row + 1)
+		}
+	}
+}
+
+impl RuleSet {
+	pub fn check(&self, tiling: &Tiling, colors: &[TileColor]) -> Vec<Violation> {
+		let mut violations = Vec::new();
+
+		let num_primary = colors.iter().filter(|&&c| c == TileColor::Primary).count();
+		let num_secondary = colors
+			.iter()
+			.filter(|&&c| c == TileColor::Secondary)
+			.count();
+		let num_colored = num_primary + num_secondary;
+
+		let coverage = num_colored as f32 / colors.len() as f32;
+		if coverage < self.min_coverage {
+			violations.push(Violation::CoverageTooLow {
+				coverage,
+				min: self.min_coverage
+			});
+		}
+		if coverage > self.max_coverage {
+			violations.push(Violation::CoverageTooHigh {
+				coverage,
+				max: self.max_coverage
+			});
+		}
+
+		if num_colored != 0 {
+			let ratio =
+				num_primary.min(num_secondary) as f32 / num_primary.max(num_secondary) as f32;
+			if ratio < self.min_color_ratio {
+				violations.push(Violation::ColorImbalance {
+					ratio,
+					min: self.min_color_ratio
+				});
+			}
+		}
+
+		if self.forbid_isolated_tiles {
+			for (tile, &color) in colors.iter().enumerate() {
+				if color == TileColor::None {
+					continue;
+				}
+				let isolated = tiling
+					.neighbors(tile)
+					.into_iter()
+					.all(|neighbor| colors[neighbor] != color);
+				if isolated {
+					violations.push(Violation::IsolatedTile { tile });
+				}
+			}
+		}
+
+		if self.forbid_empty_rows {
+			for row in 0..tiling.rows() {
+				let empty = (0..tiling.columns())
+					.flat_map(|x| tiling.square_tiles(x, row))
+					.all(|tile| colors[tile] == TileColor::None);
+				if empty {
+					violations.push(Violation::EmptyRow { row });
+				}
+			}
+		}
+
+		violations
+	}
+}
+
+/// Human readable descriptions of the violations, where repeated violations
+/// of the same kind are merged into a single line.
+pub fn summarize(violations: &[Violation]) -> Vec<String> {
+	let mut summary: Vec<(String, usize)> = Vec::new();
+	for violation in violations {
+		let message = violation.to_string();
+		match summary.iter_mut().find(|(m, _)| *m == message) {
+			Some((_, count)) => *count += 1,
+			None => summary.push((message, 1))
+		}
+	}
+	summary
+		.into_iter()
+		.map(|(message, count)| {
+			if count == 1 {
+				message
+			} else {
+				format!("{message} ({count}×)")
+			}
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tiling::TilingFormat;
+
+	use super::*;
+
+	/// A rule set that only checks for isolated tiles.
+	fn isolated_only() -> RuleSet {
+		RuleSet {
+			min_coverage: 0.0,
+			max_coverage: 1.0,
+			forbid_isolated_tiles: true,
+			min_color_ratio: 0.0,
+			forbid_empty_rows: false
+		}
+	}
+
+	/// Colors the first `primary` tiles in the primary and the next
+	/// `secondary` tiles in the secondary color.
+	fn colors(tiling: &Tiling, primary: usize, secondary: usize) -> Vec<TileColor> {
+		(0..tiling.num_tiles())
+			.map(|i| {
+				if i < primary {
+					TileColor::Primary
+				} else if i < primary + secondary {
+					TileColor::Secondary
+				} else {
+					TileColor::None
+				}
+			})
+			.collect()
+	}
+
+	#[test]
+	fn checks_coverage_bounds() {
+		let tiling = Tiling::load(TilingFormat::F10X10);
+		let num_tiles = tiling.num_tiles();
+		let rules = RuleSet {
+			min_coverage: 0.25,
+			max_coverage: 0.5,
+			forbid_isolated_tiles: false,
+			min_color_ratio: 0.0,
+			forbid_empty_rows: false
+		};
+
+		assert_eq!(
+			rules.check(&tiling, &colors(&tiling, num_tiles / 8, num_tiles / 8)),
+			[]
+		);
+		assert_eq!(
+			rules.check(&tiling, &colors(&tiling, num_tiles / 4, num_tiles / 4)),
+			[]
+		);
+		assert_eq!(
+			rules.check(&tiling, &colors(&tiling, num_tiles / 10, 0)),
+			[Violation::CoverageTooLow {
+				coverage: 0.1,
+				min: 0.25
+			}]
+		);
+		assert_eq!(
+			rules.check(&tiling, &colors(&tiling, num_tiles / 2, num_tiles / 4)),
+			[Violation::CoverageTooHigh {
+				coverage: 0.75,
+				max: 0.5
+			}]
+		);
+	}
+
+	#[test]
+	fn checks_color_ratio() {
+		let tiling = Tiling::load(TilingFormat::F10X10);
+		let rules = RuleSet {
+			min_coverage: 0.0,
+			max_coverage: 1.0,
+			forbid_isolated_tiles: false,
+			min_color_ratio: 0.25,
+			forbid_empty_rows: false
+		};
+
+		assert_eq!(rules.check(&tiling, &colors(&tiling, 40, 10)), []);
+		assert_eq!(rules.check(&tiling, &colors(&tiling, 10, 40)), []);
+		assert_eq!(
+			rules.check(&tiling, &colors(&tiling, 50, 10)),
+			[Violation::ColorImbalance {
+				ratio: 0.2,
+				min: 0.25
+			}]
+		);
+		assert_eq!(
+			rules.check(&tiling, &colors(&tiling, 0, 10)),
+			[Violation::ColorImbalance {
+				ratio: 0.0,
+				min: 0.25
+			}]
+		);
+		// An empty pattern has no colors to balance.
+		assert_eq!(rules.check(&tiling, &colors(&tiling, 0, 0)), []);
+	}
+
+	#[test]
+	fn neighbors_are_mutual() {
+		for format in [TilingFormat::F5X5, TilingFormat::F10X15] {
+			let tiling = Tiling::load(format);
+			for tile in 0..tiling.num_tiles() {
+				for neighbor in tiling.neighbors(tile) {
+					assert!(
+						tiling.neighbors(neighbor).contains(&tile),
+						"{tile} and {neighbor} in {format:?}"
+					);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn finds_isolated_tiles_across_edges() {
+		let tiling = Tiling::load(TilingFormat::F10X10);
+		let columns = tiling.columns();
+		let rows = tiling.rows();
+		let rules = isolated_only();
+
+		// The left tile of the first square touches the right tile of the last
+		// square in the same row.
+		let [left, _] = tiling.square_tiles(0, 0);
+		let [_, wrapped] = tiling.square_tiles(columns - 1, 0);
+		let mut colors = vec![TileColor::None; tiling.num_tiles()];
+		colors[left] = TileColor::Primary;
+		assert_eq!(
+			rules.check(&tiling, &colors),
+			[Violation::IsolatedTile { tile: left }]
+		);
+		colors[wrapped] = TileColor::Primary;
+		assert_eq!(rules.check(&tiling, &colors), []);
+		// Only tiles of the same color count.
+		colors[wrapped] = TileColor::Secondary;
+		assert_eq!(rules.check(&tiling, &colors).len(), 2);
+
+		// Tiles of the first row touch tiles of the last row.
+		let [top, _] = tiling.square_tiles(columns / 2, 0);
+		let below_edge: Vec<usize> = tiling
+			.neighbors(top)
+			.into_iter()
+			.filter(|&neighbor| tiling.tile_position(neighbor).1 == rows - 1)
+			.collect();
+		assert!(!below_edge.is_empty());
+		let mut colors = vec![TileColor::None; tiling.num_tiles()];
+		colors[top] = TileColor::Secondary;
+		colors[below_edge[0]] = TileColor::Secondary;
+		assert_eq!(rules.check(&tiling, &colors), []);
+	}
+
+	#[test]
+	fn finds_empty_rows() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let rules = RuleSet {
+			forbid_isolated_tiles: false,
+			forbid_empty_rows: true,
+			..isolated_only()
+		};
+		let mut colors = vec![TileColor::Primary; tiling.num_tiles()];
+		for x in 0..tiling.columns() {
+			for tile in tiling.square_tiles(x, 2) {
+				colors[tile] = TileColor::None;
+			}
+		}
+		let violations = rules.check(&tiling, &colors);
+		assert_eq!(violations, [Violation::EmptyRow { row: 2 }]);
+		assert_eq!(violations[0].tiles(&tiling).len(), 2 * tiling.columns());
+	}
+}
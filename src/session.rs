@@ -0,0 +1,198 @@
+use std::{sync::OnceLock, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{components::canvas::Brush, document::PatternData, storage};
+
+/// The state of every tab's session. The sessions themselves are stored
+/// separately, so that the heartbeat doesn't rewrite them.
+const SESSIONS_KEY: &str = "sessions";
+
+/// How often a running tab records that it is still open.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+/// How long a running session may go without a heartbeat before it counts as
+/// crashed. Browsers run the timers of background tabs only about once a
+/// minute, so this is well above that.
+const STALE_AFTER: Duration = Duration::from_secs(180);
+
+/// The id of this tab's session.
+static TAB_ID: OnceLock<String> = OnceLock::new();
+
+/// Everything that is restored when the app is reopened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+	pub pattern: PatternData,
+	pub brush: Brush
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum SessionState {
+	Running,
+	Closed,
+	Crashed
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SessionRecord {
+	id: String,
+	state: SessionState,
+	/// The time of the last heartbeat, in ms since the epoch.
+	heartbeat: f64
+}
+
+impl SessionRecord {
+	/// Whether the session belongs to a tab that is still open.
+	fn is_live(&self, now: f64) -> bool {
+		self.state == SessionState::Running && now - self.heartbeat < STALE_AFTER.as_millis() as f64
+	}
+}
+
+/// The session of a tab that is not open anymore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviousSession {
+	pub id: String,
+	pub session: Session,
+	/// Whether the tab ended unexpectedly.
+	pub crashed: bool
+}
+
+fn session_key(id: &str) -> String {
+	format!("session-{id}")
+}
+
+fn tab_id() -> &'static str {
+	TAB_ID.get_or_init(|| Uuid::new_v4().to_string())
+}
+
+fn now() -> f64 {
+	js_sys::Date::now()
+}
+
+/// The most recent session of a tab that is not open anymore.
+fn latest_inactive(records: &[SessionRecord], now: f64) -> Option<&SessionRecord> {
+	records
+		.iter()
+		.filter(|record| !record.is_live(now))
+		.max_by(|a, b| a.heartbeat.total_cmp(&b.heartbeat))
+}
+
+fn update_record(update: impl FnOnce(&mut SessionRecord)) {
+	let mut records: Vec<SessionRecord> = storage::load(SESSIONS_KEY).unwrap_or_default();
+	if let Some(record) = records.iter_mut().find(|record| record.id == tab_id()) {
+		update(record);
+		storage::save(SESSIONS_KEY, &records);
+	}
+}
+
+/// Marks a new session of this tab as running, and returns the last session
+/// of a tab that is not open anymore. Sessions of tabs that are still open
+/// are left alone; older inactive ones are forgotten.
+pub fn begin() -> Option<PreviousSession> {
+	let now = now();
+	let mut records: Vec<SessionRecord> = storage::load(SESSIONS_KEY).unwrap_or_default();
+	let previous = latest_inactive(&records, now).cloned();
+	records.retain(|record| {
+		let keep = record.is_live(now) || Some(record) == previous.as_ref();
+		if !keep {
+			storage::remove(&session_key(&record.id));
+		}
+		keep
+	});
+	records.push(SessionRecord {
+		id: tab_id().to_string(),
+		state: SessionState::Running,
+		heartbeat: now
+	});
+	storage::save(SESSIONS_KEY, &records);
+
+	let previous = previous?;
+	Some(PreviousSession {
+		session: storage::load(&session_key(&previous.id))?,
+		crashed: previous.state != SessionState::Closed,
+		id: previous.id
+	})
+}
+
+/// Removes the session of a tab that is not open anymore, once it was
+/// restored or discarded.
+pub fn forget(id: &str) {
+	let mut records: Vec<SessionRecord> = storage::load(SESSIONS_KEY).unwrap_or_default();
+	records.retain(|record| record.id != id);
+	storage::save(SESSIONS_KEY, &records);
+	storage::remove(&session_key(id));
+}
+
+/// Records that this tab is still open.
+pub fn heartbeat() {
+	update_record(|record| record.heartbeat = now());
+}
+
+/// Marks the session as closed regularly, unless it has crashed before.
+pub fn end() {
+	update_record(|record| {
+		if record.state != SessionState::Crashed {
+			record.state = SessionState::Closed;
+			record.heartbeat = now();
+		}
+	});
+}
+
+pub fn mark_crashed() {
+	update_record(|record| record.state = SessionState::Crashed);
+}
+
+pub fn save(session: &Session) {
+	storage::save(&session_key(tab_id()), session);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const NOW: f64 = 1_000_000_000.0;
+
+	fn record(id: &str, state: SessionState, age: Duration) -> SessionRecord {
+		SessionRecord {
+			id: id.to_string(),
+			state,
+			heartbeat: NOW - age.as_millis() as f64
+		}
+	}
+
+	#[test]
+	fn ignores_sessions_of_open_tabs() {
+		let records = [
+			record("open", SessionState::Running, Duration::from_secs(5)),
+			record("background", SessionState::Running, Duration::from_secs(90))
+		];
+		assert!(records.iter().all(|record| record.is_live(NOW)));
+		assert_eq!(latest_inactive(&records, NOW), None);
+	}
+
+	#[test]
+	fn restores_latest_inactive_session() {
+		let records = [
+			record("closed", SessionState::Closed, Duration::from_secs(60)),
+			record("open", SessionState::Running, Duration::from_secs(1)),
+			record(
+				"stale",
+				SessionState::Running,
+				STALE_AFTER + Duration::from_secs(1)
+			),
+			record("older", SessionState::Closed, Duration::from_secs(3600))
+		];
+		assert_eq!(latest_inactive(&records, NOW).unwrap().id, "closed");
+		assert!(!records[2].is_live(NOW));
+		assert_eq!(latest_inactive(&records[1..], NOW).unwrap().id, "stale");
+	}
+
+	#[test]
+	fn treats_crashed_sessions_as_inactive() {
+		let records = [
+			record("open", SessionState::Running, Duration::ZERO),
+			record("crashed", SessionState::Crashed, Duration::from_secs(1))
+		];
+		assert_eq!(latest_inactive(&records, NOW).unwrap().id, "crashed");
+	}
+}
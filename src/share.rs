@@ -0,0 +1,233 @@
+use std::{error::Error, fmt};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use enum_iterator::all;
+
+use crate::{
+	components::pattern::TileColor,
+	theme::Theme,
+	tiling::{Tiling, TilingFormat}
+};
+
+const VERSION: u8 = 1;
+
+/// Tiles per byte, since every [`TileColor`] needs two bits.
+const TILES_PER_BYTE: usize = 4;
+
+/// A pattern as it is transferred in a share link; only the flattened colors
+/// are included, not the layers they were painted on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedPattern {
+	pub format: TilingFormat,
+	pub theme: Theme,
+	pub colors: Vec<TileColor>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+	InvalidBase64,
+	UnsupportedVersion(u8),
+	InvalidHeader,
+	InvalidColor,
+	WrongLength { expected: usize, actual: usize }
+}
+
+impl fmt::Display for DecodeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::InvalidBase64 => write!(f, "Share code is not valid base64"),
+			Self::UnsupportedVersion(version) => {
+				write!(f, "Unsupported share code version {version}")
+			}
+			Self::InvalidHeader => write!(f, "Share code has an unknown format or theme"),
+			Self::InvalidColor => write!(f, "Share code contains an unknown tile color"),
+			Self::WrongLength { expected, actual } => write!(
+				f,
+				"Share code has {actual} bytes of tile data, expected {expected}"
+			)
+		}
+	}
+}
+
+impl Error for DecodeError {}
+
+impl SharedPattern {
+	/// Encodes the pattern as URL-safe base64 without padding.
+	///
+	/// The first byte is the version, the second holds the format in its
+	/// upper and the theme in its lower four bits. All following bytes hold
+	/// four tiles each, starting with the least significant bits.
+	pub fn encode(&self) -> String {
+		let mut bytes = Vec::with_capacity(2 + self.colors.len().div_ceil(TILES_PER_BYTE));
+		bytes.push(VERSION);
+		bytes.push((self.format as u8) << 4 | self.theme as u8);
+		for chunk in self.colors.chunks(TILES_PER_BYTE) {
+			let byte = chunk
+				.iter()
+				.enumerate()
+				.fold(0, |byte, (i, &color)| byte | (color as u8) << (2 * i));
+			bytes.push(byte);
+		}
+		URL_SAFE_NO_PAD.encode(bytes)
+	}
+
+	pub fn decode(code: &str) -> Result<Self, DecodeError> {
+		let bytes = URL_SAFE_NO_PAD
+			.decode(code.trim())
+			.map_err(|_| DecodeError::InvalidBase64)?;
+		let [version, header, data @ ..] = bytes.as_slice() else {
+			return Err(DecodeError::InvalidHeader);
+		};
+		if *version != VERSION {
+			return Err(DecodeError::UnsupportedVersion(*version));
+		}
+		let format = all::<TilingFormat>()
+			.nth((header >> 4) as usize)
+			.ok_or(DecodeError::InvalidHeader)?;
+		let theme = all::<Theme>()
+			.nth((header & 0x0f) as usize)
+			.ok_or(DecodeError::InvalidHeader)?;
+
+		let num_tiles = Tiling::load(format).num_tiles();
+		let expected = num_tiles.div_ceil(TILES_PER_BYTE);
+		if data.len() != expected {
+			return Err(DecodeError::WrongLength {
+				expected,
+				actual: data.len()
+			});
+		}
+		let colors = (0..num_tiles)
+			.map(|i| {
+				let bits = data[i / TILES_PER_BYTE] >> (2 * (i % TILES_PER_BYTE)) & 0b11;
+				all::<TileColor>()
+					.nth(bits as usize)
+					.ok_or(DecodeError::InvalidColor)
+			})
+			.collect::<Result<_, _>>()?;
+
+		Ok(Self {
+			format,
+			theme,
+			colors
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn code(bytes: &[u8]) -> String {
+		URL_SAFE_NO_PAD.encode(bytes)
+	}
+
+	/// A header and tile data of the right length for `format`.
+	fn bytes(format: TilingFormat, theme: Theme, tile_byte: u8) -> Vec<u8> {
+		let len = Tiling::load(format).num_tiles().div_ceil(TILES_PER_BYTE);
+		let mut bytes = vec![VERSION, (format as u8) << 4 | theme as u8];
+		bytes.resize(2 + len, tile_byte);
+		bytes
+	}
+
+	#[test]
+	fn round_trips_every_format_and_theme() {
+		for format in all::<TilingFormat>() {
+			for theme in all::<Theme>() {
+				let colors = (0..Tiling::load(format).num_tiles())
+					.map(|i| all::<TileColor>().nth(i * 7 % 3).unwrap())
+					.collect();
+				let pattern = SharedPattern {
+					format,
+					theme,
+					colors
+				};
+				let code = pattern.encode();
+				assert!(code
+					.bytes()
+					.all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+				assert_eq!(SharedPattern::decode(&code), Ok(pattern));
+				// Whitespace around pasted codes is ignored.
+				assert!(SharedPattern::decode(&format!(" {code}\n")).is_ok());
+			}
+		}
+	}
+
+	#[test]
+	fn decodes_tiles_from_the_least_significant_bits() {
+		let format = TilingFormat::F5X5;
+		let pattern =
+			SharedPattern::decode(&code(&bytes(format, Theme::Glisten, 0b00_10_01_00))).unwrap();
+		assert_eq!(pattern.format, format);
+		assert_eq!(pattern.theme, Theme::Glisten);
+		assert_eq!(
+			pattern.colors[..4],
+			[
+				TileColor::None,
+				TileColor::Primary,
+				TileColor::Secondary,
+				TileColor::None
+			]
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_base64() {
+		assert_eq!(
+			SharedPattern::decode("a+b/c="),
+			Err(DecodeError::InvalidBase64)
+		);
+	}
+
+	#[test]
+	fn rejects_unsupported_version() {
+		let mut bytes = bytes(TilingFormat::F5X5, Theme::Bubbles, 0);
+		bytes[0] = VERSION + 1;
+		assert_eq!(
+			SharedPattern::decode(&code(&bytes)),
+			Err(DecodeError::UnsupportedVersion(VERSION + 1))
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_header() {
+		assert_eq!(SharedPattern::decode(""), Err(DecodeError::InvalidHeader));
+		assert_eq!(
+			SharedPattern::decode(&code(&[VERSION])),
+			Err(DecodeError::InvalidHeader)
+		);
+		for header in [0xf0, 0x0f] {
+			let mut bytes = bytes(TilingFormat::F5X5, Theme::Bubbles, 0);
+			bytes[1] = header;
+			assert_eq!(
+				SharedPattern::decode(&code(&bytes)),
+				Err(DecodeError::InvalidHeader)
+			);
+		}
+	}
+
+	#[test]
+	fn rejects_invalid_color() {
+		let mut bytes = bytes(TilingFormat::F10X10, Theme::Noble, 0);
+		*bytes.last_mut().unwrap() = 0b11;
+		assert_eq!(
+			SharedPattern::decode(&code(&bytes)),
+			Err(DecodeError::InvalidColor)
+		);
+	}
+
+	#[test]
+	fn rejects_wrong_length() {
+		for format in all::<TilingFormat>() {
+			let bytes = bytes(format, Theme::Icy, 0);
+			let expected = bytes.len() - 2;
+			for actual in [0, expected - 1, expected + 1] {
+				let mut bytes = bytes.clone();
+				bytes.resize(2 + actual, 0);
+				assert_eq!(
+					SharedPattern::decode(&code(&bytes)),
+					Err(DecodeError::WrongLength { expected, actual })
+				);
+			}
+		}
+	}
+}
@@ -0,0 +1,302 @@
+use std::fmt::Write;
+
+use enum_iterator::all;
+
+use crate::{
+	artwork::SEAM_STROKE_WIDTH, components::pattern::TileColor, theme::Theme, tiling::Tiling
+};
+
+/// The class that the CSS applies the background to.
+pub const CSS_CLASS: &str = "goldbeck-pattern";
+
+/// The custom property that holds the background image of the current theme.
+const IMAGE_VARIABLE: &str = "--goldbeck-pattern";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnippetOptions {
+	/// Whether the colors follow the theme of the page instead of being fixed.
+	pub theme_variables: bool,
+	/// The edge length of a grid square in CSS pixels.
+	pub square_size: f32
+}
+
+impl Default for SnippetOptions {
+	fn default() -> Self {
+		Self {
+			theme_variables: false,
+			square_size: 20.0
+		}
+	}
+}
+
+/// The custom property from `styles.css` that holds the color of a tile.
+fn theme_variable(color: TileColor) -> &'static str {
+	match color {
+		TileColor::None => "--theme-background",
+		TileColor::Primary => "--theme-primary",
+		TileColor::Secondary => "--theme-secondary"
+	}
+}
+
+/// Writes one period of the pattern as a minimal SVG that tiles seamlessly,
+/// with one path per color. Attributes are single-quoted, so that the SVG can
+/// be put into a double-quoted CSS `url()`.
+///
+/// With `theme_variables`, the colors are read from the custom properties of
+/// `styles.css`, falling back to `theme`. This only takes effect when the SVG
+/// is inlined into a page; SVGs used as images can't see the page's styles.
+pub fn write_snippet(
+	tiling: &Tiling,
+	colors: &[TileColor],
+	theme: Theme,
+	theme_variables: bool
+) -> String {
+	let palette = theme.palette();
+	let paint = |color: TileColor| {
+		let rgb = palette.tile_color(color);
+		if theme_variables {
+			format!(
+				"rgb(var({}, {} {} {}))",
+				theme_variable(color),
+				rgb.0,
+				rgb.1,
+				rgb.2
+			)
+		} else {
+			rgb.hex()
+		}
+	};
+	let (width, height) = (tiling.viewport_width(), tiling.viewport_height());
+
+	let mut svg = String::new();
+	write!(
+		svg,
+		"<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 {width} {height}'><rect \
+		 width='{width}' height='{height}' fill='{}'/>",
+		paint(TileColor::None)
+	)
+	.unwrap();
+	for color in all::<TileColor>().filter(|c| *c != TileColor::None) {
+		let mut path = String::new();
+		for (i, shape) in tiling.iter_tiles().enumerate() {
+			if colors.get(i).copied().unwrap_or_default() == color {
+				write!(path, "M{}Z", shape.svg_path()).unwrap();
+			}
+		}
+		if path.is_empty() {
+			continue;
+		}
+		let fill = paint(color);
+		write!(
+			svg,
+			"<path d='{path}' fill='{fill}' stroke='{fill}' stroke-width='{SEAM_STROKE_WIDTH}'/>"
+		)
+		.unwrap();
+	}
+	svg.push_str("</svg>");
+	svg
+}
+
+/// Encodes an SVG as a `data:` URI. Only the characters that would end the
+/// URI or break it in CSS are escaped, which keeps it readable and shorter
+/// than base64.
+pub fn data_uri(svg: &str) -> String {
+	let mut uri = String::from("data:image/svg+xml,");
+	for ch in svg.chars() {
+		match ch {
+			'%' | '#' | '<' | '>' | '"' | '{' | '}' | '\n' => {
+				write!(uri, "%{:02X}", ch as u32).unwrap()
+			}
+			ch if !ch.is_ascii() => {
+				let mut bytes = [0; 4];
+				for byte in ch.encode_utf8(&mut bytes).bytes() {
+					write!(uri, "%{byte:02X}").unwrap();
+				}
+			}
+			ch => uri.push(ch)
+		}
+	}
+	uri
+}
+
+/// Writes CSS that sets the pattern as a repeating background of the class
+/// [`CSS_CLASS`].
+///
+/// With theme variables, there is a background image per theme that follows
+/// the `data-theme` attribute like the colors in `styles.css` do, since
+/// custom properties don't reach into background images.
+pub fn write_css(
+	tiling: &Tiling,
+	colors: &[TileColor],
+	theme: Theme,
+	options: &SnippetOptions
+) -> String {
+	let image = |theme: Theme| {
+		format!(
+			"url(\"{}\")",
+			data_uri(&write_snippet(tiling, colors, theme, false))
+		)
+	};
+	let size = format!(
+		"{}px {}px",
+		tiling.viewport_width() * options.square_size,
+		tiling.viewport_height() * options.square_size
+	);
+
+	let mut css = String::new();
+	let background = if options.theme_variables {
+		format!("var({IMAGE_VARIABLE})")
+	} else {
+		image(theme)
+	};
+	writeln!(css, ".{CSS_CLASS} {{").unwrap();
+	writeln!(css, "\tbackground-image: {background};").unwrap();
+	writeln!(css, "\tbackground-size: {size};").unwrap();
+	writeln!(css, "}}").unwrap();
+	if !options.theme_variables {
+		return css;
+	}
+
+	writeln!(css, "\n:root {{\n\t{IMAGE_VARIABLE}: {};\n}}", image(theme)).unwrap();
+	for theme in all::<Theme>() {
+		writeln!(
+			css,
+			"\n[data-theme=\"{}\"] {{\n\t{IMAGE_VARIABLE}: {};\n}}",
+			theme.name(),
+			image(theme)
+		)
+		.unwrap();
+	}
+	css
+}
+
+#[cfg(test)]
+mod tests {
+	use roxmltree::Document;
+
+	use crate::tiling::TilingFormat;
+
+	use super::*;
+
+	fn colors(tiling: &Tiling) -> Vec<TileColor> {
+		(0..tiling.num_tiles())
+			.map(|i| all::<TileColor>().nth(i * 7 % 3).unwrap())
+			.collect()
+	}
+
+	/// Decodes the percent escapes of a data URI.
+	fn decode(uri: &str) -> String {
+		let data = uri.strip_prefix("data:image/svg+xml,").unwrap();
+		let mut bytes = Vec::new();
+		let mut rest = data.as_bytes();
+		while let Some((&byte, tail)) = rest.split_first() {
+			if byte == b'%' {
+				let hex = std::str::from_utf8(&tail[..2]).unwrap();
+				bytes.push(u8::from_str_radix(hex, 16).unwrap());
+				rest = &tail[2..];
+			} else {
+				bytes.push(byte);
+				rest = tail;
+			}
+		}
+		String::from_utf8(bytes).unwrap()
+	}
+
+	#[test]
+	fn escapes_data_uris() {
+		let svg = "<svg a='50%' b=\"#x\">{ü}\n</svg>";
+		let uri = data_uri(svg);
+		assert_eq!(
+			uri,
+			"data:image/svg+xml,%3Csvg a='50%25' b=%22%23x%22%3E%7B%C3%BC%7D%0A%3C/svg%3E"
+		);
+		assert_eq!(decode(&uri), svg);
+	}
+
+	#[test]
+	fn writes_a_path_per_color() {
+		let tiling = Tiling::load(TilingFormat::F10X10);
+		let colors = colors(&tiling);
+		let svg = write_snippet(&tiling, &colors, Theme::Glisten, false);
+		// Attributes are single-quoted, so that the SVG fits into `url("…")`.
+		assert!(!svg.contains('"'));
+
+		let document = Document::parse(&svg).unwrap();
+		let root = document.root_element();
+		assert_eq!(root.attribute("viewBox"), Some("0 0 10 10"));
+		let palette = Theme::Glisten.palette();
+		let background = root.first_element_child().unwrap();
+		assert_eq!(
+			background.attribute("fill"),
+			Some(palette.tile_color(TileColor::None).hex().as_str())
+		);
+
+		let paths: Vec<_> = root
+			.children()
+			.filter(|node| node.has_tag_name("path"))
+			.collect();
+		assert_eq!(paths.len(), 2);
+		for (path, color) in paths.iter().zip([TileColor::Primary, TileColor::Secondary]) {
+			assert_eq!(
+				path.attribute("fill"),
+				Some(palette.tile_color(color).hex().as_str())
+			);
+			let tiles = colors.iter().filter(|c| **c == color).count();
+			assert_eq!(path.attribute("d").unwrap().matches('M').count(), tiles);
+		}
+
+		// Colors that don't occur get no path.
+		let empty = vec![TileColor::Primary; tiling.num_tiles()];
+		let svg = write_snippet(&tiling, &empty, Theme::Glisten, false);
+		assert_eq!(svg.matches("<path").count(), 1);
+	}
+
+	#[test]
+	fn reads_colors_from_theme_variables() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let svg = write_snippet(&tiling, &colors(&tiling), Theme::Noble, true);
+		let palette = Theme::Noble.palette();
+		for color in all::<TileColor>() {
+			let rgb = palette.tile_color(color);
+			let paint = format!(
+				"fill='rgb(var({}, {} {} {}))'",
+				theme_variable(color),
+				rgb.0,
+				rgb.1,
+				rgb.2
+			);
+			assert!(svg.contains(&paint), "{paint}");
+		}
+	}
+
+	#[test]
+	fn writes_css_backgrounds() {
+		let tiling = Tiling::load(TilingFormat::F10X15);
+		let colors = colors(&tiling);
+		let options = SnippetOptions {
+			theme_variables: false,
+			square_size: 12.0
+		};
+		let css = write_css(&tiling, &colors, Theme::Icy, &options);
+		assert!(css.starts_with(&format!(".{CSS_CLASS} {{\n")));
+		assert!(css.contains("\tbackground-size: 120px 180px;\n"));
+		let start = css.find("url(\"").unwrap() + 5;
+		let end = start + css[start..].find('"').unwrap();
+		assert_eq!(
+			decode(&css[start..end]),
+			write_snippet(&tiling, &colors, Theme::Icy, false)
+		);
+
+		let options = SnippetOptions {
+			theme_variables: true,
+			..options
+		};
+		let css = write_css(&tiling, &colors, Theme::Icy, &options);
+		assert!(css.contains(&format!("\tbackground-image: var({IMAGE_VARIABLE});\n")));
+		assert!(css.contains(":root {"));
+		for theme in all::<Theme>() {
+			assert!(css.contains(&format!("[data-theme=\"{}\"] {{", theme.name())));
+		}
+		assert_eq!(css.matches("url(").count(), 1 + all::<Theme>().count());
+	}
+}
@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	components::pattern::TileColor,
+	tiling::{Tiling, PATTERN_HEIGHT}
+};
+
+/// A rectangular region of squares, spanned between the square where a
+/// selection was started and the square where it currently ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+	pub start: (usize, usize),
+	pub end: (usize, usize)
+}
+
+impl Selection {
+	pub fn new(square: (usize, usize)) -> Self {
+		Self {
+			start: square,
+			end: square
+		}
+	}
+
+	/// The top left square, width and height of the selection.
+	pub fn bounds(&self) -> (usize, usize, usize, usize) {
+		let x = self.start.0.min(self.end.0);
+		let y = self.start.1.min(self.end.1);
+		let width = self.start.0.abs_diff(self.end.0) + 1;
+		let height = self.start.1.abs_diff(self.end.1) + 1;
+		(x, y, width, height)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+	MirrorHorizontal,
+	MirrorVertical,
+	Rotate180
+}
+
+/// A reusable motif of `width`×`height` squares. Like in a [`Tiling`], every
+/// square holds a left and a right trapezoid, stored row by row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stamp {
+	pub name: String,
+	pub width: usize,
+	pub height: usize,
+	/// The row of the tiling modulo [`PATTERN_HEIGHT`] that the top row of
+	/// the stamp belongs on, which determines the shapes of its trapezoids.
+	#[serde(default)]
+	pub phase: usize,
+	cells: Vec<TileColor>
+}
+
+/// The center of the left or right trapezoid of a square, in twentieths of a
+/// square, which keeps it exact. `phase` is the row of the square modulo
+/// [`PATTERN_HEIGHT`].
+fn tile_center(x: usize, y: usize, phase: usize, right: bool) -> (i64, i64) {
+	// The diagonal runs from `1 - phase / 5` at the top of the square to
+	// `1 - (phase + 1) / 5` at its bottom.
+	let phase = (phase % PATTERN_HEIGHT) as i64;
+	let offset = if right { 19 } else { 9 };
+	(20 * x as i64 + offset - 2 * phase, 20 * y as i64 + 10)
+}
+
+fn squared_distance(a: (i64, i64), b: (i64, i64)) -> i64 {
+	(a.0 - b.0).pow(2) + (a.1 - b.1).pow(2)
+}
+
+impl Stamp {
+	/// Parses a motif from rows of characters, two per square, where `#` is
+	/// the primary color, `+` the secondary color and anything else is
+	/// transparent.
+	fn from_rows(name: &str, rows: &[&str]) -> Self {
+		let cells: Vec<TileColor> = rows
+			.iter()
+			.flat_map(|row| row.chars())
+			.map(|ch| match ch {
+				'#' => TileColor::Primary,
+				'+' => TileColor::Secondary,
+				_ => TileColor::None
+			})
+			.collect();
+		Self {
+			name: String::from(name),
+			width: rows[0].len() / 2,
+			height: rows.len(),
+			phase: 0,
+			cells
+		}
+	}
+
+	/// Copies the selected region of a pattern into a new stamp.
+	pub fn capture(
+		name: String,
+		tiling: &Tiling,
+		colors: &[TileColor],
+		selection: Selection
+	) -> Self {
+		let (x0, y0, width, height) = selection.bounds();
+		let mut cells = Vec::with_capacity(2 * width * height);
+		for y in y0..y0 + height {
+			for x in x0..x0 + width {
+				cells.extend(tiling.square_tiles(x, y).map(|i| colors[i]));
+			}
+		}
+		Self {
+			name,
+			width,
+			height,
+			phase: y0 % PATTERN_HEIGHT,
+			cells
+		}
+	}
+
+	fn cell(&self, x: usize, y: usize, right: bool) -> TileColor {
+		self.cells[2 * (y * self.width + x) + right as usize]
+	}
+
+	/// Returns a transformed copy of the stamp.
+	///
+	/// All diagonals of the tiling lean in the same direction, so mirrors can
+	/// only be approximated. Every tile takes the color of the tile whose
+	/// mirrored center is nearest, among the two of the mirrored square, so
+	/// that applying a transform twice restores the stamp. Flipping the rows
+	/// moves the stamp to the phase where a rotation by 180° maps every
+	/// trapezoid exactly onto one of the other side.
+	pub fn transformed(&self, transform: Transform) -> Self {
+		let (flip_x, flip_y) = match transform {
+			Transform::MirrorHorizontal => (true, false),
+			Transform::MirrorVertical => (false, true),
+			Transform::Rotate180 => (true, true)
+		};
+		let phase = if flip_y {
+			(PATTERN_HEIGHT - (self.phase + self.height) % PATTERN_HEIGHT) % PATTERN_HEIGHT
+		} else {
+			self.phase
+		};
+		let (width, height) = (20 * self.width as i64, 20 * self.height as i64);
+		let mirror = |(x, y): (i64, i64)| {
+			(
+				if flip_x { width - x } else { x },
+				if flip_y { height - y } else { y }
+			)
+		};
+
+		let mut cells = Vec::with_capacity(self.cells.len());
+		for y in 0..self.height {
+			let source_y = if flip_y { self.height - 1 - y } else { y };
+			for x in 0..self.width {
+				let source_x = if flip_x { self.width - 1 - x } else { x };
+				let [source_left, source_right] = [false, true].map(|right| {
+					mirror(tile_center(
+						source_x,
+						source_y,
+						self.phase + source_y,
+						right
+					))
+				});
+				let [left, right] = [false, true].map(|right| tile_center(x, y, phase + y, right));
+				let kept =
+					squared_distance(source_left, left) + squared_distance(source_right, right);
+				let swapped =
+					squared_distance(source_left, right) + squared_distance(source_right, left);
+				let swap = swapped < kept;
+				for right in [false, true] {
+					cells.push(self.cell(source_x, source_y, right != swap));
+				}
+			}
+		}
+		Self {
+			name: self.name.clone(),
+			width: self.width,
+			height: self.height,
+			phase,
+			cells
+		}
+	}
+
+	/// The row that the top row of the stamp is placed on when it is placed
+	/// on `row`: the nearest one of its phase, so that the trapezoids keep
+	/// their shapes.
+	fn snap_row(&self, row: usize) -> usize {
+		let above = (row + PATTERN_HEIGHT - self.phase % PATTERN_HEIGHT) % PATTERN_HEIGHT;
+		if above <= PATTERN_HEIGHT / 2 && above <= row {
+			row - above
+		} else {
+			row + PATTERN_HEIGHT - above
+		}
+	}
+
+	/// The tiles painted by the stamp if its top left square is placed on
+	/// the square of tile `anchor`, together with their colors. The stamp
+	/// moves up or down to the nearest row of its phase. Transparent cells and
+	/// cells outside of the tiling are skipped.
+	pub fn placements(&self, tiling: &Tiling, anchor: usize) -> Vec<(usize, TileColor)> {
+		let (anchor_x, anchor_y, _) = tiling.tile_position(anchor);
+		let anchor_y = self.snap_row(anchor_y);
+		let mut placements = Vec::new();
+		for y in 0..self.height {
+			for x in 0..self.width {
+				let (target_x, target_y) = (anchor_x + x, anchor_y + y);
+				if target_x >= tiling.columns() || target_y >= tiling.rows() {
+					continue;
+				}
+				let tiles = tiling.square_tiles(target_x, target_y);
+				for (right, tile) in [false, true].into_iter().zip(tiles) {
+					let color = self.cell(x, y, right);
+					if color != TileColor::None {
+						placements.push((tile, color));
+					}
+				}
+			}
+		}
+		placements
+	}
+}
+
+pub fn builtin_stamps() -> Vec<Stamp> {
+	vec![
+		Stamp::from_rows(
+			"Pfeil",
+			&["..##....", "....##..", "########", "....##..", "..##...."]
+		),
+		Stamp::from_rows("G", &["..####", "##....", "##..##", "##..##", "..####"]),
+		Stamp::from_rows(
+			"Winkel",
+			&["##....", "..##..", "....##", "..##..", "##...."]
+		),
+	]
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tiling::{TilingFormat, Vertex};
+
+	use super::*;
+
+	const TRANSFORMS: [Transform; 3] = [
+		Transform::MirrorHorizontal,
+		Transform::MirrorVertical,
+		Transform::Rotate180
+	];
+
+	/// A pattern where neighboring tiles have different colors.
+	fn pattern(tiling: &Tiling) -> Vec<TileColor> {
+		(0..tiling.num_tiles())
+			.map(|i| {
+				if (i + i / 7) % 2 == 0 {
+					TileColor::Primary
+				} else {
+					TileColor::Secondary
+				}
+			})
+			.collect()
+	}
+
+	/// Stamps of every phase, with and without transparent cells.
+	fn stamps() -> Vec<Stamp> {
+		let tiling = Tiling::load(TilingFormat::F10X10);
+		let colors = pattern(&tiling);
+		let mut stamps = builtin_stamps();
+		for y in 0..PATTERN_HEIGHT {
+			let selection = Selection {
+				start: (1, y),
+				end: (4, y + 2)
+			};
+			stamps.push(Stamp::capture(String::new(), &tiling, &colors, selection));
+		}
+		stamps
+	}
+
+	fn same_vertices(a: &[Vertex], b: &[Vertex]) -> bool {
+		a.len() == b.len()
+			&& a.iter().all(|v| {
+				b.iter()
+					.any(|w| (v.x - w.x).abs() < 1e-4 && (v.y - w.y).abs() < 1e-4)
+			})
+	}
+
+	#[test]
+	fn snaps_to_rows_of_the_phase() {
+		let mut stamp = builtin_stamps().remove(0);
+		assert_eq!(
+			[0, 1, 2, 3, 4, 5].map(|row| stamp.snap_row(row)),
+			[0, 0, 0, 5, 5, 5]
+		);
+		stamp.phase = 2;
+		assert_eq!(
+			[0, 1, 2, 4, 5, 7].map(|row| stamp.snap_row(row)),
+			[2, 2, 2, 2, 7, 7]
+		);
+	}
+
+	#[test]
+	fn places_captured_stamps_where_they_keep_their_shapes() {
+		let tiling = Tiling::load(TilingFormat::F10X10);
+		let colors = pattern(&tiling);
+		let stamp = Stamp::capture(
+			String::new(),
+			&tiling,
+			&colors,
+			Selection {
+				start: (2, 7),
+				end: (5, 9)
+			}
+		);
+		assert_eq!(stamp.phase, 2);
+		// One row above the captured region, the stamp moves down into it.
+		let placements = stamp.placements(&tiling, tiling.square_tiles(2, 6)[0]);
+		assert_eq!(placements.len(), 2 * 4 * 3);
+		for (i, color) in placements {
+			let (x, y, _) = tiling.tile_position(i);
+			assert!((2..6).contains(&x) && (7..10).contains(&y));
+			assert_eq!(color, colors[i]);
+		}
+	}
+
+	#[test]
+	fn restores_stamps_transformed_twice() {
+		for stamp in stamps() {
+			for transform in TRANSFORMS {
+				let transformed = stamp.transformed(transform);
+				assert_eq!(
+					transformed.transformed(transform),
+					stamp,
+					"{transform:?} of {}",
+					stamp.name
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn mirrors_rows_into_matching_phases() {
+		for stamp in stamps() {
+			let horizontal = stamp.transformed(Transform::MirrorHorizontal);
+			assert_eq!(horizontal.phase, stamp.phase);
+			for transform in [Transform::MirrorVertical, Transform::Rotate180] {
+				let transformed = stamp.transformed(transform);
+				assert_eq!(
+					(stamp.phase + transformed.phase + stamp.height) % PATTERN_HEIGHT,
+					0
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn rotates_trapezoids_exactly() {
+		let tiling = Tiling::load(TilingFormat::F10X10);
+		for stamp in stamps() {
+			let rotated = stamp.transformed(Transform::Rotate180);
+			let anchor = |stamp: &Stamp| tiling.square_tiles(0, stamp.phase)[0];
+			let placed = stamp.placements(&tiling, anchor(&stamp));
+			let placed_rotated = rotated.placements(&tiling, anchor(&rotated));
+			assert_eq!(placed.len(), placed_rotated.len());
+
+			let (width, height) = (stamp.width as f32, stamp.height as f32);
+			for (i, color) in placed {
+				let vertices: Vec<Vertex> = tiling
+					.tile(i)
+					.vertices()
+					.iter()
+					.map(|v| {
+						Vertex::new(
+							width - v.x,
+							height - (v.y - stamp.phase as f32) + rotated.phase as f32
+						)
+					})
+					.collect();
+				let (_, rotated_color) = placed_rotated
+					.iter()
+					.find(|(j, _)| same_vertices(tiling.tile(*j).vertices(), &vertices))
+					.unwrap_or_else(|| panic!("no tile matches tile {i} of {}", stamp.name));
+				assert_eq!(*rotated_color, color);
+			}
+		}
+	}
+}
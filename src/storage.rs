@@ -0,0 +1,45 @@
+use leptos::{leptos_dom::logging::console_error, window};
+use serde::{de::DeserializeOwned, Serialize};
+use web_sys::Storage;
+
+const KEY_PREFIX: &str = "goldbeck-pattern.";
+
+fn local_storage() -> Option<Storage> {
+	window().local_storage().ok().flatten()
+}
+
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+	let json = local_storage()?
+		.get_item(&format!("{KEY_PREFIX}{key}"))
+		.ok()
+		.flatten()?;
+	match serde_json::from_str(&json) {
+		Ok(value) => Some(value),
+		Err(err) => {
+			console_error(&format!("Failed to parse stored {key}: {err}"));
+			None
+		}
+	}
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+	let Some(storage) = local_storage() else {
+		console_error("Local storage is not available!");
+		return;
+	};
+	let json = serde_json::to_string(value).expect("Failed to serialize value for storage");
+	if storage
+		.set_item(&format!("{KEY_PREFIX}{key}"), &json)
+		.is_err()
+	{
+		console_error(&format!("Failed to store {key}"));
+	}
+}
+
+pub fn remove(key: &str) {
+	if let Some(storage) = local_storage() {
+		if storage.remove_item(&format!("{KEY_PREFIX}{key}")).is_err() {
+			console_error(&format!("Failed to remove stored {key}"));
+		}
+	}
+}
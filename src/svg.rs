@@ -0,0 +1,215 @@
+use std::fmt::Write;
+
+use crate::{
+	artwork::{Artwork, SEAM_STROKE_WIDTH},
+	components::pattern::TileColor,
+	export::ExportSize,
+	pattern_file::SVG_METADATA_ID
+};
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+/// The id of the `<pattern>` that holds one repetition of the tiling.
+const TILING_ID: &str = "Pattern__Tiling";
+
+/// Escapes text for XML content and attribute values.
+fn escape(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for ch in text.chars() {
+		match ch {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			'"' => escaped.push_str("&quot;"),
+			_ => escaped.push(ch)
+		}
+	}
+	escaped
+}
+
+/// Wraps `content` in an SVG document with a viewBox of the given width and
+/// height, sized as given. If given, `metadata` is embedded, so that a
+/// pattern can be imported again.
+pub fn write_document(
+	width: f32,
+	height: f32,
+	size: &ExportSize,
+	metadata: Option<&str>,
+	content: &str
+) -> String {
+	let (export_width, export_height) = size.svg_dimensions(width / height);
+	let mut svg = String::new();
+	write!(
+		svg,
+		r#"<svg xmlns="{SVG_NAMESPACE}" viewBox="0 0 {width} {height}" width="{export_width}" height="{export_height}">"#
+	)
+	.unwrap();
+	if let Some(metadata) = metadata {
+		write!(
+			svg,
+			r#"<metadata id="{SVG_METADATA_ID}">{}</metadata>"#,
+			escape(metadata)
+		)
+		.unwrap();
+	}
+	svg.push_str(content);
+	svg.push_str("</svg>");
+	svg
+}
+
+/// Writes the artwork as an SVG. Like the pattern on screen, one repetition
+/// of the tiling is defined as a `<pattern>` that fills the viewBox. A solid
+/// background behind it identifies the theme on import, where the tile colors
+/// alone can fit several themes.
+pub fn write_svg(artwork: &Artwork, size: &ExportSize, metadata: Option<&str>) -> String {
+	let tiling = artwork.tiling;
+	let palette = artwork.theme.palette();
+	let (rep_width, rep_height) = (tiling.viewport_width(), tiling.viewport_height());
+
+	let mut content = String::new();
+	write!(
+		content,
+		r#"<defs><pattern id="{TILING_ID}" x="0" y="0" width="{rep_width}" height="{rep_height}" patternUnits="userSpaceOnUse" viewBox="0 0 {rep_width} {rep_height}">"#
+	)
+	.unwrap();
+	for (i, shape) in tiling.iter_tiles().enumerate() {
+		let color = palette.tile_color(artwork.colors.get(i).copied().unwrap_or_default());
+		write!(
+			content,
+			r#"<polygon points="{}" fill="{color}" stroke="{color}" stroke-width="{SEAM_STROKE_WIDTH}"/>"#,
+			shape.svg_path()
+		)
+		.unwrap();
+	}
+	let (width, height) = (artwork.width(), artwork.height());
+	write!(
+		content,
+		r#"</pattern></defs><rect fill="{}" width="{width}" height="{height}"/><rect fill="url(#{TILING_ID})" width="{width}" height="{height}"/>"#,
+		palette.tile_color(TileColor::None)
+	)
+	.unwrap();
+
+	write_document(width, height, size, metadata, &content)
+}
+
+#[cfg(test)]
+mod tests {
+	use enum_iterator::all;
+	use roxmltree::Document;
+
+	use super::*;
+	use crate::{
+		document::PatternData,
+		export::LengthUnit,
+		pattern_file::{PatternFile, PatternInfo},
+		svg_import::{import_svg, ImportedSvg},
+		theme::Theme,
+		tiling::{Tiling, TilingFormat}
+	};
+
+	fn colors(tiling: &Tiling) -> Vec<TileColor> {
+		(0..tiling.num_tiles())
+			.map(|i| all::<TileColor>().nth(i % 3).unwrap())
+			.collect()
+	}
+
+	fn root_attributes(svg: &str) -> [String; 3] {
+		let document = Document::parse(svg).unwrap();
+		let root = document.root_element();
+		["viewBox", "width", "height"].map(|name| root.attribute(name).unwrap().to_string())
+	}
+
+	#[test]
+	fn sizes_document_in_pixels() {
+		let svg = write_document(10.0, 5.0, &ExportSize::default(), None, "");
+		assert_eq!(root_attributes(&svg), ["0 0 10 5", "2000", "1000"]);
+	}
+
+	#[test]
+	fn sizes_document_in_millimetres() {
+		let size = ExportSize {
+			unit: LengthUnit::Mm,
+			width: Some(200.0),
+			height: None,
+			dpi: 300
+		};
+		let svg = write_document(10.0, 5.0, &size, None, "");
+		assert_eq!(root_attributes(&svg), ["0 0 10 5", "200mm", "100mm"]);
+	}
+
+	#[test]
+	fn escapes_metadata() {
+		let metadata = r#"{"name": "<Tom & \"Jerry\">"}"#;
+		let svg = write_document(1.0, 1.0, &ExportSize::default(), Some(metadata), "");
+		assert!(svg.contains(r#"&lt;Tom &amp; \&quot;Jerry\&quot;&gt;"#));
+		let document = Document::parse(&svg).unwrap();
+		let node = document
+			.descendants()
+			.find(|node| node.attribute("id") == Some(SVG_METADATA_ID))
+			.unwrap();
+		assert_eq!(node.text(), Some(metadata));
+	}
+
+	#[test]
+	fn writes_one_polygon_per_tile() {
+		let tiling = Tiling::load(TilingFormat::F10X15);
+		let colors = colors(&tiling);
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme: Theme::Glisten,
+			reps_x: 3,
+			reps_y: 2
+		};
+		let svg = write_svg(&artwork, &ExportSize::default(), None);
+		let document = Document::parse(&svg).unwrap();
+		let polygons = document
+			.descendants()
+			.filter(|node| node.has_tag_name("polygon"))
+			.count();
+		assert_eq!(polygons, tiling.num_tiles());
+		assert_eq!(
+			document.root_element().attribute("viewBox"),
+			Some(format!("0 0 {} {}", artwork.width(), artwork.height()).as_str())
+		);
+	}
+
+	#[test]
+	fn round_trips_every_format_and_theme() {
+		for format in all::<TilingFormat>() {
+			let tiling = Tiling::load(format);
+			let colors = colors(&tiling);
+			for theme in all::<Theme>() {
+				let artwork = Artwork {
+					tiling: &tiling,
+					colors: &colors,
+					theme,
+					reps_x: 2,
+					reps_y: 1
+				};
+				let file = PatternFile::new(
+					PatternInfo {
+						name: "Test".to_string(),
+						author: String::new(),
+						created: String::new()
+					},
+					String::new(),
+					PatternData::from_colors(format, theme, colors.clone())
+				);
+
+				let svg = write_svg(&artwork, &ExportSize::default(), None);
+				let Ok(ImportedSvg::Reconstructed(pattern)) = import_svg(&svg) else {
+					panic!("Failed to reconstruct {format:?} in {theme:?}");
+				};
+				assert_eq!((pattern.format, pattern.theme), (format, theme));
+				assert_eq!(pattern.composite(), colors);
+
+				let svg = write_svg(&artwork, &ExportSize::default(), Some(&file.to_json()));
+				let Ok(ImportedSvg::Document(imported)) = import_svg(&svg) else {
+					panic!("Failed to read the metadata of {format:?} in {theme:?}");
+				};
+				assert_eq!(imported, file);
+			}
+		}
+	}
+}
@@ -0,0 +1,265 @@
+use std::{error::Error, fmt};
+
+use enum_iterator::all;
+use roxmltree::Document;
+
+use crate::{
+	components::pattern::TileColor,
+	document::PatternData,
+	pattern_file::{PatternFile, PatternFileError, SVG_METADATA_ID},
+	theme::{Rgb, Theme},
+	tiling::{Tiling, TilingFormat, Vertex}
+};
+
+/// How far the center of a polygon may be from the center of the tile it is
+/// matched to.
+const MAX_CENTER_DISTANCE: f32 = 0.1;
+
+#[derive(Debug)]
+pub enum SvgImportError {
+	Xml(roxmltree::Error),
+	Metadata(PatternFileError),
+	UnknownGeometry,
+	UnknownColors,
+	/// The fill colors fit several themes, which look the same for this
+	/// pattern; see [`import_svg_as`].
+	AmbiguousTheme(Vec<Theme>)
+}
+
+impl fmt::Display for SvgImportError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Xml(err) => write!(f, "Invalid SVG: {err}"),
+			Self::Metadata(err) => write!(f, "Invalid pattern metadata: {err}"),
+			Self::UnknownGeometry => write!(f, "SVG polygons don't match any tiling format"),
+			Self::UnknownColors => write!(f, "SVG fill colors don't match any theme"),
+			Self::AmbiguousTheme(themes) => {
+				let names: Vec<&str> = themes.iter().map(|theme| theme.name()).collect();
+				write!(
+					f,
+					"SVG fill colors match several themes: {}",
+					names.join(", ")
+				)
+			}
+		}
+	}
+}
+
+impl Error for SvgImportError {}
+
+#[derive(Debug)]
+pub enum ImportedSvg {
+	/// The SVG carried the complete pattern file as metadata.
+	Document(PatternFile),
+	/// The SVG had no metadata, so the pattern was reconstructed from its
+	/// polygons.
+	Reconstructed(PatternData)
+}
+
+struct Polygon {
+	center: Vertex,
+	fill: Rgb
+}
+
+fn parse_polygon(points: &str, fill: &str) -> Option<Polygon> {
+	let vertices = points
+		.split_whitespace()
+		.map(|point| {
+			let (x, y) = point.split_once(',')?;
+			Some(Vertex::new(x.parse().ok()?, y.parse().ok()?))
+		})
+		.collect::<Option<Vec<Vertex>>>()?;
+	if vertices.is_empty() {
+		return None;
+	}
+	Some(Polygon {
+		center: Vertex::center_of(&vertices),
+		fill: Rgb::parse(fill)?
+	})
+}
+
+/// Finds the tile that each polygon covers, if there is a tiling with exactly
+/// one tile per polygon.
+fn match_tiles(polygons: &[Polygon]) -> Option<(TilingFormat, Vec<usize>)> {
+	let format = all::<TilingFormat>().find(|f| Tiling::load(*f).num_tiles() == polygons.len())?;
+	let tiling = Tiling::load(format);
+	let centers: Vec<Vertex> = tiling.iter_tiles().map(|shape| shape.center()).collect();
+	let indices = polygons
+		.iter()
+		.map(|polygon| {
+			let (index, distance) = centers
+				.iter()
+				.map(|c| (c.x - polygon.center.x).hypot(c.y - polygon.center.y))
+				.enumerate()
+				.min_by(|(_, a), (_, b)| a.total_cmp(b))?;
+			(distance <= MAX_CENTER_DISTANCE).then_some(index)
+		})
+		.collect::<Option<Vec<usize>>>()?;
+	Some((format, indices))
+}
+
+/// The tile colors of the polygons in `theme`, if its tile colors include all
+/// fills.
+fn match_colors(polygons: &[Polygon], theme: Theme) -> Option<Vec<TileColor>> {
+	let palette = theme.palette();
+	polygons
+		.iter()
+		.map(|polygon| all::<TileColor>().find(|color| palette.tile_color(*color) == polygon.fill))
+		.collect()
+}
+
+/// The theme of the polygons. Some themes share their colors in a different
+/// order, e.g. Icy is Bubbles with background and primary swapped, so if
+/// several themes match, the solid background behind the tiles decides.
+fn match_theme(svg: &Document, polygons: &[Polygon]) -> Result<Theme, SvgImportError> {
+	let mut themes: Vec<Theme> = all::<Theme>()
+		.filter(|theme| match_colors(polygons, *theme).is_some())
+		.collect();
+	if themes.len() > 1 {
+		let background = svg
+			.descendants()
+			.filter(|node| node.has_tag_name("rect"))
+			.find_map(|node| Rgb::parse(node.attribute("fill")?));
+		if let Some(background) = background {
+			themes.retain(|theme| theme.palette().tile_color(TileColor::None) == background);
+		}
+	}
+	match themes[..] {
+		[] => Err(SvgImportError::UnknownColors),
+		[theme] => Ok(theme),
+		_ => Err(SvgImportError::AmbiguousTheme(themes))
+	}
+}
+
+fn reconstruct(svg: &Document, theme: Option<Theme>) -> Result<PatternData, SvgImportError> {
+	let polygons: Vec<Polygon> = svg
+		.descendants()
+		.filter(|node| node.has_tag_name("polygon"))
+		.filter_map(|node| parse_polygon(node.attribute("points")?, node.attribute("fill")?))
+		.collect();
+	let (format, indices) = match_tiles(&polygons).ok_or(SvgImportError::UnknownGeometry)?;
+	let theme = match theme {
+		Some(theme) => theme,
+		None => match_theme(svg, &polygons)?
+	};
+	let polygon_colors = match_colors(&polygons, theme).ok_or(SvgImportError::UnknownColors)?;
+
+	let mut colors = vec![TileColor::None; indices.len()];
+	for (index, color) in indices.into_iter().zip(polygon_colors) {
+		colors[index] = color;
+	}
+	Ok(PatternData::from_colors(format, theme, colors))
+}
+
+/// Reads a pattern from an SVG, preferably from the metadata that
+/// [`write_artwork`](crate::export::write_artwork) embeds, or else from the
+/// polygons that older exports consist of.
+pub fn import_svg(svg: &str) -> Result<ImportedSvg, SvgImportError> {
+	import(svg, None)
+}
+
+/// Like [`import_svg`], but polygons are read in the colors of `theme`, as
+/// chosen by the user if [`SvgImportError::AmbiguousTheme`] occurred.
+pub fn import_svg_as(svg: &str, theme: Theme) -> Result<ImportedSvg, SvgImportError> {
+	import(svg, Some(theme))
+}
+
+fn import(svg: &str, theme: Option<Theme>) -> Result<ImportedSvg, SvgImportError> {
+	let document = Document::parse(svg).map_err(SvgImportError::Xml)?;
+	let metadata = document.descendants().find(|node| {
+		node.has_tag_name("metadata") && node.attribute("id") == Some(SVG_METADATA_ID)
+	});
+	match metadata.and_then(|node| node.text()) {
+		Some(json) => PatternFile::from_json(json)
+			.map(ImportedSvg::Document)
+			.map_err(SvgImportError::Metadata),
+		None => reconstruct(&document, theme).map(ImportedSvg::Reconstructed)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fmt::Write;
+
+	use super::*;
+	use crate::{artwork::Artwork, export::ExportSize, svg::write_svg};
+
+	fn colors(tiling: &Tiling) -> Vec<TileColor> {
+		(0..tiling.num_tiles())
+			.map(|i| all::<TileColor>().nth(i % 3).unwrap())
+			.collect()
+	}
+
+	fn reconstructed(svg: &str) -> PatternData {
+		match import_svg(svg).unwrap() {
+			ImportedSvg::Reconstructed(pattern) => pattern,
+			ImportedSvg::Document(_) => panic!("SVG without metadata was read as a document")
+		}
+	}
+
+	#[test]
+	fn reconstructs_every_theme() {
+		let tiling = Tiling::load(TilingFormat::F10X10);
+		let colors = colors(&tiling);
+		for theme in all::<Theme>() {
+			let artwork = Artwork {
+				tiling: &tiling,
+				colors: &colors,
+				theme,
+				reps_x: 1,
+				reps_y: 1
+			};
+			let pattern = reconstructed(&write_svg(&artwork, &ExportSize::default(), None));
+			assert_eq!(pattern.theme, theme);
+			assert_eq!(pattern.format, TilingFormat::F10X10);
+			assert_eq!(pattern.composite(), colors);
+		}
+	}
+
+	/// An SVG like older exports, with a polygon per tile and no background.
+	fn legacy_svg(tiling: &Tiling, fill: impl Fn(usize) -> Rgb) -> String {
+		let mut svg = String::from(r#"<svg xmlns="http://www.w3.org/2000/svg">"#);
+		for (i, shape) in tiling.iter_tiles().enumerate() {
+			write!(
+				svg,
+				r#"<polygon points="{}" fill="{}"/>"#,
+				shape.svg_path(),
+				fill(i)
+			)
+			.unwrap();
+		}
+		svg.push_str("</svg>");
+		svg
+	}
+
+	/// Without a background, Bubbles and Icy, which share their colors, can't
+	/// be told apart.
+	#[test]
+	fn reports_ambiguous_theme_without_background() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let colors = colors(&tiling);
+		let palette = Theme::Icy.palette();
+		let svg = legacy_svg(&tiling, |i| palette.tile_color(colors[i]));
+
+		match import_svg(&svg) {
+			Err(SvgImportError::AmbiguousTheme(themes)) => {
+				assert_eq!(themes, [Theme::Bubbles, Theme::Icy])
+			}
+			result => panic!("Expected an ambiguous theme, got {result:?}")
+		}
+		let Ok(ImportedSvg::Reconstructed(pattern)) = import_svg_as(&svg, Theme::Icy) else {
+			panic!("Failed to import with a chosen theme");
+		};
+		assert_eq!(pattern.theme, Theme::Icy);
+		assert_eq!(pattern.composite(), colors);
+	}
+
+	#[test]
+	fn rejects_unknown_colors() {
+		let svg = legacy_svg(&Tiling::load(TilingFormat::F5X5), |_| Rgb(255, 0, 0));
+		assert!(matches!(
+			import_svg(&svg),
+			Err(SvgImportError::UnknownColors)
+		));
+	}
+}
@@ -0,0 +1,364 @@
+use std::fmt::Write;
+
+use crate::{
+	artwork::{Artwork, SEAM_STROKE_WIDTH},
+	components::pattern::TileColor,
+	export::{ExportSize, OutputFormat},
+	lettering::{text_pixels, text_width, GLYPH_HEIGHT},
+	svg::write_document,
+	theme::{Rgb, ThemeColor},
+	tiling::Vertex
+};
+
+/// The formats a template can be written in. Others export the pattern as
+/// usual.
+pub const TEMPLATE_FORMATS: [OutputFormat; 3] =
+	[OutputFormat::Svg, OutputFormat::Pdf, OutputFormat::Png];
+
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+/// The space left for the row and column numbers, in grid squares.
+const NUMBERING_MARGIN: f32 = 1.0;
+
+/// The size of a pixel of the numbers in grid squares, so that three digits
+/// fit into a square.
+const NUMBER_PIXEL_SIZE: f32 = 0.08;
+
+/// Settings for exporting the construction grid instead of the pattern, as a
+/// coloring sheet or a drawing for fabrication.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemplateOptions {
+	pub enabled: bool,
+	/// Whether the colored tiles are filled in; otherwise the grid is empty.
+	pub filled: bool,
+	/// Width of the lines in millimetres at the export size.
+	pub line_width: f32,
+	pub line_color: Rgb,
+	/// Numbers the rows and columns of squares along the top and left edge.
+	pub numbering: bool
+}
+
+impl Default for TemplateOptions {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			filled: false,
+			line_width: 0.5,
+			line_color: ThemeColor::Carbon.rgb(),
+			numbering: true
+		}
+	}
+}
+
+fn line(content: &mut String, from: Vertex, to: Vertex) {
+	write!(
+		content,
+		r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+		from.x, from.y, to.x, to.y
+	)
+	.unwrap();
+}
+
+/// Writes `text` in the bitmap font, centered on `center`, as subpaths of a
+/// path. Unlike SVG text, this renders the same everywhere, without fonts.
+fn number(path: &mut String, text: &str, center: Vertex) {
+	let left = center.x - text_width(text) as f32 * NUMBER_PIXEL_SIZE / 2.0;
+	let top = center.y - GLYPH_HEIGHT as f32 * NUMBER_PIXEL_SIZE / 2.0;
+	for (col, row) in text_pixels(text) {
+		write!(
+			path,
+			"M{} {}h{NUMBER_PIXEL_SIZE}v{NUMBER_PIXEL_SIZE}h-{NUMBER_PIXEL_SIZE}z",
+			left + col as f32 * NUMBER_PIXEL_SIZE,
+			top + row as f32 * NUMBER_PIXEL_SIZE
+		)
+		.unwrap();
+	}
+}
+
+/// Writes the grid of the artwork as an SVG, with the lines that
+/// [`Tiling::iter_lines`](crate::tiling::Tiling::iter_lines) yields in every
+/// repetition and an outline around it.
+pub fn write_template(
+	artwork: &Artwork,
+	options: &TemplateOptions,
+	size: &ExportSize,
+	metadata: Option<&str>
+) -> String {
+	let tiling = artwork.tiling;
+	let (width, height) = (artwork.width(), artwork.height());
+	let (rep_width, rep_height) = (tiling.viewport_width(), tiling.viewport_height());
+
+	// The line width is given at the export size, so it depends on the scale
+	// of the document, which in turn depends on the margin.
+	let stroke_width = |margin: f32| {
+		let (doc_width, doc_height) = (width + 2.0 * margin, height + 2.0 * margin);
+		let (points_x, points_y) = size.points(doc_width / doc_height);
+		let scale = (points_x / doc_width).min(points_y / doc_height);
+		options.line_width * POINTS_PER_MM / scale
+	};
+	// Without numbers, leave room for the outline that is centered on the edge.
+	let margin = if options.numbering {
+		NUMBERING_MARGIN
+	} else {
+		stroke_width(0.0) / 2.0
+	};
+	let stroke_width = stroke_width(margin);
+	let color = options.line_color;
+
+	let mut content = String::new();
+	write!(content, r#"<g transform="translate({margin} {margin})">"#).unwrap();
+	if options.filled {
+		let palette = artwork.theme.palette();
+		for (tile_color, vertices) in artwork.tiles() {
+			if tile_color == TileColor::None {
+				continue;
+			}
+			let fill = palette.tile_color(tile_color);
+			let points: Vec<String> = vertices.iter().map(Vertex::svg_point).collect();
+			write!(
+				content,
+				r#"<polygon points="{}" fill="{fill}" stroke="{fill}" stroke-width="{SEAM_STROKE_WIDTH}"/>"#,
+				points.join(" ")
+			)
+			.unwrap();
+		}
+	}
+
+	write!(
+		content,
+		r#"<defs><clipPath id="Template__grid"><rect width="{width}" height="{height}"/></clipPath></defs><g clip-path="url(#Template__grid)" stroke="{color}" stroke-width="{stroke_width}" stroke-linecap="square">"#
+	)
+	.unwrap();
+	for rep_y in 0..artwork.reps_y {
+		for rep_x in 0..artwork.reps_x {
+			let (offset_x, offset_y) = (rep_x as f32 * rep_width, rep_y as f32 * rep_height);
+			for [from, to] in tiling.iter_lines() {
+				line(
+					&mut content,
+					Vertex::new(from.x + offset_x, from.y + offset_y),
+					Vertex::new(to.x + offset_x, to.y + offset_y)
+				);
+			}
+		}
+	}
+	// The lines of a tiling leave out its edges, which lie between the
+	// repetitions here.
+	for rep_x in 1..artwork.reps_x {
+		let x = rep_x as f32 * rep_width;
+		line(&mut content, Vertex::new(x, 0.0), Vertex::new(x, height));
+	}
+	for rep_y in 1..artwork.reps_y {
+		let y = rep_y as f32 * rep_height;
+		line(&mut content, Vertex::new(0.0, y), Vertex::new(width, y));
+	}
+	content.push_str("</g>");
+	write!(
+		content,
+		r#"<rect width="{width}" height="{height}" fill="none" stroke="{color}" stroke-width="{stroke_width}"/>"#
+	)
+	.unwrap();
+
+	if options.numbering {
+		let mut path = String::new();
+		for col in 0..width.round() as usize {
+			let center = Vertex::new(col as f32 + 0.5, -NUMBERING_MARGIN / 2.0);
+			number(&mut path, &(col + 1).to_string(), center);
+		}
+		for row in 0..height.round() as usize {
+			let center = Vertex::new(-NUMBERING_MARGIN / 2.0, row as f32 + 0.5);
+			number(&mut path, &(row + 1).to_string(), center);
+		}
+		write!(content, r#"<path d="{path}" fill="{color}"/>"#).unwrap();
+	}
+	content.push_str("</g>");
+
+	write_document(
+		width + 2.0 * margin,
+		height + 2.0 * margin,
+		size,
+		metadata,
+		&content
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use roxmltree::Document;
+
+	use crate::{
+		export::LengthUnit,
+		lettering::text_pixels,
+		raster::{render_png, RasterOptions},
+		theme::Theme,
+		tiling::{Tiling, TilingFormat}
+	};
+
+	use super::*;
+
+	fn template(
+		format: TilingFormat,
+		options: &TemplateOptions,
+		size: &ExportSize,
+		reps: (usize, usize)
+	) -> String {
+		let tiling = Tiling::load(format);
+		let colors = vec![TileColor::Primary; tiling.num_tiles()];
+		let artwork = Artwork {
+			tiling: &tiling,
+			colors: &colors,
+			theme: Theme::Bubbles,
+			reps_x: reps.0,
+			reps_y: reps.1
+		};
+		write_template(&artwork, options, size, None)
+	}
+
+	fn mm(width: f32) -> ExportSize {
+		ExportSize {
+			unit: LengthUnit::Mm,
+			width: Some(width),
+			height: None,
+			dpi: 300
+		}
+	}
+
+	#[test]
+	fn draws_the_lines_of_every_repetition() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let lines_per_rep = tiling.iter_lines().count();
+		for (reps_x, reps_y) in [(1, 1), (3, 1), (2, 2)] {
+			let svg = template(
+				TilingFormat::F5X5,
+				&TemplateOptions::default(),
+				&mm(100.0),
+				(reps_x, reps_y)
+			);
+			let document = Document::parse(&svg).unwrap();
+			let lines = document
+				.descendants()
+				.filter(|node| node.has_tag_name("line"))
+				.count();
+			let seams = reps_x - 1 + reps_y - 1;
+			assert_eq!(lines, reps_x * reps_y * lines_per_rep + seams);
+		}
+	}
+
+	#[test]
+	fn keeps_the_line_width_at_the_export_size() {
+		for numbering in [true, false] {
+			for width in [100.0, 400.0] {
+				let options = TemplateOptions {
+					line_width: 0.7,
+					numbering,
+					..TemplateOptions::default()
+				};
+				let svg = template(TilingFormat::F10X15, &options, &mm(width), (2, 1));
+				let document = Document::parse(&svg).unwrap();
+				let root = document.root_element();
+				let view_box: Vec<f32> = root
+					.attribute("viewBox")
+					.unwrap()
+					.split(' ')
+					.map(|n| n.parse().unwrap())
+					.collect();
+				assert_eq!(root.attribute("width").unwrap(), format!("{width}mm"));
+				let mm_per_unit = width / view_box[2];
+				let grid = document
+					.descendants()
+					.find(|node| node.attribute("clip-path").is_some())
+					.unwrap();
+				let stroke_width: f32 = grid.attribute("stroke-width").unwrap().parse().unwrap();
+				assert!(
+					(stroke_width * mm_per_unit - 0.7).abs() < 1e-4,
+					"{} mm",
+					stroke_width * mm_per_unit
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn numbers_rows_and_columns() {
+		let tiling = Tiling::load(TilingFormat::F5X5);
+		let (reps_x, reps_y) = (3, 2);
+		let columns = (tiling.viewport_width() * reps_x as f32).round() as usize;
+		let rows = (tiling.viewport_height() * reps_y as f32).round() as usize;
+		let pixels: usize = (1..=columns)
+			.chain(1..=rows)
+			.map(|n| text_pixels(&n.to_string()).count())
+			.sum();
+
+		let svg = template(
+			TilingFormat::F5X5,
+			&TemplateOptions::default(),
+			&mm(100.0),
+			(reps_x, reps_y)
+		);
+		let document = Document::parse(&svg).unwrap();
+		let path = document
+			.descendants()
+			.find(|node| node.has_tag_name("path"))
+			.unwrap();
+		assert_eq!(path.attribute("d").unwrap().matches('M').count(), pixels);
+
+		let options = TemplateOptions {
+			numbering: false,
+			..TemplateOptions::default()
+		};
+		let svg = template(TilingFormat::F5X5, &options, &mm(100.0), (reps_x, reps_y));
+		assert!(!svg.contains("<path"));
+	}
+
+	#[test]
+	fn clips_the_grid_in_png() {
+		let size = ExportSize {
+			unit: LengthUnit::Px,
+			width: Some(400.0),
+			height: None,
+			dpi: 96
+		};
+		// Some diagonals of this tiling run past its edges.
+		let svg = template(
+			TilingFormat::F15X15,
+			&TemplateOptions::default(),
+			&size,
+			(1, 1)
+		);
+		let document = Document::parse(&svg).unwrap();
+		let view_box: Vec<f32> = document
+			.root_element()
+			.attribute("viewBox")
+			.unwrap()
+			.split(' ')
+			.map(|n| n.parse().unwrap())
+			.collect();
+		let (doc_width, doc_height) = (view_box[2], view_box[3]);
+		let png = render_png(&svg, &size, &RasterOptions::default()).unwrap();
+		let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+		let mut rgba = vec![0; reader.output_buffer_size()];
+		let info = reader.next_frame(&mut rgba).unwrap();
+		let (width, height) = (info.width as usize, info.height as usize);
+		let px_per_unit = width as f32 / doc_width;
+		assert!((height as f32 - doc_height * px_per_unit).abs() <= 1.0);
+
+		// The right and bottom margins have no numbers, and the lines that
+		// cross the edge are clipped, so nothing is drawn there beyond the
+		// outline.
+		let outline = NUMBERING_MARGIN * px_per_unit;
+		let inner = |x: usize, y: usize| {
+			(x as f32) < width as f32 - outline + 4.0 && (y as f32) < height as f32 - outline + 4.0
+		};
+		let mut inside = 0;
+		for y in 0..height {
+			for x in 0..width {
+				let alpha = rgba[(y * width + x) * 4 + 3];
+				if inner(x, y) {
+					inside += usize::from(alpha > 0);
+				} else {
+					assert_eq!(alpha, 0, "pixel ({x}, {y})");
+				}
+			}
+		}
+		assert!(inside > 0);
+	}
+}
@@ -1,10 +1,16 @@
+use std::fmt;
+
 use leptos::{
-	component, create_effect, create_rw_signal, provide_context, Children, IntoView, RwSignal,
+	component, create_effect, create_rw_signal, provide_context, Children, IntoView, RwSignal
 };
 
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
 use web_sys::{window, CssStyleDeclaration};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use crate::components::pattern::TileColor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Sequence)]
 #[repr(u8)]
 pub enum Theme {
 	Bubbles,
@@ -22,6 +28,33 @@ impl Theme {
 			Self::Noble => "noble"
 		}
 	}
+
+	/// The name shown to users.
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::Bubbles => "Bubbles",
+			Self::Icy => "Icy",
+			Self::Glisten => "Glisten",
+			Self::Noble => "Noble"
+		}
+	}
+
+	pub fn palette(self) -> Palette {
+		use ThemeColor::*;
+		let (background, primary, secondary, highlight) = match self {
+			Self::Bubbles => (White, Carbon, Blue, Gold),
+			Self::Icy => (Carbon, White, Blue, Gold),
+			Self::Glisten => (White, Carbon, Gold, Blue),
+			Self::Noble => (Carbon, Blue, Gold, White)
+		};
+		Palette {
+			background,
+			primary,
+			secondary,
+			misc: Aluminium,
+			highlight
+		}
+	}
 }
 
 impl Default for Theme {
@@ -30,6 +63,109 @@ impl Default for Theme {
 	}
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+	/// Parses colors in the `rgb(r, g, b)` and `#rrggbb` notations.
+	pub fn parse(value: &str) -> Option<Self> {
+		let value = value.trim();
+		if let Some(hex) = value.strip_prefix('#') {
+			if hex.len() != 6 {
+				return None;
+			}
+			let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+			return Some(Self(channel(0)?, channel(2)?, channel(4)?));
+		}
+		let mut channels = value
+			.strip_prefix("rgb(")?
+			.strip_suffix(')')?
+			.split(',')
+			.map(|channel| channel.trim().parse::<u8>().ok());
+		let color = Self(channels.next()??, channels.next()??, channels.next()??);
+		channels.next().is_none().then_some(color)
+	}
+
+	/// The color in `#rrggbb` notation, as color inputs expect it.
+	pub fn hex(&self) -> String {
+		format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+	}
+}
+
+impl fmt::Display for Rgb {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "rgb({}, {}, {})", self.0, self.1, self.2)
+	}
+}
+
+/// The brand colors that the themes are made of.
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Sequence,
+)]
+#[repr(u8)]
+pub enum ThemeColor {
+	Carbon,
+	Blue,
+	White,
+	Gold,
+	Aluminium
+}
+
+impl ThemeColor {
+	pub fn rgb(self) -> Rgb {
+		match self {
+			Self::Carbon => Rgb(43, 50, 58),
+			Self::Blue => Rgb(62, 186, 236),
+			Self::White => Rgb(255, 255, 255),
+			Self::Gold => Rgb(193, 163, 104),
+			Self::Aluminium => Rgb(184, 187, 190)
+		}
+	}
+
+	/// The name of the color, e.g. for print swatches.
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::Carbon => "Carbon",
+			Self::Blue => "Blau",
+			Self::White => "Weiß",
+			Self::Gold => "Gold",
+			Self::Aluminium => "Aluminium"
+		}
+	}
+}
+
+/// The colors of a theme as defined in `styles.css`, for when there is no
+/// stylesheet to read them from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+	pub background: ThemeColor,
+	pub primary: ThemeColor,
+	pub secondary: ThemeColor,
+	pub misc: ThemeColor,
+	pub highlight: ThemeColor
+}
+
+impl Palette {
+	/// The theme color a tile is filled with; empty tiles show the background.
+	pub fn tile_theme_color(&self, color: TileColor) -> ThemeColor {
+		match color {
+			TileColor::Primary => self.primary,
+			TileColor::Secondary => self.secondary,
+			TileColor::None => self.background
+		}
+	}
+
+	/// The color a tile is filled with.
+	pub fn tile_color(&self, color: TileColor) -> Rgb {
+		self.tile_theme_color(color).rgb()
+	}
+
+	/// The name of the color a tile is filled with, e.g. for print swatches.
+	pub fn tile_color_name(&self, color: TileColor) -> &'static str {
+		self.tile_theme_color(color).name()
+	}
+}
+
 #[derive(Debug)]
 pub struct ThemeData {
 	pub background: String,
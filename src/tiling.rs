@@ -1,3 +1,6 @@
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Vertex {
 	pub x: f32,
@@ -13,6 +16,15 @@ impl Vertex {
 		Self { x, y }
 	}
 
+	/// The average of the given vertices.
+	pub fn center_of(vertices: &[Vertex]) -> Self {
+		let n = vertices.len() as f32;
+		Self::new(
+			vertices.iter().map(|v| v.x).sum::<f32>() / n,
+			vertices.iter().map(|v| v.y).sum::<f32>() / n
+		)
+	}
+
 	pub fn svg_point(&self) -> String {
 		format!("{},{}", svg_precision(self.x), svg_precision(self.y))
 	}
@@ -22,6 +34,14 @@ impl Vertex {
 pub struct Shape(&'static [Vertex]);
 
 impl Shape {
+	pub fn center(&self) -> Vertex {
+		Vertex::center_of(self.0)
+	}
+
+	pub fn vertices(&self) -> &'static [Vertex] {
+		self.0
+	}
+
 	pub fn svg_path(&self) -> String {
 		let mut path = String::new();
 		for vertex in self.0 {
@@ -35,7 +55,9 @@ impl Shape {
 }
 
 const PATTERN_SIZE_SQUARES: usize = 5;
-const PATTERN_HEIGHT: usize = PATTERN_SIZE_SQUARES;
+/// The rows after which the trapezoids repeat their shapes, as the diagonals
+/// cross one square every this many rows.
+pub const PATTERN_HEIGHT: usize = PATTERN_SIZE_SQUARES;
 const PATTERN_WIDTH: usize = 2 * PATTERN_HEIGHT;
 
 const fn num_tiles(reps_x: usize, reps_y: usize) -> usize {
@@ -141,7 +163,16 @@ const fn generate_lines<const NUM_LINES: usize>(reps_x: usize, reps_y: usize) ->
 	lines
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Whether two horizontal edges in the same column share a segment of
+/// positive length. Only the x coordinates are compared, since the edges of
+/// vertically adjacent rows may lie in different repetitions of the tiling.
+fn edges_overlap(left_a: Vertex, right_a: Vertex, left_b: Vertex, right_b: Vertex) -> bool {
+	let start = f32::max(left_a.x, left_b.x);
+	let end = f32::min(right_a.x, right_b.x);
+	end - start > 1e-4
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Sequence)]
 #[repr(u8)]
 pub enum TilingFormat {
 	F5X5,
@@ -150,6 +181,17 @@ pub enum TilingFormat {
 	F15X15
 }
 
+impl TilingFormat {
+	pub fn label(self) -> &'static str {
+		match self {
+			Self::F5X5 => "5×5",
+			Self::F10X10 => "10×10",
+			Self::F10X15 => "10×15",
+			Self::F15X15 => "15×15"
+		}
+	}
+}
+
 #[derive(Debug)]
 pub struct Tiling {
 	tiles: &'static [Tile],
@@ -236,10 +278,68 @@ impl Tiling {
 		self.tiles.iter().map(|points| Shape(points))
 	}
 
+	pub fn tile(&self, i: usize) -> Shape {
+		Shape(&self.tiles[i])
+	}
+
 	pub fn num_tiles(&self) -> usize {
 		self.tiles.len()
 	}
 
+	pub fn columns(&self) -> usize {
+		self.viewport_width as usize
+	}
+
+	pub fn rows(&self) -> usize {
+		self.viewport_height as usize
+	}
+
+	/// The indices of the left and right trapezoid that make up the square
+	/// at column `x` and row `y`.
+	pub fn square_tiles(&self, x: usize, y: usize) -> [usize; 2] {
+		let left = 2 * (y * self.columns() + x);
+		[left, left + 1]
+	}
+
+	/// The column, row and side (`false` for left, `true` for right) of the
+	/// tile with the given index.
+	pub fn tile_position(&self, i: usize) -> (usize, usize, bool) {
+		let square = i / 2;
+		(square % self.columns(), square / self.columns(), i % 2 == 1)
+	}
+
+	/// All tiles that share an edge with the tile at index `i`. Since the
+	/// tiling is repeated when rendered, edges wrap around the borders.
+	pub fn neighbors(&self, i: usize) -> Vec<usize> {
+		let (x, y, right) = self.tile_position(i);
+		let columns = self.columns();
+		let rows = self.rows();
+
+		let mut neighbors = vec![i ^ 1];
+		if right {
+			neighbors.push(self.square_tiles((x + 1) % columns, y)[0]);
+		} else {
+			neighbors.push(self.square_tiles((x + columns - 1) % columns, y)[1]);
+		}
+
+		let [top_left, top_right, bottom_right, bottom_left] = self.tiles[i];
+		let above = self.square_tiles(x, (y + rows - 1) % rows);
+		let below = self.square_tiles(x, (y + 1) % rows);
+		for j in above {
+			let [.., other_right, other_left] = self.tiles[j];
+			if edges_overlap(top_left, top_right, other_left, other_right) {
+				neighbors.push(j);
+			}
+		}
+		for j in below {
+			let [other_left, other_right, ..] = self.tiles[j];
+			if edges_overlap(bottom_left, bottom_right, other_left, other_right) {
+				neighbors.push(j);
+			}
+		}
+		neighbors
+	}
+
 	pub fn iter_lines(&self) -> impl Iterator<Item = &'static Line> {
 		self.lines.iter()
 	}
@@ -0,0 +1,261 @@
+use crc32fast::Hasher;
+use miniz_oxide::deflate::compress_to_vec;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+
+/// Version 2.0, the first with deflate and directories.
+const VERSION: u16 = 20;
+/// File names are encoded in UTF-8.
+const UTF8_FLAG: u16 = 1 << 11;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+/// 1980-01-01 00:00 in MS-DOS format, the earliest time a ZIP can hold.
+/// Entries carry no modification time, since none is available in the
+/// browser without a detour through JavaScript.
+const DOS_DATE: u16 = (1 << 5) | 1;
+const DOS_TIME: u16 = 0;
+
+const COMPRESSION_LEVEL: u8 = 6;
+
+struct Entry {
+	name: String,
+	method: u16,
+	crc: u32,
+	compressed_size: u32,
+	size: u32,
+	offset: u32
+}
+
+/// Writes a ZIP archive in memory. Entries are deflated, unless that doesn't
+/// make them smaller, as with PNGs and compressed PDFs.
+#[derive(Default)]
+pub struct ZipWriter {
+	data: Vec<u8>,
+	entries: Vec<Entry>
+}
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+	data.extend_from_slice(&value.to_le_bytes());
+}
+
+fn push_u32(data: &mut Vec<u8>, value: u32) {
+	data.extend_from_slice(&value.to_le_bytes());
+}
+
+impl ZipWriter {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a file; `name` is a path relative to the archive root, with `/`
+	/// as separator.
+	pub fn add_file(&mut self, name: &str, content: &[u8]) {
+		let mut hasher = Hasher::new();
+		hasher.update(content);
+		let crc = hasher.finalize();
+
+		let deflated = compress_to_vec(content, COMPRESSION_LEVEL);
+		let (method, stored) = if deflated.len() < content.len() {
+			(METHOD_DEFLATED, deflated.as_slice())
+		} else {
+			(METHOD_STORED, content)
+		};
+		let entry = Entry {
+			name: name.to_string(),
+			method,
+			crc,
+			compressed_size: stored.len() as u32,
+			size: content.len() as u32,
+			offset: self.data.len() as u32
+		};
+
+		let data = &mut self.data;
+		push_u32(data, LOCAL_HEADER_SIGNATURE);
+		push_u16(data, VERSION);
+		push_u16(data, UTF8_FLAG);
+		push_u16(data, entry.method);
+		push_u16(data, DOS_TIME);
+		push_u16(data, DOS_DATE);
+		push_u32(data, entry.crc);
+		push_u32(data, entry.compressed_size);
+		push_u32(data, entry.size);
+		push_u16(data, entry.name.len() as u16);
+		// Extra field length
+		push_u16(data, 0);
+		data.extend_from_slice(entry.name.as_bytes());
+		data.extend_from_slice(stored);
+
+		self.entries.push(entry);
+	}
+
+	/// Appends the central directory and returns the archive.
+	pub fn finish(mut self) -> Vec<u8> {
+		let directory_offset = self.data.len() as u32;
+		let data = &mut self.data;
+		for entry in &self.entries {
+			push_u32(data, CENTRAL_HEADER_SIGNATURE);
+			// Version made by, then version needed to extract
+			push_u16(data, VERSION);
+			push_u16(data, VERSION);
+			push_u16(data, UTF8_FLAG);
+			push_u16(data, entry.method);
+			push_u16(data, DOS_TIME);
+			push_u16(data, DOS_DATE);
+			push_u32(data, entry.crc);
+			push_u32(data, entry.compressed_size);
+			push_u32(data, entry.size);
+			push_u16(data, entry.name.len() as u16);
+			// Extra field and comment length, disk number, internal and
+			// external attributes
+			push_u16(data, 0);
+			push_u16(data, 0);
+			push_u16(data, 0);
+			push_u16(data, 0);
+			push_u32(data, 0);
+			push_u32(data, entry.offset);
+			data.extend_from_slice(entry.name.as_bytes());
+		}
+		let directory_size = data.len() as u32 - directory_offset;
+
+		push_u32(data, END_OF_DIRECTORY_SIGNATURE);
+		// Number of this disk, and of the disk the directory starts on
+		push_u16(data, 0);
+		push_u16(data, 0);
+		push_u16(data, self.entries.len() as u16);
+		push_u16(data, self.entries.len() as u16);
+		push_u32(data, directory_size);
+		push_u32(data, directory_offset);
+		// Comment length
+		push_u16(data, 0);
+		self.data
+	}
+}
+
+#[cfg(test)]
+pub mod tests {
+	use miniz_oxide::inflate::decompress_to_vec;
+
+	use super::*;
+
+	fn u16_at(data: &[u8], offset: usize) -> u16 {
+		u16::from_le_bytes([data[offset], data[offset + 1]])
+	}
+
+	fn u32_at(data: &[u8], offset: usize) -> u32 {
+		u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+	}
+
+	/// The CRC-32 of ZIP, computed bit by bit independently of `crc32fast`.
+	fn crc32(data: &[u8]) -> u32 {
+		let mut crc = !0u32;
+		for &byte in data {
+			crc ^= byte as u32;
+			for _ in 0..8 {
+				crc = if crc & 1 != 0 {
+					(crc >> 1) ^ 0xedb8_8320
+				} else {
+					crc >> 1
+				};
+			}
+		}
+		!crc
+	}
+
+	/// Reads the entries of an archive by its central directory, checking
+	/// that every local header agrees with it and that the CRCs and sizes
+	/// match the contents.
+	pub fn read_archive(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+		let end = data.len() - 22;
+		assert_eq!(u32_at(data, end), END_OF_DIRECTORY_SIGNATURE);
+		let count = u16_at(data, end + 10) as usize;
+		assert_eq!(u16_at(data, end + 8) as usize, count);
+		let directory_size = u32_at(data, end + 12) as usize;
+		let mut offset = u32_at(data, end + 16) as usize;
+		assert_eq!(offset + directory_size, end);
+		assert_eq!(u16_at(data, end + 20), 0, "comment length");
+
+		let mut entries = Vec::new();
+		for _ in 0..count {
+			let central = &data[offset..];
+			assert_eq!(u32_at(central, 0), CENTRAL_HEADER_SIGNATURE);
+			assert_eq!(u16_at(central, 8), UTF8_FLAG);
+			let name_len = u16_at(central, 28) as usize;
+			assert_eq!(u16_at(central, 30), 0, "extra field length");
+			assert_eq!(u16_at(central, 32), 0, "comment length");
+			let name = String::from_utf8(central[46..46 + name_len].to_vec()).unwrap();
+			let local_offset = u32_at(central, 42) as usize;
+
+			let local = &data[local_offset..];
+			assert_eq!(u32_at(local, 0), LOCAL_HEADER_SIGNATURE);
+			// Version needed to extract to the lengths of the name and the
+			// extra field are the same in both headers.
+			assert_eq!(local[4..30], central[6..32], "local header of {name}");
+			assert_eq!(&local[30..30 + name_len], name.as_bytes());
+			let method = u16_at(local, 8);
+			let crc = u32_at(local, 14);
+			let compressed_size = u32_at(local, 18) as usize;
+			let size = u32_at(local, 22) as usize;
+			let start = 30 + name_len;
+			let stored = &local[start..start + compressed_size];
+			let content = match method {
+				METHOD_STORED => stored.to_vec(),
+				METHOD_DEFLATED => decompress_to_vec(stored).unwrap(),
+				method => panic!("unknown method {method} of {name}")
+			};
+			assert_eq!(content.len(), size, "size of {name}");
+			assert_eq!(crc32(&content), crc, "CRC of {name}");
+
+			offset += 46 + name_len;
+			entries.push((name, content));
+		}
+		entries
+	}
+
+	#[test]
+	fn computes_known_crc() {
+		assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+	}
+
+	#[test]
+	fn writes_readable_archive() {
+		let text = "Goldbeck ".repeat(100);
+		let mut state = 0x2545_f491_u32;
+		let random: Vec<u8> = (0..1000)
+			.map(|_| {
+				state ^= state << 13;
+				state ^= state >> 17;
+				state ^= state << 5;
+				state as u8
+			})
+			.collect();
+		let mut zip = ZipWriter::new();
+		zip.add_file("Bubbles/Muster.svg", text.as_bytes());
+		zip.add_file("Ä/Größe.bin", &random);
+		zip.add_file("leer.txt", b"");
+		let data = zip.finish();
+
+		let entries = read_archive(&data);
+		assert_eq!(
+			entries,
+			[
+				("Bubbles/Muster.svg".to_string(), text.as_bytes().to_vec()),
+				("Ä/Größe.bin".to_string(), random),
+				("leer.txt".to_string(), Vec::new())
+			]
+		);
+		// Text compresses, random data is stored.
+		assert_eq!(u16_at(&data, 8), METHOD_DEFLATED);
+		assert!((u32_at(&data, 18) as usize) < text.len());
+		let second = 30 + "Bubbles/Muster.svg".len() + u32_at(&data, 18) as usize;
+		assert_eq!(u16_at(&data, second + 8), METHOD_STORED);
+	}
+
+	#[test]
+	fn writes_empty_archive() {
+		let data = ZipWriter::new().finish();
+		assert_eq!(data.len(), 22);
+		assert!(read_archive(&data).is_empty());
+	}
+}